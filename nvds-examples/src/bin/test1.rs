@@ -56,8 +56,8 @@ fn main() {
         .build()
         .unwrap();
 
-    source.nvds_parse_file_source(CONFIG_YML, "source");
-    streammux.nvds_parse_streammux(CONFIG_YML, "streammux");
+    source.nvds_parse_file_source(CONFIG_YML, "source").unwrap();
+    streammux.nvds_parse_streammux(CONFIG_YML, "streammux").unwrap();
     pgie.set_property("config-file-path", "dstest1_pgie_config.yml");
 
     pipeline
@@ -71,14 +71,14 @@ fn main() {
         .build()
         .unwrap();
 
-    source.nvds_parse_file_source(CONFIG_YML, "source");
-    streammux.nvds_parse_streammux(CONFIG_YML, "streammux");
+    source.nvds_parse_file_source(CONFIG_YML, "source").unwrap();
+    streammux.nvds_parse_streammux(CONFIG_YML, "streammux").unwrap();
     pgie.set_property("config-file-path", "dstest1_pgie_config.yml");
     sgie1.set_property("config-file-path", "dstest2_sgie1_config.yml");
     sgie2.set_property("config-file-path", "dstest2_sgie2_config.yml");
     sgie3.set_property("config-file-path", "dstest2_sgie3_config.yml");
 
-    nvtracker.nvds_parse_tracker(CONFIG_YML, "tracker");
+    nvtracker.nvds_parse_tracker(CONFIG_YML, "tracker").unwrap();
 
     pipeline
         .add_many(&[
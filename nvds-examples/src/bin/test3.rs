@@ -82,22 +82,22 @@ fn main() {
         .name("nvvideo-renderer")
         .build()
         .unwrap();
-    streammux.nvds_parse_streammux(CONFIG_YML, "streammux");
+    streammux.nvds_parse_streammux(CONFIG_YML, "streammux").unwrap();
     pgie.set_property("config-file-path", "dstest3_pgie_config.yml");
 
     let pgie_batch_size = pgie.property::<u32>("batch-size");
     if src_list.len() != pgie_batch_size as _ {
         pgie.set_property::<u32>("batch-size", src_list.len() as _);
     }
-    nvosd.nvds_parse_osd(CONFIG_YML, "osd");
+    nvosd.nvds_parse_osd(CONFIG_YML, "osd").unwrap();
 
     let tiler_rows = f64::sqrt(src_list.len() as _) as u32;
     let tiler_columns = f64::ceil(1.0 * src_list.len() as f64 / tiler_rows as f64) as u32;
     tiler.set_property("rows", tiler_rows);
     tiler.set_property("columns", tiler_columns);
 
-    tiler.nvds_parse_tiler(CONFIG_YML, "tiler");
-    sink.nvds_parse_egl_sink(CONFIG_YML, "sink");
+    tiler.nvds_parse_tiler(CONFIG_YML, "tiler").unwrap();
+    sink.nvds_parse_egl_sink(CONFIG_YML, "sink").unwrap();
 
     pipeline
         .add_many(&[
@@ -4,11 +4,13 @@ use gstreamer::glib::{GStr, GString};
 use gstreamer::prelude::*;
 use gstreamer::{PadProbeData, PadProbeReturn, PadProbeType};
 use nvidia_deepstream::meta::osd::{ColorParams, FontParamsBuilder, TextParamsBuilder};
-use nvidia_deepstream::meta::schema::EventMsgMetaBuilder;
-use nvidia_deepstream::meta::{
-    BaseMetaType, BatchMetaExt, BufferExt, DisplayMetaBuilder, MetaType, UserMeta,
+use nvidia_deepstream::meta::schema::{
+    EventMsgMeta, EventMsgMetaBuilder, PersonObjectBuilder, VehicleObjectBuilder,
 };
+use nvidia_deepstream::meta::{BatchMetaExt, BufferExt, DisplayMetaBuilder};
+use nvidia_deepstream::timestamp::{self, FrameClock, NtpSyncMode};
 use nvidia_deepstream::yaml::ElementNvdsYamlExt;
+use std::sync::Mutex;
 
 static CONFIG_YML: &str = "dstest4_config.yml";
 
@@ -80,13 +82,13 @@ fn main() {
         .build()
         .unwrap();
 
-    source.nvds_parse_file_source(CONFIG_YML, "source");
-    nvstreammux.nvds_parse_streammux(CONFIG_YML, "streammux");
+    source.nvds_parse_file_source(CONFIG_YML, "source").unwrap();
+    nvstreammux.nvds_parse_streammux(CONFIG_YML, "streammux").unwrap();
     pgie.set_property("config-file-path", "dstest4_pgie_config.yml");
     msgconv.set_property("config", "dstest4_msgconv_config.yml");
-    msgconv.nvds_parse_msgconv(CONFIG_YML, "msgconv");
-    msgbroker.nvds_parse_msgbroker(CONFIG_YML, "msgbroker");
-    sink.nvds_parse_egl_sink(CONFIG_YML, "sink");
+    msgconv.nvds_parse_msgconv(CONFIG_YML, "msgconv").unwrap();
+    msgbroker.nvds_parse_msgbroker(CONFIG_YML, "msgbroker").unwrap();
+    sink.nvds_parse_egl_sink(CONFIG_YML, "sink").unwrap();
 
     pipeline
         .add_many(&[
@@ -124,7 +126,11 @@ fn main() {
     tee_render_pad.link(&sink_pad).unwrap();
 
     let osd_sink_pad = nvosd.static_pad("sink").unwrap();
-    osd_sink_pad.add_probe(PadProbeType::BUFFER, |_, info| {
+    let clock = Mutex::new(FrameClock::new(
+        NtpSyncMode::from_streammux(&nvstreammux),
+        timestamp::now(),
+    ));
+    osd_sink_pad.add_probe(PadProbeType::BUFFER, move |_, info| {
         if let PadProbeData::Buffer(buf) = &info.data.as_ref().unwrap() {
             unsafe {
                 let mut vehicle_count: u32 = 0;
@@ -141,7 +147,7 @@ fn main() {
                                 };
                                 if is_first_object {
                                     let object_id = obj_meta.object_id().to_string();
-                                    let ts = chrono::Local::now().format("%+").to_string();
+                                    let ts = clock.lock().unwrap().frame_rfc3339(frame_meta);
                                     let msg_meta = EventMsgMetaBuilder::new()
                                         .sensor_id(0)
                                         .place_id(0)
@@ -150,36 +156,34 @@ fn main() {
                                         .object_id(object_id.as_str())
                                         .ts(ts.as_str());
 
-                                    let user_event_meta =
-                                        match obj_meta.class_id() {
-                                            PGIE_CLASS_ID_VEHICLE =>
-                                                UserMeta::new(batch_meta, MetaType::Base(BaseMetaType::EventMsgMeta),
-                                                              msg_meta
-                                                                  .build_with_ext_msg(Box::new(nvidia_deepstream::meta::schema::VehicleObjectBuilder::new()
-                                                                      .type_("sedan")
-                                                                      .color("blue")
-                                                                      .make("Bugatti")
-                                                                      .model("M")
-                                                                      .license("XX1234")
-                                                                      .region("CA")
-                                                                      .build()))),
-                                            PGIE_CLASS_ID_PERSON =>
-                                                UserMeta::new(batch_meta, MetaType::Base(BaseMetaType::EventMsgMeta),
-                                                              msg_meta
-                                                                  .build_with_ext_msg(Box::new(nvidia_deepstream::meta::schema::PersonObjectBuilder::new()
-                                                                      .age(45)
-                                                                      .cap("none")
-                                                                      .hair("black")
-                                                                      .gender("male")
-                                                                      .apparel("formal")
-                                                                      .build()))),
-                                            _ =>
-                                                UserMeta::new(batch_meta, MetaType::Base(BaseMetaType::EventMsgMeta),
-                                                              msg_meta.build()),
-                                        };
-                                    if let Some(user_event_meta) = user_event_meta {
-                                        frame_meta.add_user_meta(user_event_meta);
-                                    }
+                                    match obj_meta.class_id() {
+                                        PGIE_CLASS_ID_VEHICLE => EventMsgMeta::vehicle(
+                                            msg_meta,
+                                            VehicleObjectBuilder::new()
+                                                .type_("sedan")
+                                                .color("blue")
+                                                .make("Bugatti")
+                                                .model("M")
+                                                .license("XX1234")
+                                                .region("CA")
+                                                .build(),
+                                        )
+                                        .attach_to_frame(batch_meta, frame_meta),
+                                        PGIE_CLASS_ID_PERSON => EventMsgMeta::person(
+                                            msg_meta,
+                                            PersonObjectBuilder::new()
+                                                .age(45)
+                                                .cap("none")
+                                                .hair("black")
+                                                .gender("male")
+                                                .apparel("formal")
+                                                .build(),
+                                        )
+                                        .attach_to_frame(batch_meta, frame_meta),
+                                        _ => msg_meta
+                                            .build()
+                                            .attach_to_frame(batch_meta, frame_meta),
+                                    };
                                     is_first_object = false;
                                 }
                             }
@@ -18,6 +18,35 @@ fn main() {
         .canonicalize()
         .unwrap();
 
+    #[cfg(feature = "infer-custom")]
+    {
+        println!("cargo:rerun-if-env-changed=CUDA_PATH");
+        println!("cargo:rerun-if-changed=infer_custom.cpp");
+        let cuda_root = std::env::var("CUDA_PATH").unwrap_or("/usr/local/cuda".into());
+        cc::Build::new()
+            .cpp(true)
+            .file("infer_custom.cpp")
+            .include(&root_path)
+            .include(std::path::Path::new(&cuda_root).join("include"))
+            .includes(&pk.include_paths)
+            .compile("nvds-infer-custom");
+    }
+
+    #[cfg(feature = "infer-server-custom")]
+    {
+        println!("cargo:rerun-if-env-changed=CUDA_PATH");
+        println!("cargo:rerun-if-changed=infer_server_custom.cpp");
+        let cuda_root = std::env::var("CUDA_PATH").unwrap_or("/usr/local/cuda".into());
+        cc::Build::new()
+            .cpp(true)
+            .file("infer_server_custom.cpp")
+            .include(&root_path)
+            .include(root_path.join("nvdsinferserver"))
+            .include(std::path::Path::new(&cuda_root).join("include"))
+            .includes(&pk.include_paths)
+            .compile("nvds-infer-server-custom");
+    }
+
     let pk_video = pkg_config::Config::new()
         .probe("gstreamer-video-1.0")
         .into_iter()
@@ -0,0 +1,57 @@
+// C entry points over the C++ types of nvdsinfer_custom_impl.h, used by the custom
+// parsers exported from Rust.
+#include <cstring>
+#include <nvdsinfer_custom_impl.h>
+
+extern "C" {
+
+const NvDsInferLayerInfo *
+nvds_rs_layer_info_vector_data(const std::vector<NvDsInferLayerInfo> *v) {
+  return v->data();
+}
+
+size_t nvds_rs_layer_info_vector_size(const std::vector<NvDsInferLayerInfo> *v) {
+  return v->size();
+}
+
+unsigned int nvds_rs_detection_params_num_classes(
+    const NvDsInferParseDetectionParams *p) {
+  return p->numClassesConfigured;
+}
+
+const float *nvds_rs_detection_params_pre_cluster_thresholds(
+    const NvDsInferParseDetectionParams *p, size_t *len) {
+  *len = p->perClassPreclusterThreshold.size();
+  return p->perClassPreclusterThreshold.data();
+}
+
+const float *nvds_rs_detection_params_post_cluster_thresholds(
+    const NvDsInferParseDetectionParams *p, size_t *len) {
+  *len = p->perClassPostclusterThreshold.size();
+  return p->perClassPostclusterThreshold.data();
+}
+
+void nvds_rs_object_detection_info_vector_assign(
+    std::vector<NvDsInferObjectDetectionInfo> *v,
+    const NvDsInferObjectDetectionInfo *data, size_t len) {
+  v->assign(data, data + len);
+}
+
+// The label is duplicated with strdup as nvinfer releases it with free.
+void nvds_rs_attribute_vector_push(std::vector<NvDsInferAttribute> *v,
+                                   unsigned int attribute_index,
+                                   unsigned int attribute_value,
+                                   float attribute_confidence,
+                                   const char *attribute_label) {
+  NvDsInferAttribute attr;
+  attr.attributeIndex = attribute_index;
+  attr.attributeValue = attribute_value;
+  attr.attributeConfidence = attribute_confidence;
+  attr.attributeLabel = attribute_label ? strdup(attribute_label) : nullptr;
+  v->push_back(attr);
+}
+
+void nvds_rs_string_assign(std::string *s, const char *data, size_t len) {
+  s->assign(data, len);
+}
+}
@@ -0,0 +1,187 @@
+// IInferCustomProcessor of nvinferserver forwarding to a processor written in Rust,
+// and C entry points over the C++ types of infer_custom_process.h.
+#include <algorithm>
+#include <cstring>
+#include <infer_custom_process.h>
+
+using nvdsinferserver::IBatchArray;
+using nvdsinferserver::IBatchBuffer;
+using nvdsinferserver::IInferCustomProcessor;
+using nvdsinferserver::InferBufferDescription;
+using nvdsinferserver::InferMemType;
+using nvdsinferserver::IOptions;
+
+extern "C" {
+
+struct NvDsRsCustomProcessorVTable {
+  void (*drop)(void *processor);
+  int (*supported_input_mem_type)(void *processor);
+  bool (*require_infer_loop)(void *processor);
+  NvDsInferStatus (*extra_input_process)(void *processor,
+                                         IBatchBuffer *const *primary_inputs,
+                                         size_t num_primary_inputs,
+                                         IBatchBuffer *const *extra_inputs,
+                                         size_t num_extra_inputs,
+                                         const IOptions *options);
+  NvDsInferStatus (*inference_done)(void *processor, const IBatchArray *outputs,
+                                    const IOptions *options);
+  void (*notify_error)(void *processor, NvDsInferStatus status);
+};
+
+struct NvDsRsInferBufferDesc {
+  int mem_type;
+  int64_t device_id;
+  int data_type;
+  unsigned int num_dims;
+  int d[NVDSINFER_MAX_DIMS];
+  unsigned int num_elements;
+  unsigned int element_size;
+  const char *name;
+  size_t name_len;
+  bool is_input;
+};
+}
+
+namespace {
+
+class RustCustomProcessor : public IInferCustomProcessor {
+public:
+  RustCustomProcessor(void *processor, const NvDsRsCustomProcessorVTable *vtable)
+      : m_Processor(processor), m_VTable(*vtable) {}
+
+  ~RustCustomProcessor() override { m_VTable.drop(m_Processor); }
+
+  void supportInputMemType(InferMemType &type) override {
+    type = static_cast<InferMemType>(
+        m_VTable.supported_input_mem_type(m_Processor));
+  }
+
+  bool requireInferLoop() const override {
+    return m_VTable.require_infer_loop(m_Processor);
+  }
+
+  NvDsInferStatus
+  extraInputProcess(const std::vector<IBatchBuffer *> &primaryInputs,
+                    std::vector<IBatchBuffer *> &extraInputs,
+                    const IOptions *options) override {
+    return m_VTable.extra_input_process(m_Processor, primaryInputs.data(),
+                                        primaryInputs.size(), extraInputs.data(),
+                                        extraInputs.size(), options);
+  }
+
+  NvDsInferStatus inferenceDone(const IBatchArray *outputs,
+                                const IOptions *inOptions) override {
+    return m_VTable.inference_done(m_Processor, outputs, inOptions);
+  }
+
+  void notifyError(NvDsInferStatus status) override {
+    m_VTable.notify_error(m_Processor, status);
+  }
+
+private:
+  void *m_Processor;
+  NvDsRsCustomProcessorVTable m_VTable;
+};
+
+} // namespace
+
+extern "C" {
+
+// Takes the ownership of `processor`, released with `vtable->drop`.
+IInferCustomProcessor *
+nvds_rs_custom_processor_new(void *processor,
+                             const NvDsRsCustomProcessorVTable *vtable) {
+  return new RustCustomProcessor(processor, vtable);
+}
+
+void nvds_rs_batch_buffer_desc(const IBatchBuffer *buffer,
+                               NvDsRsInferBufferDesc *desc) {
+  const InferBufferDescription &d = buffer->getBufDesc();
+  desc->mem_type = static_cast<int>(d.memType);
+  desc->device_id = d.devId;
+  desc->data_type = static_cast<int>(d.dataType);
+  desc->num_dims = d.dims.numDims;
+  std::memcpy(desc->d, d.dims.d, sizeof(desc->d));
+  desc->num_elements = d.dims.numElements;
+  desc->element_size = d.elementSize;
+  desc->name = d.name.c_str();
+  desc->name_len = d.name.size();
+  desc->is_input = d.isInput;
+}
+
+uint32_t nvds_rs_batch_buffer_batch_size(const IBatchBuffer *buffer) {
+  return buffer->getBatchSize();
+}
+
+uint64_t nvds_rs_batch_buffer_total_bytes(const IBatchBuffer *buffer) {
+  return buffer->getTotalBytes();
+}
+
+void *nvds_rs_batch_buffer_ptr(const IBatchBuffer *buffer, uint32_t batch_idx) {
+  return buffer->getBufPtr(batch_idx);
+}
+
+uint32_t nvds_rs_batch_array_size(const IBatchArray *array) {
+  return array->getSize();
+}
+
+const IBatchBuffer *nvds_rs_batch_array_buffer(const IBatchArray *array,
+                                               uint32_t index) {
+  return array->getBuffer(index);
+}
+
+bool nvds_rs_options_has_value(const IOptions *options, const char *key) {
+  return options->hasValue(key);
+}
+
+NvDsInferStatus nvds_rs_options_get_int(const IOptions *options,
+                                        const char *key, int64_t *value) {
+  return options->getInt(key, *value);
+}
+
+NvDsInferStatus nvds_rs_options_get_uint(const IOptions *options,
+                                         const char *key, uint64_t *value) {
+  return options->getUInt(key, *value);
+}
+
+NvDsInferStatus nvds_rs_options_get_double(const IOptions *options,
+                                           const char *key, double *value) {
+  return options->getDouble(key, *value);
+}
+
+NvDsInferStatus nvds_rs_options_get_bool(const IOptions *options,
+                                         const char *key, bool *value) {
+  return options->getBool(key, *value);
+}
+
+// Copies at most `capacity` bytes and sets `len` to the length of the string.
+NvDsInferStatus nvds_rs_options_get_string(const IOptions *options,
+                                           const char *key, char *value,
+                                           size_t capacity, size_t *len) {
+  std::string s;
+  NvDsInferStatus status = options->getString(key, s);
+  if (status == NVDSINFER_SUCCESS) {
+    *len = s.size();
+    std::memcpy(value, s.data(), std::min(capacity, s.size()));
+  }
+  return status;
+}
+
+NvDsInferStatus nvds_rs_options_get_obj(const IOptions *options,
+                                        const char *key, void **value) {
+  return options->getObj(key, *value);
+}
+
+// Copies at most `capacity` values and sets `len` to the number of values.
+NvDsInferStatus nvds_rs_options_get_uint_array(const IOptions *options,
+                                               const char *key, uint64_t *values,
+                                               size_t capacity, size_t *len) {
+  std::vector<uint64_t> v;
+  NvDsInferStatus status = options->getValueArray(key, v);
+  if (status == NVDSINFER_SUCCESS) {
+    *len = v.size();
+    std::memcpy(values, v.data(), std::min(capacity, v.size()) * sizeof(uint64_t));
+  }
+  return status;
+}
+}
@@ -0,0 +1,63 @@
+//! Helpers of `infer_custom.cpp` for the C++ types of `nvdsinfer_custom_impl.h`.
+
+use super::{NvDsInferLayerInfo, NvDsInferObjectDetectionInfo};
+
+/// `std::vector<NvDsInferLayerInfo>`
+#[repr(C)]
+pub struct NvDsInferLayerInfoVector {
+    _private: [u8; 0],
+}
+
+/// `std::vector<NvDsInferObjectDetectionInfo>`
+#[repr(C)]
+pub struct NvDsInferObjectDetectionInfoVector {
+    _private: [u8; 0],
+}
+
+/// `std::vector<NvDsInferAttribute>`
+#[repr(C)]
+pub struct NvDsInferAttributeVector {
+    _private: [u8; 0],
+}
+
+/// `std::string`
+#[repr(C)]
+pub struct CppString {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct NvDsInferParseDetectionParams {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    pub fn nvds_rs_layer_info_vector_data(
+        v: *const NvDsInferLayerInfoVector,
+    ) -> *const NvDsInferLayerInfo;
+    pub fn nvds_rs_layer_info_vector_size(v: *const NvDsInferLayerInfoVector) -> usize;
+    pub fn nvds_rs_detection_params_num_classes(
+        p: *const NvDsInferParseDetectionParams,
+    ) -> std::os::raw::c_uint;
+    pub fn nvds_rs_detection_params_pre_cluster_thresholds(
+        p: *const NvDsInferParseDetectionParams,
+        len: *mut usize,
+    ) -> *const f32;
+    pub fn nvds_rs_detection_params_post_cluster_thresholds(
+        p: *const NvDsInferParseDetectionParams,
+        len: *mut usize,
+    ) -> *const f32;
+    pub fn nvds_rs_object_detection_info_vector_assign(
+        v: *mut NvDsInferObjectDetectionInfoVector,
+        data: *const NvDsInferObjectDetectionInfo,
+        len: usize,
+    );
+    pub fn nvds_rs_attribute_vector_push(
+        v: *mut NvDsInferAttributeVector,
+        attribute_index: std::os::raw::c_uint,
+        attribute_value: std::os::raw::c_uint,
+        attribute_confidence: f32,
+        attribute_label: *const std::os::raw::c_char,
+    );
+    pub fn nvds_rs_string_assign(s: *mut CppString, data: *const std::os::raw::c_char, len: usize);
+}
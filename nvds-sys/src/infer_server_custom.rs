@@ -0,0 +1,121 @@
+//! Helpers of `infer_server_custom.cpp` for the C++ types of `infer_custom_process.h`
+//! of nvinferserver.
+
+use super::{NvDsInferStatus, NVDSINFER_MAX_DIMS};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+/// `nvdsinferserver::IInferCustomProcessor`
+#[repr(C)]
+pub struct IInferCustomProcessor {
+    _private: [u8; 0],
+}
+
+/// `nvdsinferserver::IBatchBuffer`
+#[repr(C)]
+pub struct IBatchBuffer {
+    _private: [u8; 0],
+}
+
+/// `nvdsinferserver::IBatchArray`
+#[repr(C)]
+pub struct IBatchArray {
+    _private: [u8; 0],
+}
+
+/// `nvdsinferserver::IOptions`
+#[repr(C)]
+pub struct IOptions {
+    _private: [u8; 0],
+}
+
+/// Methods of `IInferCustomProcessor` implemented in Rust.
+#[repr(C)]
+pub struct NvDsRsCustomProcessorVTable {
+    pub drop: unsafe extern "C" fn(processor: *mut c_void),
+    pub supported_input_mem_type: unsafe extern "C" fn(processor: *mut c_void) -> c_int,
+    pub require_infer_loop: unsafe extern "C" fn(processor: *mut c_void) -> bool,
+    pub extra_input_process: unsafe extern "C" fn(
+        processor: *mut c_void,
+        primary_inputs: *const *mut IBatchBuffer,
+        num_primary_inputs: usize,
+        extra_inputs: *const *mut IBatchBuffer,
+        num_extra_inputs: usize,
+        options: *const IOptions,
+    ) -> NvDsInferStatus,
+    pub inference_done: unsafe extern "C" fn(
+        processor: *mut c_void,
+        outputs: *const IBatchArray,
+        options: *const IOptions,
+    ) -> NvDsInferStatus,
+    pub notify_error: unsafe extern "C" fn(processor: *mut c_void, status: NvDsInferStatus),
+}
+
+/// `nvdsinferserver::InferBufferDescription`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct NvDsRsInferBufferDesc {
+    pub mem_type: c_int,
+    pub device_id: i64,
+    pub data_type: c_int,
+    pub num_dims: c_uint,
+    pub d: [c_int; NVDSINFER_MAX_DIMS as usize],
+    pub num_elements: c_uint,
+    pub element_size: c_uint,
+    pub name: *const c_char,
+    pub name_len: usize,
+    pub is_input: bool,
+}
+
+extern "C" {
+    pub fn nvds_rs_custom_processor_new(
+        processor: *mut c_void,
+        vtable: *const NvDsRsCustomProcessorVTable,
+    ) -> *mut IInferCustomProcessor;
+    pub fn nvds_rs_batch_buffer_desc(buffer: *const IBatchBuffer, desc: *mut NvDsRsInferBufferDesc);
+    pub fn nvds_rs_batch_buffer_batch_size(buffer: *const IBatchBuffer) -> u32;
+    pub fn nvds_rs_batch_buffer_total_bytes(buffer: *const IBatchBuffer) -> u64;
+    pub fn nvds_rs_batch_buffer_ptr(buffer: *const IBatchBuffer, batch_idx: u32) -> *mut c_void;
+    pub fn nvds_rs_batch_array_size(array: *const IBatchArray) -> u32;
+    pub fn nvds_rs_batch_array_buffer(array: *const IBatchArray, index: u32)
+        -> *const IBatchBuffer;
+    pub fn nvds_rs_options_has_value(options: *const IOptions, key: *const c_char) -> bool;
+    pub fn nvds_rs_options_get_int(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut i64,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_uint(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut u64,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_double(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut f64,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_bool(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut bool,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_string(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut c_char,
+        capacity: usize,
+        len: *mut usize,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_obj(
+        options: *const IOptions,
+        key: *const c_char,
+        value: *mut *mut c_void,
+    ) -> NvDsInferStatus;
+    pub fn nvds_rs_options_get_uint_array(
+        options: *const IOptions,
+        key: *const c_char,
+        values: *mut u64,
+        capacity: usize,
+        len: *mut usize,
+    ) -> NvDsInferStatus;
+}
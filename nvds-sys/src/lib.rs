@@ -4,3 +4,13 @@
 #![allow(improper_ctypes)]
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+#[cfg(feature = "infer-custom")]
+mod infer_custom;
+#[cfg(feature = "infer-custom")]
+pub use infer_custom::*;
+
+#[cfg(feature = "infer-server-custom")]
+mod infer_server_custom;
+#[cfg(feature = "infer-server-custom")]
+pub use infer_server_custom::*;
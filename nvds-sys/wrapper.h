@@ -20,6 +20,7 @@
 
 //  infer
 #include <nvdsinfer.h>
+#include <gstnvdsinfer.h>
 
 //  surface
 #include <nvbufsurface.h>
@@ -0,0 +1,416 @@
+use crate::meta::{FrameMeta, ObjectMeta};
+use crate::obj_encode::{EncodedCrop, ObjEnc, ObjEncUsrArgs};
+use std::collections::{HashMap, HashSet};
+
+pub const UNTRACKED_OBJECT_ID: u64 = nvidia_deepstream_sys::UNTRACKED_OBJECT_ID as _;
+
+/// Default of [`BestShotSelector::with_min_improvement`].
+pub const DEFAULT_MIN_IMPROVEMENT: f32 = 0.05;
+
+/// Weights of the terms of the score, each term being in `[0, 1]`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScoreWeights {
+    pub confidence: f32,
+    pub area: f32,
+    pub edge_distance: f32,
+    pub sharpness: f32,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            confidence: 1.0,
+            area: 1.0,
+            edge_distance: 0.5,
+            sharpness: 0.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// `frame_width`/`frame_height` are the dimensions the object rectangle refers to,
+    /// `sharpness` is an optional blur metric normalized to `[0, 1]` (1 is sharp).
+    pub fn score(
+        &self,
+        confidence: f32,
+        rect: (f32, f32, f32, f32),
+        frame_width: f32,
+        frame_height: f32,
+        sharpness: Option<f32>,
+    ) -> f32 {
+        let (left, top, width, height) = rect;
+        let area = if frame_width > 0.0 && frame_height > 0.0 {
+            (width * height / (frame_width * frame_height)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let half = frame_width.min(frame_height) / 2.0;
+        let edge_distance = if half > 0.0 {
+            let d = left
+                .min(top)
+                .min(frame_width - (left + width))
+                .min(frame_height - (top + height));
+            (d / half).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let mut score = self.confidence * confidence.clamp(0.0, 1.0)
+            + self.area * area
+            + self.edge_distance * edge_distance;
+        let mut total = self.confidence + self.area + self.edge_distance;
+        if let Some(sharpness) = sharpness {
+            score += self.sharpness * sharpness.clamp(0.0, 1.0);
+            total += self.sharpness;
+        }
+        if total > 0.0 {
+            score / total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn score_object(
+        &self,
+        object_meta: &ObjectMeta,
+        frame_meta: &FrameMeta,
+        sharpness: Option<f32>,
+    ) -> f32 {
+        let (frame_width, frame_height) = frame_size(frame_meta);
+        let rect = object_meta.rect_params();
+        self.score(
+            object_meta.confidence(),
+            (rect.left(), rect.top(), rect.width(), rect.height()),
+            frame_width,
+            frame_height,
+            sharpness,
+        )
+    }
+}
+
+fn frame_size(frame_meta: &FrameMeta) -> (f32, f32) {
+    if frame_meta.pipeline_width() > 0 && frame_meta.pipeline_height() > 0 {
+        (
+            frame_meta.pipeline_width() as _,
+            frame_meta.pipeline_height() as _,
+        )
+    } else {
+        (
+            frame_meta.source_frame_width() as _,
+            frame_meta.source_frame_height() as _,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BestShot {
+    pub crop: EncodedCrop,
+    pub score: f32,
+    pub confidence: f32,
+    pub first_frame_num: i32,
+    pub last_frame_num: i32,
+}
+
+struct Track {
+    best: Option<BestShot>,
+    best_score: f32,
+    first_frame_num: i32,
+    last_frame_num: i32,
+}
+
+/// Keeps the best scored crop of every tracked object and emits it once the
+/// track has not been seen for `max_missing_frames` frames.
+///
+/// Crops are encoded only when a candidate beats the current best of its track
+/// by more than `min_improvement`, so the surface does not need to be retained.
+pub struct BestShotSelector {
+    weights: ScoreWeights,
+    max_missing_frames: u32,
+    min_improvement: f32,
+    tracks: HashMap<(u32, u64), Track>,
+}
+
+impl BestShotSelector {
+    pub fn new(weights: ScoreWeights, max_missing_frames: u32) -> BestShotSelector {
+        BestShotSelector {
+            weights,
+            max_missing_frames,
+            min_improvement: DEFAULT_MIN_IMPROVEMENT,
+            tracks: HashMap::new(),
+        }
+    }
+
+    pub fn with_min_improvement(mut self, min_improvement: f32) -> Self {
+        self.min_improvement = min_improvement;
+        self
+    }
+
+    pub fn weights(&self) -> &ScoreWeights {
+        &self.weights
+    }
+
+    pub fn num_tracks(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Updates the tracks with the objects of `frame_meta` and returns the best
+    /// shots of the tracks of this source which disappeared.
+    ///
+    /// `sharpness` may return a blur metric for an object, see [`ScoreWeights::score`].
+    pub fn process_frame<F: FnMut(&ObjectMeta) -> Option<f32>>(
+        &mut self,
+        obj_enc: &ObjEnc,
+        user_args: &ObjEncUsrArgs,
+        surface: &crate::surface::Surface,
+        frame_meta: &FrameMeta,
+        mut sharpness: F,
+    ) -> Vec<BestShot> {
+        let source_id = frame_meta.source_id();
+        let frame_num = frame_meta.frame_num();
+
+        let mut improved = HashMap::new();
+        if let Some(list) = frame_meta.obj_meta_list() {
+            for object_meta in list.iter() {
+                let object_id = object_meta.object_id();
+                if object_id == UNTRACKED_OBJECT_ID {
+                    continue;
+                }
+                let score =
+                    self.weights
+                        .score_object(object_meta, frame_meta, sharpness(object_meta));
+                if self.update_track((source_id, object_id), frame_num, score) {
+                    improved.insert(object_id, (score, object_meta.confidence()));
+                }
+            }
+        }
+
+        if !improved.is_empty() {
+            let ids = improved.keys().copied().collect::<HashSet<_>>();
+            for crop in obj_enc.encode_objects(user_args, surface, frame_meta, |o| {
+                ids.contains(&o.object_id())
+            }) {
+                let (score, confidence) = improved[&crop.object_id];
+                self.set_best(crop, score, confidence);
+            }
+        }
+
+        self.drain_missing(source_id, frame_num)
+    }
+
+    /// Marks the track of `key` as seen at `frame_num` and returns whether `score`
+    /// beats its best shot by more than `min_improvement`, i.e. the crop is to be
+    /// encoded.
+    fn update_track(&mut self, key: (u32, u64), frame_num: i32, score: f32) -> bool {
+        let track = self.tracks.entry(key).or_insert(Track {
+            best: None,
+            best_score: f32::MIN,
+            first_frame_num: frame_num,
+            last_frame_num: frame_num,
+        });
+        track.last_frame_num = frame_num;
+        track.best.is_none() || score > track.best_score + self.min_improvement
+    }
+
+    fn set_best(&mut self, crop: EncodedCrop, score: f32, confidence: f32) {
+        if let Some(track) = self.tracks.get_mut(&(crop.source_id, crop.object_id)) {
+            track.best_score = score;
+            track.best = Some(BestShot {
+                crop,
+                score,
+                confidence,
+                first_frame_num: track.first_frame_num,
+                last_frame_num: track.last_frame_num,
+            });
+        }
+    }
+
+    /// Emits the tracks of `source_id` not seen for more than `max_missing_frames`.
+    fn drain_missing(&mut self, source_id: u32, frame_num: i32) -> Vec<BestShot> {
+        let max_missing_frames = self.max_missing_frames as i64;
+        self.drain(|(id, _), track| {
+            *id == source_id && frame_num as i64 - track.last_frame_num as i64 > max_missing_frames
+        })
+    }
+
+    /// Emits the best shots of the tracks of `source_id`, e.g. on end of stream.
+    pub fn flush_source(&mut self, source_id: u32) -> Vec<BestShot> {
+        self.drain(|(id, _), _| *id == source_id)
+    }
+
+    pub fn flush(&mut self) -> Vec<BestShot> {
+        self.drain(|_, _| true)
+    }
+
+    fn drain<P: FnMut(&(u32, u64), &Track) -> bool>(&mut self, mut predicate: P) -> Vec<BestShot> {
+        let keys = self
+            .tracks
+            .iter()
+            .filter(|(k, v)| predicate(k, v))
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        let mut r = keys
+            .into_iter()
+            .filter_map(|k| self.tracks.remove(&k))
+            .filter_map(|track| {
+                track.best.map(|mut best| {
+                    best.last_frame_num = track.last_frame_num;
+                    best
+                })
+            })
+            .collect::<Vec<_>>();
+        r.sort_by_key(|b| (b.crop.source_id, b.crop.object_id));
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crop(source_id: u32, object_id: u64, frame_num: i32) -> EncodedCrop {
+        EncodedCrop {
+            jpeg: vec![frame_num as u8],
+            object_id,
+            frame_num,
+            source_id,
+        }
+    }
+
+    /// Runs the selection of a frame with objects of `(object_id, score)`, encoding
+    /// the crops the selector asks for.
+    fn frame(
+        selector: &mut BestShotSelector,
+        source_id: u32,
+        frame_num: i32,
+        objects: &[(u64, f32)],
+    ) -> (Vec<u64>, Vec<BestShot>) {
+        let mut encoded = Vec::new();
+        for &(object_id, score) in objects {
+            if selector.update_track((source_id, object_id), frame_num, score) {
+                encoded.push(object_id);
+                selector.set_best(crop(source_id, object_id, frame_num), score, score);
+            }
+        }
+        (encoded, selector.drain_missing(source_id, frame_num))
+    }
+
+    #[test]
+    fn score_terms() {
+        let weights = ScoreWeights {
+            confidence: 1.0,
+            area: 0.0,
+            edge_distance: 0.0,
+            sharpness: 0.0,
+        };
+        assert_eq!(
+            weights.score(0.8, (0.0, 0.0, 10.0, 10.0), 100.0, 100.0, None),
+            0.8
+        );
+        assert_eq!(
+            weights.score(1.5, (0.0, 0.0, 10.0, 10.0), 100.0, 100.0, None),
+            1.0
+        );
+
+        let area = ScoreWeights {
+            confidence: 0.0,
+            area: 1.0,
+            ..weights
+        };
+        assert_eq!(
+            area.score(1.0, (0.0, 0.0, 50.0, 20.0), 100.0, 100.0, None),
+            0.1
+        );
+        assert_eq!(
+            area.score(1.0, (0.0, 0.0, 50.0, 20.0), 0.0, 100.0, None),
+            0.0
+        );
+
+        let edge = ScoreWeights {
+            confidence: 0.0,
+            edge_distance: 1.0,
+            ..weights
+        };
+        // 25 pixels from the nearest (bottom) edge, out of half the shortest side.
+        assert_eq!(
+            edge.score(1.0, (40.0, 40.0, 20.0, 35.0), 200.0, 100.0, None),
+            0.5
+        );
+        assert_eq!(
+            edge.score(1.0, (0.0, 40.0, 20.0, 20.0), 200.0, 100.0, None),
+            0.0
+        );
+        assert_eq!(
+            edge.score(1.0, (-10.0, 40.0, 20.0, 20.0), 200.0, 100.0, None),
+            0.0
+        );
+
+        // Sharpness only counts when given.
+        let sharp = ScoreWeights {
+            sharpness: 1.0,
+            ..weights
+        };
+        assert_eq!(
+            sharp.score(0.8, (0.0, 0.0, 10.0, 10.0), 100.0, 100.0, None),
+            0.8
+        );
+        assert_eq!(
+            sharp.score(0.8, (0.0, 0.0, 10.0, 10.0), 100.0, 100.0, Some(0.4)),
+            0.6
+        );
+
+        let none = ScoreWeights {
+            confidence: 0.0,
+            ..weights
+        };
+        assert_eq!(
+            none.score(1.0, (0.0, 0.0, 10.0, 10.0), 100.0, 100.0, None),
+            0.0
+        );
+    }
+
+    #[test]
+    fn encodes_only_on_improvement() {
+        let mut selector = BestShotSelector::new(ScoreWeights::default(), 2);
+        assert_eq!(frame(&mut selector, 0, 0, &[(1, 0.5), (2, 0.5)]).0, [1, 2]);
+        // Equal scores and improvements within the margin are not encoded again.
+        assert_eq!(
+            frame(&mut selector, 0, 1, &[(1, 0.5), (2, 0.54)]).0,
+            [] as [u64; 0]
+        );
+        assert_eq!(frame(&mut selector, 0, 2, &[(1, 0.56), (2, 0.4)]).0, [1]);
+        assert_eq!(frame(&mut selector, 0, 3, &[(1, 0.6), (2, 0.9)]).0, [2]);
+
+        let mut selector =
+            BestShotSelector::new(ScoreWeights::default(), 2).with_min_improvement(0.0);
+        assert_eq!(frame(&mut selector, 0, 0, &[(1, 0.5)]).0, [1]);
+        assert_eq!(frame(&mut selector, 0, 1, &[(1, 0.5)]).0, [] as [u64; 0]);
+        assert_eq!(frame(&mut selector, 0, 2, &[(1, 0.51)]).0, [1]);
+    }
+
+    #[test]
+    fn emits_missing_tracks() {
+        let mut selector = BestShotSelector::new(ScoreWeights::default(), 1);
+        frame(&mut selector, 0, 0, &[(1, 0.5), (2, 0.5)]);
+        frame(&mut selector, 1, 0, &[(1, 0.5)]);
+        frame(&mut selector, 0, 1, &[(1, 0.9)]);
+        assert_eq!(selector.num_tracks(), 3);
+
+        // Track 2 of source 0 is missing for 2 frames, the tracks of source 1 are kept.
+        let (_, shots) = frame(&mut selector, 0, 2, &[(1, 0.1)]);
+        assert_eq!(shots.len(), 1);
+        assert_eq!((shots[0].crop.source_id, shots[0].crop.object_id), (0, 2));
+        assert_eq!((shots[0].first_frame_num, shots[0].last_frame_num), (0, 0));
+        assert_eq!(selector.num_tracks(), 2);
+
+        let shots = selector.flush_source(0);
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].score, 0.9);
+        assert_eq!(shots[0].crop.frame_num, 1);
+        assert_eq!((shots[0].first_frame_num, shots[0].last_frame_num), (0, 2));
+
+        let shots = selector.flush();
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].crop.source_id, 1);
+        assert_eq!(selector.num_tracks(), 0);
+    }
+}
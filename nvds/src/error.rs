@@ -0,0 +1,198 @@
+//! Error of the crate, into which the errors of the modules convert, so that `?` can be
+//! used across modules.
+
+#[derive(Debug)]
+pub enum Error {
+    #[cfg(feature = "infer")]
+    Infer(crate::infer::Status),
+    #[cfg(feature = "infer")]
+    Tensor(crate::infer::tensor::TensorError),
+    #[cfg(feature = "infer")]
+    PriorBox(crate::infer::decode::PriorBoxError),
+    #[cfg(feature = "infer_config")]
+    InferConfig(crate::infer::config::ConfigError),
+    /// Non-zero return value of the NvBufSurface function `function`.
+    #[cfg(feature = "surface")]
+    Surface { function: &'static str, code: i32 },
+    #[cfg(feature = "surface_transform")]
+    SurfaceTransform(crate::surface_transform::Error),
+    #[cfg(feature = "yaml")]
+    Yaml(crate::yaml::YamlParserStatus),
+    #[cfg(feature = "obj_encode")]
+    ObjEncode,
+    /// Failure of the meta function `function`.
+    #[cfg(feature = "meta")]
+    Meta { function: &'static str },
+    #[cfg(feature = "meta")]
+    Misc(crate::meta::misc::MiscError),
+    #[cfg(feature = "msgconv")]
+    MsgConv(crate::msgconv::Error),
+    #[cfg(feature = "msgbroker")]
+    MsgBroker(crate::msgbroker::Error),
+    #[cfg(feature = "event_engine")]
+    EventEngine(crate::event_engine::EventEngineError),
+    #[cfg(feature = "world")]
+    Calibration(crate::world::CalibrationError),
+    #[cfg(feature = "overlay")]
+    Overlay(crate::overlay::OverlayError),
+}
+
+// `*self` is matched since the enum is empty without features, leaving `f` unused.
+impl std::fmt::Display for Error {
+    #[allow(unused_variables)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            #[cfg(feature = "infer")]
+            Error::Infer(ref e) => write!(f, "inference error: {}", e),
+            #[cfg(feature = "infer")]
+            Error::Tensor(ref e) => write!(f, "tensor error: {}", e),
+            #[cfg(feature = "infer")]
+            Error::PriorBox(ref e) => write!(f, "prior box error: {}", e),
+            #[cfg(feature = "infer_config")]
+            Error::InferConfig(ref e) => write!(f, "nvinfer configuration error: {}", e),
+            #[cfg(feature = "surface")]
+            Error::Surface { function, code } => write!(f, "{} failed: {}", function, code),
+            #[cfg(feature = "surface_transform")]
+            Error::SurfaceTransform(ref e) => write!(f, "surface transform error: {}", e),
+            #[cfg(feature = "yaml")]
+            Error::Yaml(ref e) => write!(f, "yaml parser error: {}", e),
+            #[cfg(feature = "obj_encode")]
+            Error::ObjEncode => write!(f, "object encoding failed"),
+            #[cfg(feature = "meta")]
+            Error::Meta { function } => write!(f, "{} failed", function),
+            #[cfg(feature = "meta")]
+            Error::Misc(ref e) => write!(f, "misc meta error: {}", e),
+            #[cfg(feature = "msgconv")]
+            Error::MsgConv(ref e) => write!(f, "message conversion error: {}", e),
+            #[cfg(feature = "msgbroker")]
+            Error::MsgBroker(ref e) => write!(f, "message broker error: {}", e),
+            #[cfg(feature = "event_engine")]
+            Error::EventEngine(ref e) => write!(f, "event engine error: {}", e),
+            #[cfg(feature = "world")]
+            Error::Calibration(ref e) => write!(f, "calibration error: {}", e),
+            #[cfg(feature = "overlay")]
+            Error::Overlay(ref e) => write!(f, "overlay error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "infer")]
+            Error::Infer(ref e) => Some(e),
+            #[cfg(feature = "infer")]
+            Error::Tensor(ref e) => Some(e),
+            #[cfg(feature = "infer")]
+            Error::PriorBox(ref e) => Some(e),
+            #[cfg(feature = "infer_config")]
+            Error::InferConfig(ref e) => Some(e),
+            #[cfg(feature = "surface_transform")]
+            Error::SurfaceTransform(ref e) => Some(e),
+            #[cfg(feature = "yaml")]
+            Error::Yaml(ref e) => Some(e),
+            #[cfg(feature = "meta")]
+            Error::Misc(ref e) => Some(e),
+            #[cfg(feature = "msgconv")]
+            Error::MsgConv(ref e) => Some(e),
+            #[cfg(feature = "msgbroker")]
+            Error::MsgBroker(ref e) => Some(e),
+            #[cfg(feature = "event_engine")]
+            Error::EventEngine(ref e) => Some(e),
+            #[cfg(feature = "world")]
+            Error::Calibration(ref e) => Some(e),
+            #[cfg(feature = "overlay")]
+            Error::Overlay(ref e) => Some(e),
+            #[cfg(feature = "surface")]
+            Error::Surface { .. } => None,
+            #[cfg(feature = "obj_encode")]
+            Error::ObjEncode => None,
+            #[cfg(feature = "meta")]
+            Error::Meta { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "infer")]
+impl From<crate::infer::Status> for Error {
+    fn from(e: crate::infer::Status) -> Self {
+        Error::Infer(e)
+    }
+}
+
+#[cfg(feature = "infer")]
+impl From<crate::infer::tensor::TensorError> for Error {
+    fn from(e: crate::infer::tensor::TensorError) -> Self {
+        Error::Tensor(e)
+    }
+}
+
+#[cfg(feature = "infer")]
+impl From<crate::infer::decode::PriorBoxError> for Error {
+    fn from(e: crate::infer::decode::PriorBoxError) -> Self {
+        Error::PriorBox(e)
+    }
+}
+
+#[cfg(feature = "infer_config")]
+impl From<crate::infer::config::ConfigError> for Error {
+    fn from(e: crate::infer::config::ConfigError) -> Self {
+        Error::InferConfig(e)
+    }
+}
+
+#[cfg(feature = "surface_transform")]
+impl From<crate::surface_transform::Error> for Error {
+    fn from(e: crate::surface_transform::Error) -> Self {
+        Error::SurfaceTransform(e)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<crate::yaml::YamlParserStatus> for Error {
+    fn from(e: crate::yaml::YamlParserStatus) -> Self {
+        Error::Yaml(e)
+    }
+}
+
+#[cfg(feature = "meta")]
+impl From<crate::meta::misc::MiscError> for Error {
+    fn from(e: crate::meta::misc::MiscError) -> Self {
+        Error::Misc(e)
+    }
+}
+
+#[cfg(feature = "msgconv")]
+impl From<crate::msgconv::Error> for Error {
+    fn from(e: crate::msgconv::Error) -> Self {
+        Error::MsgConv(e)
+    }
+}
+
+#[cfg(feature = "msgbroker")]
+impl From<crate::msgbroker::Error> for Error {
+    fn from(e: crate::msgbroker::Error) -> Self {
+        Error::MsgBroker(e)
+    }
+}
+
+#[cfg(feature = "event_engine")]
+impl From<crate::event_engine::EventEngineError> for Error {
+    fn from(e: crate::event_engine::EventEngineError) -> Self {
+        Error::EventEngine(e)
+    }
+}
+
+#[cfg(feature = "world")]
+impl From<crate::world::CalibrationError> for Error {
+    fn from(e: crate::world::CalibrationError) -> Self {
+        Error::Calibration(e)
+    }
+}
+
+#[cfg(feature = "overlay")]
+impl From<crate::overlay::OverlayError> for Error {
+    fn from(e: crate::overlay::OverlayError) -> Self {
+        Error::Overlay(e)
+    }
+}
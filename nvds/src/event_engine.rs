@@ -0,0 +1,785 @@
+//! Rule based generation of ENTRY/EXIT/MOVING/STOPPED/PARKED events from tracked objects.
+//!
+//! ```yaml
+//! object_types: { 0: Vehicle, 2: Person }
+//! max_missing_frames: 30
+//! stationary_displacement: 5.0
+//! entry: { min_frames: 3 }
+//! exit: {}
+//! moving: { min_displacement: 40.0, window_frames: 15 }
+//! stopped: { min_frames: 30 }
+//! parked: { classes: [0], min_frames: 900 }
+//! rate_limit: { max_events: 10, period: 1.0, exempt: [entry, exit] }
+//! ```
+
+use crate::meta::schema::{EventType, ObjectType};
+use crate::meta::{BatchMetaExt, FrameMeta, ObjectMeta};
+use crate::msgconv::{BBox, Event};
+use crate::timestamp::{self, format_rfc3339, FrameClock, NtpSyncMode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+
+const UNTRACKED_OBJECT_ID: u64 = nvidia_deepstream_sys::UNTRACKED_OBJECT_ID as _;
+
+#[derive(Debug)]
+pub enum EventEngineError {
+    Io(std::io::Error),
+    Yaml(serde_yaml::Error),
+    InvalidValue { key: String, value: String },
+}
+
+impl std::fmt::Display for EventEngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventEngineError::Io(e) => write!(f, "failed to read event rules: {}", e),
+            EventEngineError::Yaml(e) => write!(f, "invalid event rules yaml: {}", e),
+            EventEngineError::InvalidValue { key, value } => {
+                write!(f, "invalid value `{}` for `{}`", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventEngineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EventEngineError::Io(e) => Some(e),
+            EventEngineError::Yaml(e) => Some(e),
+            EventEngineError::InvalidValue { .. } => None,
+        }
+    }
+}
+
+/// Emits ENTRY once a track has been seen for `min_frames` frames.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryRule {
+    pub classes: Vec<i32>,
+    pub min_frames: u32,
+}
+
+impl Default for EntryRule {
+    fn default() -> Self {
+        EntryRule {
+            classes: Vec::new(),
+            min_frames: 1,
+        }
+    }
+}
+
+/// Emits EXIT for entered tracks missing for more than `max_missing_frames`.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExitRule {
+    pub classes: Vec<i32>,
+}
+
+/// Emits MOVING when the center of the bounding box moved by at least
+/// `min_displacement` pixels within `window_frames` frames, and again after the
+/// object stayed stationary for `window_frames` frames or was STOPPED or PARKED.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MovingRule {
+    pub classes: Vec<i32>,
+    pub min_displacement: f32,
+    pub window_frames: u32,
+}
+
+impl Default for MovingRule {
+    fn default() -> Self {
+        MovingRule {
+            classes: Vec::new(),
+            min_displacement: 20.0,
+            window_frames: 10,
+        }
+    }
+}
+
+/// Emits STOPPED or PARKED when an object stayed within `stationary_displacement`
+/// pixels for `min_frames` frames.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StationaryRule {
+    pub classes: Vec<i32>,
+    pub min_frames: u32,
+}
+
+impl Default for StationaryRule {
+    fn default() -> Self {
+        StationaryRule {
+            classes: Vec::new(),
+            min_frames: 30,
+        }
+    }
+}
+
+/// At most `max_events` events per sensor within `period` seconds, event types
+/// listed in `exempt` (e.g. `entry`) are never dropped.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    pub max_events: u32,
+    pub period: f64,
+    pub exempt: Vec<String>,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            max_events: 10,
+            period: 1.0,
+            exempt: Vec::new(),
+        }
+    }
+}
+
+/// Rules of an [`EventEngine`], a rule which is not set does not emit events.
+///
+/// `classes` restricts the objects considered (all when empty) and `object_types`
+/// maps class ids to the schema object types (`Vehicle`, `Person`, ...).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub classes: Vec<i32>,
+    pub object_types: BTreeMap<i32, String>,
+    pub max_missing_frames: u32,
+    pub stationary_displacement: f32,
+    pub entry: Option<EntryRule>,
+    pub exit: Option<ExitRule>,
+    pub moving: Option<MovingRule>,
+    pub stopped: Option<StationaryRule>,
+    pub parked: Option<StationaryRule>,
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            classes: Vec::new(),
+            object_types: BTreeMap::new(),
+            max_missing_frames: 30,
+            stationary_displacement: 5.0,
+            entry: None,
+            exit: None,
+            moving: None,
+            stopped: None,
+            parked: None,
+            rate_limit: None,
+        }
+    }
+}
+
+impl Rules {
+    pub fn from_yaml(s: &str) -> Result<Rules, EventEngineError> {
+        serde_yaml::from_str(s).map_err(EventEngineError::Yaml)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Rules, EventEngineError> {
+        let s = std::fs::read_to_string(path).map_err(EventEngineError::Io)?;
+        Self::from_yaml(&s)
+    }
+
+    pub fn to_yaml(&self) -> Result<String, EventEngineError> {
+        serde_yaml::to_string(self).map_err(EventEngineError::Yaml)
+    }
+}
+
+fn applies(classes: &[i32], class_id: i32) -> bool {
+    classes.is_empty() || classes.contains(&class_id)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrackedObject {
+    pub object_id: u64,
+    pub class_id: i32,
+    pub confidence: f32,
+    pub bbox: BBox,
+}
+
+impl TrackedObject {
+    pub fn from_object_meta(object_meta: &ObjectMeta) -> TrackedObject {
+        let rect = object_meta.rect_params();
+        TrackedObject {
+            object_id: object_meta.object_id(),
+            class_id: object_meta.class_id(),
+            confidence: object_meta.confidence(),
+            bbox: BBox {
+                left: rect.left(),
+                top: rect.top(),
+                width: rect.width(),
+                height: rect.height(),
+            },
+        }
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (
+            self.bbox.left + self.bbox.width / 2.0,
+            self.bbox.top + self.bbox.height / 2.0,
+        )
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Motion {
+    Unknown,
+    Moving,
+    Stopped,
+    Parked,
+}
+
+struct Track {
+    object: TrackedObject,
+    seen_frames: u32,
+    last_frame_num: i32,
+    entered: bool,
+    history: VecDeque<(i32, (f32, f32))>,
+    anchor: (f32, f32),
+    anchor_frame_num: i32,
+    motion: Motion,
+}
+
+/// Turns the tracked objects of every frame into events according to [`Rules`].
+pub struct EventEngine {
+    rules: Rules,
+    object_types: BTreeMap<i32, ObjectType>,
+    exempt: Vec<EventType>,
+    tracks: HashMap<(u32, u64), Track>,
+    emitted: HashMap<u32, VecDeque<u64>>,
+    clock: FrameClock,
+}
+
+impl EventEngine {
+    pub fn new(rules: Rules) -> Result<EventEngine, EventEngineError> {
+        let object_types = rules
+            .object_types
+            .iter()
+            .map(|(class_id, name)| {
+                ObjectType::from_schema_str(name)
+                    .map(|t| (*class_id, t))
+                    .ok_or_else(|| EventEngineError::InvalidValue {
+                        key: "object_types".to_string(),
+                        value: name.clone(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        let exempt = rules
+            .rate_limit
+            .iter()
+            .flat_map(|r| r.exempt.iter())
+            .map(|name| {
+                EventType::from_schema_str(name).ok_or_else(|| EventEngineError::InvalidValue {
+                    key: "rate_limit.exempt".to_string(),
+                    value: name.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(EventEngine {
+            rules,
+            object_types,
+            exempt,
+            tracks: HashMap::new(),
+            emitted: HashMap::new(),
+            clock: FrameClock::new(NtpSyncMode::SystemTime, timestamp::now()),
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<EventEngine, EventEngineError> {
+        Self::new(Rules::from_file(path)?)
+    }
+
+    /// Clock used by [`EventEngine::process_frame`] to timestamp the events.
+    pub fn with_clock(mut self, clock: FrameClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn num_tracks(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Updates the tracks of `source_id` with the objects of a frame and returns the
+    /// events, `timestamp` being in nanoseconds since the epoch.
+    pub fn update(
+        &mut self,
+        source_id: u32,
+        frame_num: i32,
+        timestamp: u64,
+        objects: &[TrackedObject],
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        for object in objects {
+            if object.object_id == UNTRACKED_OBJECT_ID
+                || !applies(&self.rules.classes, object.class_id)
+            {
+                continue;
+            }
+            let center = object.center();
+            let track = self
+                .tracks
+                .entry((source_id, object.object_id))
+                .or_insert(Track {
+                    object: *object,
+                    seen_frames: 0,
+                    last_frame_num: frame_num,
+                    entered: false,
+                    history: VecDeque::new(),
+                    anchor: center,
+                    anchor_frame_num: frame_num,
+                    motion: Motion::Unknown,
+                });
+            track.object = *object;
+            track.seen_frames += 1;
+            track.last_frame_num = frame_num;
+            track.history.push_back((frame_num, center));
+            let window = self.rules.moving.as_ref().map_or(0, |r| r.window_frames) as i32;
+            while track
+                .history
+                .front()
+                .is_some_and(|(f, _)| *f < frame_num - window)
+            {
+                track.history.pop_front();
+            }
+
+            let class_id = object.class_id;
+            let mut types = Vec::new();
+            if !track.entered {
+                let min_frames = self.rules.entry.as_ref().map_or(1, |r| r.min_frames);
+                if track.seen_frames < min_frames {
+                    continue;
+                }
+                track.entered = true;
+                track.anchor = center;
+                track.anchor_frame_num = frame_num;
+                if self
+                    .rules
+                    .entry
+                    .as_ref()
+                    .is_some_and(|r| applies(&r.classes, class_id))
+                {
+                    types.push(EventType::Entry);
+                }
+            }
+
+            if distance(center, track.anchor) > self.rules.stationary_displacement {
+                track.anchor = center;
+                track.anchor_frame_num = frame_num;
+                if matches!(track.motion, Motion::Stopped | Motion::Parked) {
+                    track.motion = Motion::Unknown;
+                }
+            }
+            let stationary_frames = (frame_num - track.anchor_frame_num) as i64;
+            if let Some(rule) = &self.rules.moving {
+                if track.motion == Motion::Moving && stationary_frames >= rule.window_frames as i64
+                {
+                    track.motion = Motion::Unknown;
+                }
+                let moved = track
+                    .history
+                    .front()
+                    .map_or(0.0, |(_, first)| distance(center, *first));
+                if applies(&rule.classes, class_id)
+                    && track.motion != Motion::Moving
+                    && moved >= rule.min_displacement
+                {
+                    track.motion = Motion::Moving;
+                    types.push(EventType::Moving);
+                }
+            }
+            if let Some(rule) = &self.rules.stopped {
+                if applies(&rule.classes, class_id)
+                    && matches!(track.motion, Motion::Unknown | Motion::Moving)
+                    && stationary_frames >= rule.min_frames as i64
+                {
+                    track.motion = Motion::Stopped;
+                    types.push(EventType::Stopped);
+                }
+            }
+            if let Some(rule) = &self.rules.parked {
+                if applies(&rule.classes, class_id)
+                    && track.motion != Motion::Parked
+                    && stationary_frames >= rule.min_frames as i64
+                {
+                    track.motion = Motion::Parked;
+                    types.push(EventType::Parked);
+                }
+            }
+
+            let object = track.object;
+            for type_ in types {
+                self.emit(&mut events, type_, source_id, frame_num, timestamp, &object);
+            }
+        }
+
+        let max_missing_frames = self.rules.max_missing_frames as i64;
+        let exited = self.remove_tracks(|(id, _), track| {
+            *id == source_id && frame_num as i64 - track.last_frame_num as i64 > max_missing_frames
+        });
+        self.exit(&mut events, exited, source_id, frame_num, timestamp);
+        events
+    }
+
+    /// Ends every track of `source_id`, e.g. on end of stream.
+    pub fn flush_source(&mut self, source_id: u32, frame_num: i32, timestamp: u64) -> Vec<Event> {
+        let mut events = Vec::new();
+        let exited = self.remove_tracks(|(id, _), _| *id == source_id);
+        self.exit(&mut events, exited, source_id, frame_num, timestamp);
+        self.emitted.remove(&source_id);
+        self.clock.reset_source(source_id);
+        events
+    }
+
+    /// Runs [`EventEngine::update`] on the objects of `frame_meta`, timestamped by the
+    /// [`FrameClock`] of the engine.
+    pub fn process_frame(&mut self, frame_meta: &FrameMeta) -> Vec<Event> {
+        let objects = frame_meta
+            .obj_meta_list()
+            .map(|list| {
+                list.iter()
+                    .map(TrackedObject::from_object_meta)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let timestamp = self.clock.frame_wall_clock(frame_meta);
+        self.update(
+            frame_meta.source_id(),
+            frame_meta.frame_num(),
+            timestamp,
+            &objects,
+        )
+    }
+
+    /// Like [`EventEngine::process_frame`] and attaches each event to `frame_meta` as
+    /// an `NvDsEventMsgMeta`.
+    pub fn process_and_attach<BM: BatchMetaExt>(
+        &mut self,
+        batch_meta: &BM,
+        frame_meta: &FrameMeta,
+    ) -> Vec<Event> {
+        let events = self.process_frame(frame_meta);
+        for event in events.iter() {
+            event.attach_to_frame(batch_meta, frame_meta);
+        }
+        events
+    }
+
+    fn remove_tracks<P: FnMut(&(u32, u64), &Track) -> bool>(
+        &mut self,
+        mut predicate: P,
+    ) -> Vec<Track> {
+        let keys = self
+            .tracks
+            .iter()
+            .filter(|(k, v)| predicate(k, v))
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        let mut tracks = keys
+            .into_iter()
+            .filter_map(|k| self.tracks.remove(&k))
+            .collect::<Vec<_>>();
+        tracks.sort_by_key(|t| t.object.object_id);
+        tracks
+    }
+
+    fn exit(
+        &mut self,
+        events: &mut Vec<Event>,
+        tracks: Vec<Track>,
+        source_id: u32,
+        frame_num: i32,
+        timestamp: u64,
+    ) {
+        let Some(rule) = self.rules.exit.clone() else {
+            return;
+        };
+        for track in tracks {
+            if track.entered && applies(&rule.classes, track.object.class_id) {
+                self.emit(
+                    events,
+                    EventType::Exit,
+                    source_id,
+                    frame_num,
+                    timestamp,
+                    &track.object,
+                );
+            }
+        }
+    }
+
+    fn emit(
+        &mut self,
+        events: &mut Vec<Event>,
+        type_: EventType,
+        source_id: u32,
+        frame_num: i32,
+        timestamp: u64,
+        object: &TrackedObject,
+    ) {
+        if let Some(rate_limit) = &self.rules.rate_limit {
+            if !self.exempt.contains(&type_) {
+                let emitted = self.emitted.entry(source_id).or_default();
+                let period = (rate_limit.period * 1e9) as u64;
+                while emitted.front().is_some_and(|t| *t + period <= timestamp) {
+                    emitted.pop_front();
+                }
+                if emitted.len() >= rate_limit.max_events as usize {
+                    return;
+                }
+                emitted.push_back(timestamp);
+            }
+        }
+
+        events.push(Event {
+            type_,
+            obj_type: self
+                .object_types
+                .get(&object.class_id)
+                .copied()
+                .unwrap_or(ObjectType::Unknown),
+            bbox: object.bbox,
+            obj_class_id: object.class_id,
+            sensor_id: source_id as _,
+            frame_id: frame_num,
+            confidence: object.confidence as _,
+            tracking_id: object.object_id,
+            ts: format_rfc3339(timestamp),
+            object_id: object.object_id.to_string(),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_NANOS: u64 = 40_000_000;
+
+    fn object(object_id: u64, class_id: i32, left: f32) -> TrackedObject {
+        TrackedObject {
+            object_id,
+            class_id,
+            confidence: 0.9,
+            bbox: BBox {
+                left,
+                top: 100.0,
+                width: 20.0,
+                height: 40.0,
+            },
+        }
+    }
+
+    fn engine(yaml: &str) -> EventEngine {
+        EventEngine::new(Rules::from_yaml(yaml).unwrap()).unwrap()
+    }
+
+    /// Updates `engine` with `objects(frame_num)` for every frame of `frames` and returns
+    /// the frame number, type and tracking id of the events.
+    fn run<F: Fn(i32) -> Vec<TrackedObject>>(
+        engine: &mut EventEngine,
+        frames: std::ops::Range<i32>,
+        objects: F,
+    ) -> Vec<(i32, EventType, u64)> {
+        frames
+            .flat_map(|frame_num| {
+                engine
+                    .update(
+                        0,
+                        frame_num,
+                        frame_num as u64 * FRAME_NANOS,
+                        &objects(frame_num),
+                    )
+                    .into_iter()
+                    .map(move |e| (frame_num, e.type_, e.tracking_id))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entry_after_min_frames() {
+        let mut e = engine("{ classes: [0, 2], entry: { classes: [0], min_frames: 3 } }");
+        let events = run(&mut e, 0..6, |_| {
+            vec![
+                object(1, 0, 0.0),
+                object(2, 2, 0.0),
+                object(3, 1, 0.0),
+                object(UNTRACKED_OBJECT_ID, 0, 0.0),
+            ]
+        });
+        assert_eq!(events, [(2, EventType::Entry, 1)]);
+        assert_eq!(e.num_tracks(), 2);
+
+        let mut e = engine("{ entry: {} }");
+        let events = run(&mut e, 0..3, |_| vec![object(1, 0, 0.0)]);
+        assert_eq!(events, [(0, EventType::Entry, 1)]);
+    }
+
+    #[test]
+    fn exit_after_max_missing_frames() {
+        let mut e = engine("{ max_missing_frames: 2, entry: { min_frames: 2 }, exit: {} }");
+        let events = run(&mut e, 0..8, |frame_num| {
+            let mut objects = Vec::new();
+            if frame_num < 2 {
+                objects.push(object(1, 0, 0.0));
+            }
+            objects.push(object(2, 0, 0.0));
+            if frame_num == 0 {
+                objects.push(object(3, 0, 0.0));
+            }
+            objects
+        });
+        assert_eq!(
+            events,
+            [
+                (1, EventType::Entry, 1),
+                (1, EventType::Entry, 2),
+                (4, EventType::Exit, 1),
+            ]
+        );
+        assert_eq!(e.num_tracks(), 1);
+
+        let events = e.flush_source(0, 8, 8 * FRAME_NANOS);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].type_, EventType::Exit);
+        assert_eq!(events[0].tracking_id, 2);
+        assert_eq!(e.num_tracks(), 0);
+    }
+
+    #[test]
+    fn moving_again_after_standing_still() {
+        let mut e = engine("{ moving: { min_displacement: 40.0, window_frames: 5 } }");
+        // 10 pixels per frame until frame 10, still until frame 20, then moving again.
+        let events = run(&mut e, 0..30, |frame_num| {
+            let left = match frame_num {
+                0..=10 => frame_num as f32 * 10.0,
+                11..=20 => 100.0,
+                _ => 100.0 + (frame_num - 20) as f32 * 10.0,
+            };
+            vec![object(1, 0, left)]
+        });
+        assert_eq!(
+            events,
+            [(4, EventType::Moving, 1), (24, EventType::Moving, 1)]
+        );
+    }
+
+    #[test]
+    fn stopped_and_parked() {
+        let mut e = engine(
+            "{ stationary_displacement: 5.0, stopped: { min_frames: 3 }, \
+               parked: { classes: [0], min_frames: 6 } }",
+        );
+        // Jitters by 4 pixels, then moves by 10 pixels at frame 10.
+        let events = run(&mut e, 0..20, |frame_num| {
+            let left = if frame_num < 10 {
+                (frame_num % 2) as f32 * 4.0
+            } else {
+                14.0
+            };
+            vec![object(1, 0, left), object(2, 2, left)]
+        });
+        assert_eq!(
+            events,
+            [
+                (3, EventType::Stopped, 1),
+                (3, EventType::Stopped, 2),
+                (6, EventType::Parked, 1),
+                (13, EventType::Stopped, 1),
+                (13, EventType::Stopped, 2),
+                (16, EventType::Parked, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_and_stopped() {
+        let mut e = engine(
+            "{ moving: { min_displacement: 40.0, window_frames: 5 }, \
+               stopped: { min_frames: 3 } }",
+        );
+        let events = run(&mut e, 0..20, |frame_num| {
+            vec![object(1, 0, frame_num.min(10) as f32 * 10.0)]
+        });
+        assert_eq!(
+            events,
+            [(4, EventType::Moving, 1), (13, EventType::Stopped, 1)]
+        );
+    }
+
+    #[test]
+    fn rate_limit() {
+        let mut e = engine(
+            "{ entry: {}, stopped: { min_frames: 1 }, \
+               rate_limit: { max_events: 2, period: 1.0, exempt: [entry] } }",
+        );
+        let events = run(&mut e, 0..2, |_| {
+            (1..5).map(|id| object(id, 0, 0.0)).collect()
+        });
+        let types = events.iter().map(|e| e.1).collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                EventType::Entry,
+                EventType::Entry,
+                EventType::Entry,
+                EventType::Entry,
+                EventType::Stopped,
+                EventType::Stopped,
+            ]
+        );
+
+        // The period started with the events of frame 1.
+        let events = run(&mut e, 25..28, |frame_num| {
+            (25..=frame_num)
+                .map(|f| object(10 + f as u64, 0, 0.0))
+                .collect()
+        });
+        assert_eq!(
+            events,
+            [
+                (25, EventType::Entry, 35),
+                (26, EventType::Stopped, 35),
+                (26, EventType::Entry, 36),
+                (27, EventType::Stopped, 36),
+                (27, EventType::Entry, 37),
+            ]
+        );
+
+        let mut e = engine("{ entry: {}, rate_limit: { max_events: 2 } }");
+        let events = run(&mut e, 0..1, |_| {
+            (1..5).map(|id| object(id, 0, 0.0)).collect()
+        });
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn object_types() {
+        let mut e = engine("{ object_types: { 0: Vehicle }, entry: {} }");
+        let events = e.update(3, 7, 0, &[object(1, 0, 0.0), object(2, 1, 0.0)]);
+        assert_eq!(events[0].obj_type, ObjectType::Vehicle);
+        assert_eq!(events[0].sensor_id, 3);
+        assert_eq!(events[0].frame_id, 7);
+        assert_eq!(events[0].object_id, "1");
+        assert_eq!(events[1].obj_type, ObjectType::Unknown);
+
+        let rules = Rules::from_yaml("{ object_types: { 0: Car } }").unwrap();
+        assert!(matches!(
+            EventEngine::new(rules),
+            Err(EventEngineError::InvalidValue { .. })
+        ));
+        let rules = Rules::from_yaml("{ rate_limit: { exempt: [leave] } }").unwrap();
+        assert!(matches!(
+            EventEngine::new(rules),
+            Err(EventEngineError::InvalidValue { .. })
+        ));
+    }
+}
@@ -2,6 +2,21 @@ use crate::WrapperExt;
 use gstreamer::glib::GStr;
 use std::ptr::NonNull;
 
+pub mod classify;
+pub mod cluster;
+#[cfg(feature = "infer_config")]
+pub mod config;
+#[cfg(feature = "infer_server")]
+pub mod custom_process;
+pub mod decode;
+#[cfg(feature = "infer_parse")]
+pub mod parse;
+#[cfg(feature = "meta")]
+pub mod segmentation;
+pub mod tensor;
+#[cfg(feature = "meta")]
+pub mod tensor_meta;
+
 #[repr(u32)]
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub enum DataType {
@@ -30,6 +45,23 @@ pub enum Status {
 }
 
 impl Status {
+    pub fn from_u32(v: u32) -> Option<Status> {
+        [
+            Status::Success,
+            Status::ConfigFailed,
+            Status::CustomLibFailed,
+            Status::InvalidParams,
+            Status::OutputParsingFailed,
+            Status::CudaError,
+            Status::TensorrtError,
+            Status::ResourceError,
+            Status::TritonError,
+            Status::UnknownError,
+        ]
+        .into_iter()
+        .find(|s| *s as u32 == v)
+    }
+
     pub fn to_str(&self) -> &GStr {
         unsafe {
             NonNull::new(nvidia_deepstream_sys::NvDsInferStatus2Str(*self as _) as _)
@@ -37,8 +69,23 @@ impl Status {
                 .unwrap_or_default()
         }
     }
+
+    pub fn into_result(self) -> Result<(), Status> {
+        match self {
+            Status::Success => Ok(()),
+            e => Err(e),
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
 }
 
+impl std::error::Error for Status {}
+
 #[repr(u32)]
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub enum LogLevel {
@@ -49,6 +96,45 @@ pub enum LogLevel {
     Debug = nvidia_deepstream_sys::NvDsInferLogLevel_NVDSINFER_LOG_DEBUG as _,
 }
 
+/// Element types of layer buffers.
+pub trait TensorElement: Copy {
+    const DATA_TYPE: DataType;
+
+    fn to_f32(self) -> f32;
+}
+
+impl TensorElement for f32 {
+    const DATA_TYPE: DataType = DataType::Float;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+impl TensorElement for half::f16 {
+    const DATA_TYPE: DataType = DataType::Half;
+
+    fn to_f32(self) -> f32 {
+        half::f16::to_f32(self)
+    }
+}
+
+impl TensorElement for i8 {
+    const DATA_TYPE: DataType = DataType::Int8;
+
+    fn to_f32(self) -> f32 {
+        self as _
+    }
+}
+
+impl TensorElement for i32 {
+    const DATA_TYPE: DataType = DataType::Int32;
+
+    fn to_f32(self) -> f32 {
+        self as _
+    }
+}
+
 crate::wrapper_impl_ref_type!(Dims, nvidia_deepstream_sys::NvDsInferDims);
 
 impl Dims {
@@ -135,6 +221,20 @@ impl LayerInfo {
         self.as_native_type_ref().buffer as _
     }
 
+    /// Host buffer of the layer, `None` if it is not set or `T` does not match the
+    /// data type of the layer.
+    ///
+    /// The buffer must be in host memory, as for the layers passed to custom parsers.
+    pub fn as_slice<T: TensorElement>(&self) -> Option<&[T]> {
+        let buffer = self.as_native_type_ref().buffer as *const T;
+        if buffer.is_null() || self.data_type() != T::DATA_TYPE {
+            return None;
+        }
+        Some(unsafe {
+            std::slice::from_raw_parts(buffer, self.infer_dims().num_elements() as _)
+        })
+    }
+
     pub fn is_input(&self) -> bool {
         self.as_native_type_ref().isInput != 0
     }
@@ -156,12 +256,32 @@ impl NetworkInfo {
     }
 }
 
-crate::wrapper_impl_ref_type!(
+crate::wrapper_impl_value_type!(
     ObjectDetectionInfo,
     nvidia_deepstream_sys::NvDsInferObjectDetectionInfo
 );
 
 impl ObjectDetectionInfo {
+    pub fn new(
+        class_id: u32,
+        left: f32,
+        top: f32,
+        width: f32,
+        height: f32,
+        detection_confidence: f32,
+    ) -> ObjectDetectionInfo {
+        ObjectDetectionInfo::from_native_type(
+            nvidia_deepstream_sys::NvDsInferObjectDetectionInfo {
+                classId: class_id,
+                left,
+                top,
+                width,
+                height,
+                detectionConfidence: detection_confidence,
+            },
+        )
+    }
+
     pub fn class_id(&self) -> u32 {
         self.as_native_type_ref().classId
     }
@@ -0,0 +1,298 @@
+//! Classifier outputs: scores of output layers into attributes, label files, and
+//! classifier meta of objects.
+
+use super::{DataType, LayerInfo};
+use std::path::Path;
+
+pub fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+pub fn softmax(scores: &mut [f32]) {
+    let max = scores.iter().copied().fold(f32::MIN, f32::max);
+    scores.iter_mut().for_each(|x| *x = (*x - max).exp());
+    let sum = scores.iter().sum::<f32>();
+    scores.iter_mut().for_each(|x| *x /= sum);
+}
+
+/// Index and value of the highest score, the first one on ties.
+pub fn argmax(scores: &[f32]) -> Option<(usize, f32)> {
+    scores
+        .iter()
+        .copied()
+        .enumerate()
+        .fold(None, |best, (i, x)| match best {
+            Some((_, b)) if b >= x => best,
+            _ => Some((i, x)),
+        })
+}
+
+/// Classes whose sigmoid of `logits` is above `threshold`, for multi-label classifiers.
+pub fn multi_label(logits: &[f32], threshold: f32) -> Vec<(usize, f32)> {
+    logits
+        .iter()
+        .map(|&x| sigmoid(x))
+        .enumerate()
+        .filter(|&(_, p)| p > threshold)
+        .collect()
+}
+
+/// Scores of a float or half layer.
+pub fn layer_scores(layer: &LayerInfo) -> Option<Vec<f32>> {
+    match layer.data_type() {
+        DataType::Float => layer.tensor::<f32>().ok().map(|t| t.to_vec()),
+        DataType::Half => layer.tensor::<half::f16>().ok().map(|t| t.to_f32_vec()),
+        _ => None,
+    }
+}
+
+/// Labels file of nvinfer, `labelfile-path`: a line of labels separated by `;` for
+/// every attribute of a classifier, or a label per line for detectors.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct LabelFile {
+    lines: Vec<Vec<String>>,
+}
+
+impl LabelFile {
+    pub fn parse(s: &str) -> LabelFile {
+        LabelFile {
+            lines: s
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|l| l.split(';').map(|v| v.trim().to_string()).collect())
+                .collect(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> std::io::Result<LabelFile> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn num_attributes(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Labels of the values of `attribute_index`.
+    pub fn attribute(&self, attribute_index: usize) -> &[String] {
+        self.lines
+            .get(attribute_index)
+            .map_or(&[], |l| l.as_slice())
+    }
+
+    pub fn label(&self, attribute_index: usize, value: usize) -> Option<&str> {
+        self.attribute(attribute_index)
+            .get(value)
+            .map(String::as_str)
+    }
+
+    /// Labels of a detector, one per line.
+    pub fn detector_labels(&self) -> Vec<&str> {
+        self.lines.iter().flatten().map(String::as_str).collect()
+    }
+}
+
+/// Owned counterpart of [`Attribute`](super::Attribute) produced by parsers.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Classification {
+    pub attribute_index: u32,
+    pub attribute_value: u32,
+    pub attribute_confidence: f32,
+    pub attribute_label: Option<String>,
+}
+
+impl Classification {
+    pub fn new(
+        attribute_index: u32,
+        attribute_value: u32,
+        attribute_confidence: f32,
+        labels: Option<&LabelFile>,
+    ) -> Classification {
+        Classification {
+            attribute_index,
+            attribute_value,
+            attribute_confidence,
+            attribute_label: labels
+                .and_then(|l| l.label(attribute_index as _, attribute_value as _))
+                .map(str::to_string),
+        }
+    }
+}
+
+/// Parsing of nvinfer for classifiers: every output layer holds the probabilities of an
+/// attribute, whose best value is kept if above `classifier_threshold`. Scores are
+/// first normalized if `apply_softmax`.
+pub fn parse_softmax_layers(
+    layers: &[LayerInfo],
+    classifier_threshold: f32,
+    apply_softmax: bool,
+    labels: Option<&LabelFile>,
+) -> Vec<Classification> {
+    layers
+        .iter()
+        .filter(|l| !l.is_input())
+        .enumerate()
+        .filter_map(|(attribute_index, layer)| {
+            let mut scores = layer_scores(layer)?;
+            if apply_softmax {
+                softmax(&mut scores);
+            }
+            let (value, confidence) = argmax(&scores)?;
+            (confidence > classifier_threshold)
+                .then(|| Classification::new(attribute_index as _, value as _, confidence, labels))
+        })
+        .collect()
+}
+
+/// A multi-label layer of logits as an attribute per class above `threshold`, all with
+/// `attribute_index`.
+pub fn parse_multi_label_layer(
+    layer: &LayerInfo,
+    attribute_index: u32,
+    threshold: f32,
+    labels: Option<&LabelFile>,
+) -> Vec<Classification> {
+    layer_scores(layer)
+        .map(|logits| {
+            multi_label(&logits, threshold)
+                .into_iter()
+                .map(|(value, confidence)| {
+                    Classification::new(attribute_index, value as _, confidence, labels)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Labels of `classifications` separated by spaces, as the description string of
+/// nvinfer.
+pub fn description(classifications: &[Classification]) -> String {
+    classifications
+        .iter()
+        .filter_map(|c| c.attribute_label.as_deref())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(feature = "meta")]
+impl crate::meta::ObjectMeta {
+    /// Adds `classifications` as a classifier meta of `unique_component_id`, with a
+    /// label info per classification. Returns `false` if a meta pool is exhausted, in
+    /// which case the classifier meta holds the label infos acquired so far.
+    pub fn add_classifications<BM: crate::meta::BatchMetaExt>(
+        &self,
+        batch_meta: &BM,
+        classifications: &[Classification],
+        unique_component_id: i32,
+        labels: Option<&LabelFile>,
+    ) -> bool {
+        let Some(classifier_meta) = batch_meta.acquire_classifier_meta_from_pool() else {
+            return false;
+        };
+        classifier_meta.set_unique_component_id(unique_component_id);
+
+        let mut num_labels = 0;
+        for c in classifications {
+            let Some(label_info) = batch_meta.acquire_label_info_meta_from_pool() else {
+                break;
+            };
+            label_info.set_num_classes(
+                labels.map_or(0, |l| l.attribute(c.attribute_index as _).len() as _),
+            );
+            label_info.set_label_id(c.attribute_index);
+            label_info.set_result_class_id(c.attribute_value);
+            label_info.set_result_prob(c.attribute_confidence);
+            label_info.set_result_label(c.attribute_label.as_deref().unwrap_or_default());
+            classifier_meta.add_label_info_meta(label_info);
+            num_labels += 1;
+        }
+        classifier_meta.set_num_labels(num_labels);
+        self.add_classifier_meta(classifier_meta);
+        num_labels as usize == classifications.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len());
+        assert!(
+            a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn softmax_large_logits() {
+        let mut small = [0.0, 1.0, 2.0];
+        softmax(&mut small);
+        assert_close(&small, &[0.09003057, 0.24472847, 0.66524096]);
+
+        let mut large = [1000.0, 1001.0, 1002.0];
+        softmax(&mut large);
+        assert_close(&large, &small);
+
+        let mut mixed = [-1000.0, 1000.0];
+        softmax(&mut mixed);
+        assert_close(&mixed, &[0.0, 1.0]);
+
+        let mut empty: [f32; 0] = [];
+        softmax(&mut empty);
+    }
+
+    #[test]
+    fn argmax_ties() {
+        assert_eq!(argmax(&[0.1, 0.7, 0.2]), Some((1, 0.7)));
+        assert_eq!(argmax(&[0.4, 0.1, 0.4]), Some((0, 0.4)));
+        assert_eq!(argmax(&[-3.0, -2.0]), Some((1, -2.0)));
+        assert_eq!(argmax(&[]), None);
+    }
+
+    #[test]
+    fn multi_label_threshold() {
+        assert_eq!(sigmoid(0.0), 0.5);
+        let labels = multi_label(&[2.0, 0.0, -2.0, 5.0], 0.5);
+        assert_eq!(labels.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [0, 3]);
+        assert_close(&[labels[0].1], &[sigmoid(2.0)]);
+        assert_eq!(multi_label(&[2.0, 0.0, -2.0], 0.1).len(), 3);
+        assert!(multi_label(&[2.0, 0.0, -2.0], 0.9).is_empty());
+    }
+
+    #[test]
+    fn label_file() {
+        let labels = LabelFile::parse("black; blue ;red\n\n  \ncoupe;sedan;suv\r\n");
+        assert_eq!(labels.num_attributes(), 2);
+        assert_eq!(labels.attribute(0), ["black", "blue", "red"]);
+        assert_eq!(labels.label(0, 1), Some("blue"));
+        assert_eq!(labels.label(1, 2), Some("suv"));
+        assert_eq!(labels.label(1, 3), None);
+        assert!(labels.attribute(2).is_empty());
+        assert_eq!(labels.label(2, 0), None);
+
+        let labels = LabelFile::parse("car\nbicycle\n\nperson\nroad_sign\n");
+        assert_eq!(
+            labels.detector_labels(),
+            ["car", "bicycle", "person", "road_sign"]
+        );
+        assert_eq!(LabelFile::parse(""), LabelFile::default());
+    }
+
+    #[test]
+    fn descriptions() {
+        let labels = LabelFile::parse("black;blue;red\ncoupe;sedan;suv");
+        let classifications = [
+            Classification::new(0, 2, 0.9, Some(&labels)),
+            Classification::new(1, 5, 0.8, Some(&labels)),
+            Classification::new(1, 1, 0.7, Some(&labels)),
+            Classification::new(0, 0, 0.6, None),
+        ];
+        assert_eq!(classifications[0].attribute_label.as_deref(), Some("red"));
+        assert_eq!(classifications[1].attribute_label, None);
+        assert_eq!(description(&classifications), "red sedan");
+        assert_eq!(description(&[]), "");
+    }
+}
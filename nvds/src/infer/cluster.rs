@@ -0,0 +1,751 @@
+//! Filtering and clustering of detections as done by nvinfer after parsing, for custom
+//! parsers and probes on tensor output meta. Parameters are named after the keys of the
+//! nvinfer configuration.
+//!
+//! Clustering only merges or suppresses detections of the same class.
+
+use super::ObjectDetectionInfo;
+use crate::WrapperExt;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// `cluster-mode`
+#[repr(u32)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClusterMode {
+    #[default]
+    GroupRectangles = 0,
+    Dbscan = 1,
+    Nms = 2,
+    DbscanNmsHybrid = 3,
+    None = 4,
+}
+
+impl ClusterMode {
+    pub fn from_u32(v: u32) -> Option<ClusterMode> {
+        match v {
+            0 => Some(ClusterMode::GroupRectangles),
+            1 => Some(ClusterMode::Dbscan),
+            2 => Some(ClusterMode::Nms),
+            3 => Some(ClusterMode::DbscanNmsHybrid),
+            4 => Some(ClusterMode::None),
+            _ => None,
+        }
+    }
+}
+
+/// Detection parameters of a `[class-attrs-*]` group.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClassAttrs {
+    /// `pre-cluster-threshold`
+    pub pre_cluster_threshold: f32,
+    /// `post-cluster-threshold`
+    pub post_cluster_threshold: f32,
+    /// `nms-iou-threshold`
+    pub nms_iou_threshold: f32,
+    /// `topk`, detections kept by NMS, all if `None`.
+    pub topk: Option<usize>,
+    /// `eps`
+    pub eps: f32,
+    /// `group-threshold`
+    pub group_threshold: u32,
+    /// `minBoxes`
+    pub min_boxes: u32,
+    /// `dbscan-min-score`
+    pub dbscan_min_score: f32,
+    /// `detected-min-w`
+    pub detected_min_w: f32,
+    /// `detected-min-h`
+    pub detected_min_h: f32,
+    /// `detected-max-w`, no limit if 0.
+    pub detected_max_w: f32,
+    /// `detected-max-h`, no limit if 0.
+    pub detected_max_h: f32,
+    /// `roi-top-offset`
+    pub roi_top_offset: f32,
+    /// `roi-bottom-offset`
+    pub roi_bottom_offset: f32,
+}
+
+impl Default for ClassAttrs {
+    fn default() -> Self {
+        ClassAttrs {
+            pre_cluster_threshold: 0.2,
+            post_cluster_threshold: 0.0,
+            nms_iou_threshold: 0.3,
+            topk: None,
+            eps: 0.0,
+            group_threshold: 0,
+            min_boxes: 0,
+            dbscan_min_score: 0.0,
+            detected_min_w: 0.0,
+            detected_min_h: 0.0,
+            detected_max_w: 0.0,
+            detected_max_h: 0.0,
+            roi_top_offset: 0.0,
+            roi_bottom_offset: 0.0,
+        }
+    }
+}
+
+impl ClassAttrs {
+    /// Whether `o` passes the size limits and lies within the RoI of a frame of
+    /// `frame_height`, in the coordinates of `o`.
+    pub fn accepts(&self, o: &ObjectDetectionInfo, frame_height: f32) -> bool {
+        o.width() >= self.detected_min_w
+            && o.height() >= self.detected_min_h
+            && (self.detected_max_w <= 0.0 || o.width() <= self.detected_max_w)
+            && (self.detected_max_h <= 0.0 || o.height() <= self.detected_max_h)
+            && o.top() >= self.roi_top_offset
+            && o.top() + o.height() <= frame_height - self.roi_bottom_offset
+    }
+}
+
+/// `cluster-mode` with the `[class-attrs-all]` and `[class-attrs-<class-id>]` groups.
+#[derive(Clone, Default, Debug)]
+pub struct ClusterConfig {
+    pub cluster_mode: ClusterMode,
+    pub class_attrs_all: ClassAttrs,
+    pub class_attrs: HashMap<u32, ClassAttrs>,
+}
+
+impl ClusterConfig {
+    pub fn new(cluster_mode: ClusterMode) -> ClusterConfig {
+        ClusterConfig {
+            cluster_mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn attrs(&self, class_id: u32) -> &ClassAttrs {
+        self.class_attrs
+            .get(&class_id)
+            .unwrap_or(&self.class_attrs_all)
+    }
+
+    /// Pre-cluster threshold, size and RoI filtering, clustering and post-cluster
+    /// threshold, with the attributes of the class of every detection.
+    pub fn cluster(
+        &self,
+        objects: Vec<ObjectDetectionInfo>,
+        frame_height: f32,
+    ) -> Vec<ObjectDetectionInfo> {
+        let mut classes = BTreeMap::<u32, Vec<ObjectDetectionInfo>>::new();
+        for o in objects {
+            let attrs = self.attrs(o.class_id());
+            if o.detection_confidence() >= attrs.pre_cluster_threshold
+                && attrs.accepts(&o, frame_height)
+            {
+                classes.entry(o.class_id()).or_default().push(o);
+            }
+        }
+
+        classes
+            .into_iter()
+            .flat_map(|(class_id, objects)| {
+                let a = self.attrs(class_id);
+                let mut objects = match self.cluster_mode {
+                    ClusterMode::GroupRectangles => {
+                        group_rectangles(objects, a.group_threshold, a.eps)
+                    }
+                    ClusterMode::Dbscan => dbscan(objects, a.eps, a.min_boxes, a.dbscan_min_score),
+                    ClusterMode::Nms => nms(objects, a.nms_iou_threshold, a.topk),
+                    ClusterMode::DbscanNmsHybrid => dbscan_nms(
+                        objects,
+                        a.eps,
+                        a.min_boxes,
+                        a.dbscan_min_score,
+                        a.nms_iou_threshold,
+                        a.topk,
+                    ),
+                    ClusterMode::None => objects,
+                };
+                objects.retain(|o| o.detection_confidence() >= a.post_cluster_threshold);
+                objects
+            })
+            .collect()
+    }
+}
+
+pub fn area(o: &ObjectDetectionInfo) -> f32 {
+    o.width().max(0.0) * o.height().max(0.0)
+}
+
+/// Intersection over union of two boxes.
+pub fn iou(a: &ObjectDetectionInfo, b: &ObjectDetectionInfo) -> f32 {
+    let w = (a.left() + a.width()).min(b.left() + b.width()) - a.left().max(b.left());
+    let h = (a.top() + a.height()).min(b.top() + b.height()) - a.top().max(b.top());
+    if w <= 0.0 || h <= 0.0 {
+        return 0.0;
+    }
+    let inter = w * h;
+    let union = area(a) + area(b) - inter;
+    if union > 0.0 {
+        inter / union
+    } else {
+        0.0
+    }
+}
+
+fn sort_by_confidence(objects: &mut [ObjectDetectionInfo]) {
+    objects.sort_by(|a, b| {
+        b.detection_confidence()
+            .total_cmp(&a.detection_confidence())
+    });
+}
+
+fn group_by_class(objects: Vec<ObjectDetectionInfo>) -> BTreeMap<u32, Vec<ObjectDetectionInfo>> {
+    let mut classes = BTreeMap::<u32, Vec<ObjectDetectionInfo>>::new();
+    for o in objects {
+        classes.entry(o.class_id()).or_default().push(o);
+    }
+    classes
+}
+
+/// Keeps detections of every class with a confidence of at least the threshold of the
+/// class, e.g. [`DetectionParams::pre_cluster_thresholds`]. Classes without a threshold
+/// are dropped.
+///
+/// [`DetectionParams::pre_cluster_thresholds`]: crate::infer::parse::DetectionParams::pre_cluster_thresholds
+pub fn filter_thresholds(
+    mut objects: Vec<ObjectDetectionInfo>,
+    thresholds: &[f32],
+) -> Vec<ObjectDetectionInfo> {
+    objects.retain(|o| {
+        thresholds
+            .get(o.class_id() as usize)
+            .is_some_and(|&t| o.detection_confidence() >= t)
+    });
+    objects
+}
+
+/// Keeps detections of at least `min_w` x `min_h`.
+pub fn filter_min_size(
+    mut objects: Vec<ObjectDetectionInfo>,
+    min_w: f32,
+    min_h: f32,
+) -> Vec<ObjectDetectionInfo> {
+    objects.retain(|o| o.width() >= min_w && o.height() >= min_h);
+    objects
+}
+
+/// The `k` detections of highest confidence of all classes, sorted by confidence.
+pub fn top_k(mut objects: Vec<ObjectDetectionInfo>, k: usize) -> Vec<ObjectDetectionInfo> {
+    sort_by_confidence(&mut objects);
+    objects.truncate(k);
+    objects
+}
+
+/// Greedy non-maximum suppression: detections overlapping a detection of higher
+/// confidence by more than `nms_iou_threshold` are removed, then at most `topk`
+/// detections are kept for every class.
+pub fn nms(
+    objects: Vec<ObjectDetectionInfo>,
+    nms_iou_threshold: f32,
+    topk: Option<usize>,
+) -> Vec<ObjectDetectionInfo> {
+    group_by_class(objects)
+        .into_values()
+        .flat_map(|mut objects| {
+            sort_by_confidence(&mut objects);
+            let mut kept = Vec::<ObjectDetectionInfo>::new();
+            for o in objects {
+                if topk.is_some_and(|k| kept.len() >= k) {
+                    break;
+                }
+                if kept.iter().all(|k| iou(k, &o) <= nms_iou_threshold) {
+                    kept.push(o);
+                }
+            }
+            kept
+        })
+        .collect()
+}
+
+/// Confidence decay of [`soft_nms`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SoftNms {
+    /// Scales by `1 - iou` the confidence of detections overlapping by more than
+    /// `iou_threshold`.
+    Linear { iou_threshold: f32 },
+    /// Scales the confidence of overlapping detections by `exp(-iou² / sigma)`.
+    Gaussian { sigma: f32 },
+}
+
+impl SoftNms {
+    fn weight(&self, iou: f32) -> f32 {
+        match *self {
+            SoftNms::Linear { iou_threshold } => {
+                if iou > iou_threshold {
+                    1.0 - iou
+                } else {
+                    1.0
+                }
+            }
+            SoftNms::Gaussian { sigma } => (-iou * iou / sigma).exp(),
+        }
+    }
+}
+
+/// Soft non-maximum suppression: instead of being removed, detections overlapping a
+/// detection of higher confidence have their confidence decayed, and are removed once
+/// it falls below `score_threshold`.
+pub fn soft_nms(
+    objects: Vec<ObjectDetectionInfo>,
+    method: SoftNms,
+    score_threshold: f32,
+) -> Vec<ObjectDetectionInfo> {
+    group_by_class(objects)
+        .into_values()
+        .flat_map(|mut remaining| {
+            remaining.retain(|o| o.detection_confidence() >= score_threshold);
+            let mut kept = Vec::with_capacity(remaining.len());
+            while let Some(best) = remaining
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| {
+                    a.detection_confidence()
+                        .total_cmp(&b.detection_confidence())
+                })
+                .map(|(i, _)| i)
+            {
+                let best = remaining.swap_remove(best);
+                for o in remaining.iter_mut() {
+                    o.as_native_type_mut().detectionConfidence *= method.weight(iou(&best, o));
+                }
+                remaining.retain(|o| o.detection_confidence() >= score_threshold);
+                kept.push(best);
+            }
+            kept
+        })
+        .collect()
+}
+
+/// Clusters of a single class, as indices into `objects`. Detections are neighbours if
+/// `1 - iou` is at most `eps`, and a cluster is grown from every detection with at least
+/// `min_boxes` neighbours, itself included. Detections which are not part of a cluster
+/// are noise.
+fn dbscan_clusters(objects: &[ObjectDetectionInfo], eps: f32, min_boxes: u32) -> Vec<Vec<usize>> {
+    let neighbours = |i: usize| {
+        (0..objects.len())
+            .filter(|&j| 1.0 - iou(&objects[i], &objects[j]) <= eps)
+            .collect::<Vec<_>>()
+    };
+
+    let mut visited = vec![false; objects.len()];
+    let mut assigned = vec![false; objects.len()];
+    let mut clusters = Vec::new();
+    for i in 0..objects.len() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        let n = neighbours(i);
+        if n.len() < min_boxes as usize {
+            continue;
+        }
+
+        let mut cluster = vec![i];
+        assigned[i] = true;
+        let mut queue = VecDeque::from(n);
+        while let Some(j) = queue.pop_front() {
+            if !assigned[j] {
+                assigned[j] = true;
+                cluster.push(j);
+            }
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+            let n = neighbours(j);
+            if n.len() >= min_boxes as usize {
+                queue.extend(n);
+            }
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// DBSCAN clustering: every cluster whose summed confidence is at least
+/// `dbscan_min_score` is merged into its confidence-weighted mean box, with the highest
+/// confidence of the cluster. Noise is removed.
+pub fn dbscan(
+    objects: Vec<ObjectDetectionInfo>,
+    eps: f32,
+    min_boxes: u32,
+    dbscan_min_score: f32,
+) -> Vec<ObjectDetectionInfo> {
+    group_by_class(objects)
+        .into_iter()
+        .flat_map(|(class_id, objects)| {
+            dbscan_clusters(&objects, eps, min_boxes)
+                .into_iter()
+                .filter_map(|cluster| {
+                    let members = cluster.iter().map(|&i| &objects[i]);
+                    let sum = members
+                        .clone()
+                        .map(|o| o.detection_confidence())
+                        .sum::<f32>();
+                    if sum < dbscan_min_score || sum <= 0.0 {
+                        return None;
+                    }
+                    let mean = |f: fn(&ObjectDetectionInfo) -> f32| {
+                        members
+                            .clone()
+                            .map(|o| f(o) * o.detection_confidence())
+                            .sum::<f32>()
+                            / sum
+                    };
+                    let confidence = members
+                        .clone()
+                        .map(|o| o.detection_confidence())
+                        .fold(f32::MIN, f32::max);
+                    Some(ObjectDetectionInfo::new(
+                        class_id,
+                        mean(ObjectDetectionInfo::left),
+                        mean(ObjectDetectionInfo::top),
+                        mean(ObjectDetectionInfo::width),
+                        mean(ObjectDetectionInfo::height),
+                        confidence,
+                    ))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// DBSCAN followed by NMS: noise and clusters below `dbscan_min_score` are removed, and
+/// NMS is applied to the detections of the remaining clusters instead of merging them.
+pub fn dbscan_nms(
+    objects: Vec<ObjectDetectionInfo>,
+    eps: f32,
+    min_boxes: u32,
+    dbscan_min_score: f32,
+    nms_iou_threshold: f32,
+    topk: Option<usize>,
+) -> Vec<ObjectDetectionInfo> {
+    let clustered = group_by_class(objects)
+        .into_values()
+        .flat_map(|objects| {
+            dbscan_clusters(&objects, eps, min_boxes)
+                .into_iter()
+                .filter(|cluster| {
+                    cluster
+                        .iter()
+                        .map(|&i| objects[i].detection_confidence())
+                        .sum::<f32>()
+                        >= dbscan_min_score
+                })
+                .flatten()
+                .map(|i| objects[i])
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    nms(clustered, nms_iou_threshold, topk)
+}
+
+/// Rectangle grouping of OpenCV's `groupRectangles`: detections whose edges are within
+/// `eps` of their mean size are grouped, groups of at most `group_threshold` detections
+/// are removed, and groups nested into a larger group, with a margin of `eps` times its
+/// size, are removed. Every group is replaced by its mean box, with the highest
+/// confidence of the group.
+///
+/// Detections are returned unchanged if `group_threshold` is 0.
+pub fn group_rectangles(
+    objects: Vec<ObjectDetectionInfo>,
+    group_threshold: u32,
+    eps: f32,
+) -> Vec<ObjectDetectionInfo> {
+    if group_threshold == 0 {
+        return objects;
+    }
+
+    let similar = |a: &ObjectDetectionInfo, b: &ObjectDetectionInfo| {
+        let delta = eps * (a.width().min(b.width()) + a.height().min(b.height())) * 0.5;
+        (a.left() - b.left()).abs() <= delta
+            && (a.top() - b.top()).abs() <= delta
+            && (a.left() + a.width() - b.left() - b.width()).abs() <= delta
+            && (a.top() + a.height() - b.top() - b.height()).abs() <= delta
+    };
+
+    group_by_class(objects)
+        .into_iter()
+        .flat_map(|(class_id, objects)| {
+            let mut labels = (0..objects.len()).collect::<Vec<_>>();
+            fn root(labels: &mut [usize], mut i: usize) -> usize {
+                while labels[i] != i {
+                    labels[i] = labels[labels[i]];
+                    i = labels[i];
+                }
+                i
+            }
+            for i in 0..objects.len() {
+                for j in i + 1..objects.len() {
+                    if similar(&objects[i], &objects[j]) {
+                        let (a, b) = (root(&mut labels, i), root(&mut labels, j));
+                        labels[a] = b;
+                    }
+                }
+            }
+
+            let mut groups = BTreeMap::<usize, Vec<&ObjectDetectionInfo>>::new();
+            for (i, o) in objects.iter().enumerate() {
+                groups.entry(root(&mut labels, i)).or_default().push(o);
+            }
+            let groups = groups
+                .into_values()
+                .map(|members| {
+                    let n = members.len() as f32;
+                    let mean = |f: fn(&ObjectDetectionInfo) -> f32| {
+                        members.iter().map(|o| f(o)).sum::<f32>() / n
+                    };
+                    let confidence = members
+                        .iter()
+                        .map(|o| o.detection_confidence())
+                        .fold(f32::MIN, f32::max);
+                    let o = ObjectDetectionInfo::new(
+                        class_id,
+                        mean(ObjectDetectionInfo::left),
+                        mean(ObjectDetectionInfo::top),
+                        mean(ObjectDetectionInfo::width),
+                        mean(ObjectDetectionInfo::height),
+                        confidence,
+                    );
+                    (o, members.len())
+                })
+                .filter(|&(_, n)| n > group_threshold as usize)
+                .collect::<Vec<_>>();
+
+            groups
+                .iter()
+                .filter(|(r1, n1)| {
+                    !groups.iter().any(|(r2, n2)| {
+                        let dx = r2.width() * eps;
+                        let dy = r2.height() * eps;
+                        !std::ptr::eq(r1, r2)
+                            && (*n2 > 3.max(*n1) || *n1 < 3)
+                            && r1.left() >= r2.left() - dx
+                            && r1.top() >= r2.top() - dy
+                            && r1.left() + r1.width() <= r2.left() + r2.width() + dx
+                            && r1.top() + r1.height() <= r2.top() + r2.height() + dy
+                    })
+                })
+                .map(|&(o, _)| o)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn det(class_id: u32, left: f32, top: f32, size: f32, confidence: f32) -> ObjectDetectionInfo {
+        ObjectDetectionInfo::new(class_id, left, top, size, size, confidence)
+    }
+
+    fn confidences(objects: &[ObjectDetectionInfo]) -> Vec<f32> {
+        objects.iter().map(|o| o.detection_confidence()).collect()
+    }
+
+    #[test]
+    fn iou_of_boxes() {
+        let a = det(0, 0.0, 0.0, 10.0, 1.0);
+        assert_eq!(iou(&a, &a), 1.0);
+        assert_eq!(iou(&a, &det(0, 5.0, 0.0, 10.0, 1.0)), 50.0 / 150.0);
+        assert_eq!(iou(&a, &det(0, 10.0, 0.0, 10.0, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn nms_with_topk() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.9),
+            det(0, 1.0, 1.0, 10.0, 0.8),
+            det(0, 50.0, 50.0, 10.0, 0.7),
+            det(0, 80.0, 80.0, 10.0, 0.6),
+            det(1, 0.0, 0.0, 10.0, 0.5),
+        ];
+        assert_eq!(
+            confidences(&nms(objects.clone(), 0.5, None)),
+            [0.9, 0.7, 0.6, 0.5]
+        );
+        assert_eq!(confidences(&nms(objects.clone(), 0.7, None)).len(), 5);
+        assert_eq!(
+            confidences(&nms(objects.clone(), 0.5, Some(2))),
+            [0.9, 0.7, 0.5]
+        );
+        assert_eq!(confidences(&nms(objects, 0.5, Some(0))), [] as [f32; 0]);
+    }
+
+    #[test]
+    fn soft_nms_decay() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.9),
+            det(0, 1.0, 1.0, 10.0, 0.8),
+            det(0, 50.0, 50.0, 10.0, 0.7),
+        ];
+        let overlap = iou(&objects[0], &objects[1]);
+
+        let r = soft_nms(objects.clone(), SoftNms::Linear { iou_threshold: 0.3 }, 0.1);
+        assert_eq!(confidences(&r), [0.9, 0.7, 0.8 * (1.0 - overlap)]);
+        let r = soft_nms(objects.clone(), SoftNms::Linear { iou_threshold: 0.3 }, 0.5);
+        assert_eq!(confidences(&r), [0.9, 0.7]);
+        let r = soft_nms(objects.clone(), SoftNms::Linear { iou_threshold: 0.9 }, 0.5);
+        assert_eq!(confidences(&r), [0.9, 0.8, 0.7]);
+
+        let r = soft_nms(objects, SoftNms::Gaussian { sigma: 0.5 }, 0.1);
+        let decayed = 0.8 * (-overlap * overlap / 0.5).exp();
+        assert_eq!(confidences(&r), [0.9, 0.7, decayed]);
+    }
+
+    #[test]
+    fn dbscan_noise() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.9),
+            det(0, 1.0, 1.0, 10.0, 0.3),
+            det(0, 0.0, 1.0, 10.0, 0.6),
+            det(0, 50.0, 50.0, 10.0, 0.7),
+            det(1, 80.0, 80.0, 10.0, 0.8),
+        ];
+
+        let r = dbscan(objects.clone(), 0.5, 3, 0.0);
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].class_id(), 0);
+        assert_eq!(r[0].detection_confidence(), 0.9);
+        assert!((r[0].left() - 0.3 / 1.8).abs() < 1e-5);
+        assert!((r[0].top() - 0.9 / 1.8).abs() < 1e-5);
+
+        assert!(dbscan(objects.clone(), 0.5, 3, 2.0).is_empty());
+        assert_eq!(
+            confidences(&dbscan(objects.clone(), 0.5, 1, 0.0)),
+            [0.9, 0.7, 0.8]
+        );
+        assert!(dbscan(objects.clone(), 0.0, 2, 0.0).is_empty());
+
+        let r = dbscan_nms(objects.clone(), 0.5, 3, 0.0, 0.5, None);
+        assert_eq!(confidences(&r), [0.9]);
+        let r = dbscan_nms(objects.clone(), 0.5, 3, 0.0, 0.95, None);
+        assert_eq!(confidences(&r), [0.9, 0.6, 0.3]);
+        let r = dbscan_nms(objects.clone(), 0.5, 3, 0.0, 0.95, Some(2));
+        assert_eq!(confidences(&r), [0.9, 0.6]);
+        assert!(dbscan_nms(objects, 0.5, 3, 2.0, 0.5, None).is_empty());
+    }
+
+    #[test]
+    fn group_rectangles_threshold() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.5),
+            det(0, 1.0, 1.0, 10.0, 0.9),
+            det(0, 50.0, 50.0, 10.0, 0.7),
+            det(1, 0.0, 0.0, 10.0, 0.6),
+        ];
+        assert_eq!(group_rectangles(objects.clone(), 0, 0.2).len(), 4);
+
+        let r = group_rectangles(objects.clone(), 1, 0.2);
+        assert_eq!(r.len(), 1);
+        assert_eq!(
+            (r[0].class_id(), r[0].left(), r[0].top(), r[0].width()),
+            (0, 0.5, 0.5, 10.0)
+        );
+        assert_eq!(r[0].detection_confidence(), 0.9);
+
+        assert!(group_rectangles(objects.clone(), 2, 0.2).is_empty());
+        assert!(group_rectangles(objects, 1, 0.01).is_empty());
+
+        let nested = vec![
+            det(0, 0.0, 0.0, 100.0, 0.5),
+            det(0, 1.0, 1.0, 100.0, 0.5),
+            det(0, 0.0, 1.0, 100.0, 0.5),
+            det(0, 1.0, 0.0, 100.0, 0.5),
+            det(0, 10.0, 10.0, 20.0, 0.8),
+            det(0, 11.0, 11.0, 20.0, 0.8),
+        ];
+        let r = group_rectangles(nested, 1, 0.2);
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].width(), 100.0);
+    }
+
+    #[test]
+    fn group_rectangles_nested_eps() {
+        // A group of 100x100 boxes at (0.5, 0.5) and a group of 20x20 boxes sticking
+        // out of its left edge by 10 or 30 pixels.
+        let nested = |left: f32| {
+            vec![
+                det(0, 0.0, 0.0, 100.0, 0.5),
+                det(0, 1.0, 1.0, 100.0, 0.5),
+                det(0, 0.0, 1.0, 100.0, 0.5),
+                det(0, 1.0, 0.0, 100.0, 0.5),
+                det(0, left, 10.0, 20.0, 0.8),
+                det(0, left + 1.0, 11.0, 20.0, 0.8),
+            ]
+        };
+        assert_eq!(group_rectangles(nested(-10.0), 1, 0.05).len(), 2);
+        assert_eq!(group_rectangles(nested(-10.0), 1, 0.2).len(), 1);
+        assert_eq!(group_rectangles(nested(-30.0), 1, 0.2).len(), 2);
+        let r = group_rectangles(nested(-30.0), 1, 0.5);
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].width(), 100.0);
+    }
+
+    #[test]
+    fn cluster_per_class_attrs() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.9),
+            det(0, 1.0, 1.0, 10.0, 0.8),
+            det(0, 50.0, 50.0, 10.0, 0.3),
+            det(1, 0.0, 0.0, 10.0, 0.9),
+            det(1, 1.0, 1.0, 10.0, 0.8),
+            det(1, 40.0, 80.0, 10.0, 0.5),
+            det(2, 0.0, 0.0, 4.0, 0.9),
+        ];
+        let mut config = ClusterConfig::new(ClusterMode::Nms);
+        config.class_attrs_all.nms_iou_threshold = 0.5;
+        config.class_attrs_all.detected_min_w = 5.0;
+        config.class_attrs.insert(
+            1,
+            ClassAttrs {
+                pre_cluster_threshold: 0.6,
+                nms_iou_threshold: 0.9,
+                roi_bottom_offset: 15.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(config.attrs(0), &config.class_attrs_all);
+        assert_eq!(config.attrs(1).nms_iou_threshold, 0.9);
+
+        let r = config.cluster(objects.clone(), 100.0);
+        let classes: Vec<_> = r
+            .iter()
+            .map(|o| (o.class_id(), o.detection_confidence()))
+            .collect();
+        assert_eq!(classes, [(0, 0.9), (0, 0.3), (1, 0.9), (1, 0.8)]);
+
+        config.class_attrs_all.post_cluster_threshold = 0.5;
+        config.class_attrs.get_mut(&1).unwrap().roi_bottom_offset = 0.0;
+        let r = config.cluster(objects.clone(), 100.0);
+        assert_eq!(confidences(&r), [0.9, 0.9, 0.8]);
+
+        config.cluster_mode = ClusterMode::None;
+        assert_eq!(config.cluster(objects, 100.0).len(), 4);
+    }
+
+    #[test]
+    fn filters() {
+        let objects = vec![
+            det(0, 0.0, 0.0, 10.0, 0.9),
+            det(1, 0.0, 0.0, 20.0, 0.4),
+            det(2, 0.0, 0.0, 30.0, 0.8),
+        ];
+        assert_eq!(
+            confidences(&filter_thresholds(objects.clone(), &[0.5, 0.3])),
+            [0.9, 0.4]
+        );
+        assert_eq!(
+            confidences(&filter_min_size(objects.clone(), 15.0, 15.0)),
+            [0.4, 0.8]
+        );
+        assert_eq!(confidences(&top_k(objects, 2)), [0.9, 0.8]);
+        assert_eq!(ClusterMode::from_u32(3), Some(ClusterMode::DbscanNmsHybrid));
+        assert_eq!(ClusterMode::from_u32(5), None);
+    }
+}
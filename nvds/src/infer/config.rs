@@ -0,0 +1,944 @@
+//! Configuration file of nvinfer, `config-file-path`, in the key file (INI) or YAML
+//! format.
+//!
+//! Unknown groups and keys are rejected when parsing, and [`InferConfig::validate`]
+//! checks the constraints between keys, so that mistakes are found before nvinfer
+//! loads the file.
+
+use super::cluster::{ClassAttrs, ClusterConfig, ClusterMode};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Ini(String),
+    Yaml(serde_yaml::Error),
+    UnknownGroup(String),
+    UnknownKey {
+        group: String,
+        key: String,
+    },
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    /// None of the model keys is set.
+    MissingModel,
+    Missing {
+        key: &'static str,
+        required_by: &'static str,
+    },
+    MissingFile {
+        key: &'static str,
+        path: PathBuf,
+    },
+    Invalid {
+        key: String,
+        reason: &'static str,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "io error: {}", e),
+            ConfigError::Ini(e) => write!(f, "config error: {}", e),
+            ConfigError::Yaml(e) => write!(f, "yaml error: {}", e),
+            ConfigError::UnknownGroup(group) => write!(f, "unknown group `{}`", group),
+            ConfigError::UnknownKey { group, key } => {
+                write!(f, "unknown key `{}` in `{}`", key, group)
+            }
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "invalid value `{}` for `{}`", value, key)
+            }
+            ConfigError::MissingModel => write!(f, "no model is set"),
+            ConfigError::Missing { key, required_by } => {
+                write!(f, "`{}` is required by `{}`", key, required_by)
+            }
+            ConfigError::MissingFile { key, path } => {
+                write!(f, "`{}` does not exist: {}", key, path.display())
+            }
+            ConfigError::Invalid { key, reason } => write!(f, "invalid `{}`: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Yaml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ConfigError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ConfigError::Yaml(e)
+    }
+}
+
+fn invalid(key: &str, value: &str) -> ConfigError {
+    ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Value of a key, in the syntax of the key file.
+trait Value: Sized {
+    fn parse(s: &str) -> Option<Self>;
+    fn format(&self) -> String;
+}
+
+macro_rules! impl_value_from_str {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn parse(s: &str) -> Option<Self> {
+                    s.trim().parse().ok()
+                }
+
+                fn format(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_value_from_str!(u32, i32, f32, String);
+
+impl Value for PathBuf {
+    fn parse(s: &str) -> Option<Self> {
+        Some(PathBuf::from(s.trim()))
+    }
+
+    fn format(&self) -> String {
+        self.display().to_string()
+    }
+}
+
+impl Value for bool {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn format(&self) -> String {
+        (*self as u32).to_string()
+    }
+}
+
+/// Lists are separated by `;`.
+impl<T: Value> Value for Vec<T> {
+    fn parse(s: &str) -> Option<Self> {
+        s.split(';')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(T::parse)
+            .collect()
+    }
+
+    fn format(&self) -> String {
+        self.iter().map(T::format).collect::<Vec<_>>().join(";")
+    }
+}
+
+macro_rules! config_enum {
+    ($(#[$attr:meta])* $name:ident { $($variant:ident = $value:literal,)* }) => {
+        $(#[$attr])*
+        #[repr(u32)]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum $name {
+            $($variant = $value,)*
+        }
+
+        impl $name {
+            pub fn from_u32(v: u32) -> Option<$name> {
+                match v {
+                    $($value => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl Value for $name {
+            fn parse(s: &str) -> Option<Self> {
+                Self::from_u32(u32::parse(s)?)
+            }
+
+            fn format(&self) -> String {
+                (*self as u32).to_string()
+            }
+        }
+    };
+}
+
+config_enum!(
+    /// `network-mode`
+    NetworkMode {
+        Fp32 = 0,
+        Int8 = 1,
+        Fp16 = 2,
+    }
+);
+
+config_enum!(
+    /// `network-type`
+    NetworkType {
+        Detector = 0,
+        Classifier = 1,
+        Segmentation = 2,
+        InstanceSegmentation = 3,
+        Other = 100,
+    }
+);
+
+config_enum!(
+    /// `process-mode`
+    ProcessMode {
+        Primary = 1,
+        Secondary = 2,
+    }
+);
+
+config_enum!(
+    /// `model-color-format`
+    ModelColorFormat {
+        Rgb = 0,
+        Bgr = 1,
+        Gray = 2,
+    }
+);
+
+impl Value for ClusterMode {
+    fn parse(s: &str) -> Option<Self> {
+        ClusterMode::from_u32(u32::parse(s)?)
+    }
+
+    fn format(&self) -> String {
+        (*self as u32).to_string()
+    }
+}
+
+macro_rules! properties {
+    ($($field:ident: $ty:ty = $key:literal,)*) => {
+        /// `[property]` group.
+        #[derive(Clone, Default, PartialEq, Debug)]
+        pub struct Property {
+            $(
+                #[doc = concat!("`", $key, "`")]
+                pub $field: Option<$ty>,
+            )*
+            /// Other keys known to nvinfer, in [`OTHER_PROPERTY_KEYS`], written back as is.
+            pub other: BTreeMap<String, String>,
+        }
+
+        impl Property {
+            fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+                match key {
+                    $($key => self.$field = Some(Value::parse(value).ok_or_else(|| invalid(key, value))?),)*
+                    _ if OTHER_PROPERTY_KEYS.contains(&key) => {
+                        self.other.insert(key.to_string(), value.to_string());
+                    }
+                    _ => {
+                        return Err(ConfigError::UnknownKey {
+                            group: PROPERTY.to_string(),
+                            key: key.to_string(),
+                        })
+                    }
+                }
+                Ok(())
+            }
+
+            fn entries(&self) -> Vec<(String, String)> {
+                let mut entries = Vec::new();
+                $(
+                    if let Some(v) = &self.$field {
+                        entries.push(($key.to_string(), v.format()));
+                    }
+                )*
+                entries.extend(self.other.iter().map(|(k, v)| (k.clone(), v.clone())));
+                entries
+            }
+        }
+    };
+}
+
+properties!(
+    gpu_id: u32 = "gpu-id",
+    net_scale_factor: f32 = "net-scale-factor",
+    offsets: Vec<f32> = "offsets",
+    model_color_format: ModelColorFormat = "model-color-format",
+    onnx_file: PathBuf = "onnx-file",
+    model_engine_file: PathBuf = "model-engine-file",
+    model_file: PathBuf = "model-file",
+    proto_file: PathBuf = "proto-file",
+    uff_file: PathBuf = "uff-file",
+    tlt_encoded_model: PathBuf = "tlt-encoded-model",
+    tlt_model_key: String = "tlt-model-key",
+    int8_calib_file: PathBuf = "int8-calib-file",
+    labelfile_path: PathBuf = "labelfile-path",
+    custom_network_config: PathBuf = "custom-network-config",
+    batch_size: u32 = "batch-size",
+    network_mode: NetworkMode = "network-mode",
+    network_type: NetworkType = "network-type",
+    process_mode: ProcessMode = "process-mode",
+    num_detected_classes: u32 = "num-detected-classes",
+    interval: u32 = "interval",
+    gie_unique_id: u32 = "gie-unique-id",
+    operate_on_gie_id: i32 = "operate-on-gie-id",
+    operate_on_class_ids: Vec<u32> = "operate-on-class-ids",
+    filter_out_class_ids: Vec<u32> = "filter-out-class-ids",
+    output_blob_names: Vec<String> = "output-blob-names",
+    infer_dims: Vec<u32> = "infer-dims",
+    cluster_mode: ClusterMode = "cluster-mode",
+    maintain_aspect_ratio: bool = "maintain-aspect-ratio",
+    symmetric_padding: bool = "symmetric-padding",
+    custom_lib_path: PathBuf = "custom-lib-path",
+    parse_bbox_func_name: String = "parse-bbox-func-name",
+    parse_bbox_instance_mask_func_name: String = "parse-bbox-instance-mask-func-name",
+    parse_classifier_func_name: String = "parse-classifier-func-name",
+    engine_create_func_name: String = "engine-create-func-name",
+    classifier_threshold: f32 = "classifier-threshold",
+    segmentation_threshold: f32 = "segmentation-threshold",
+    output_tensor_meta: bool = "output-tensor-meta",
+    output_instance_mask: bool = "output-instance-mask",
+    input_object_min_width: u32 = "input-object-min-width",
+    input_object_min_height: u32 = "input-object-min-height",
+    input_object_max_width: u32 = "input-object-max-width",
+    input_object_max_height: u32 = "input-object-max-height",
+    workspace_size: u32 = "workspace-size",
+);
+
+/// Keys of `[property]` without a field in [`Property`].
+pub const OTHER_PROPERTY_KEYS: &[&str] = &[
+    "classifier-async-mode",
+    "classifier-type",
+    "crop-objects-to-roi-boundary",
+    "enable-dbscan",
+    "enable-dla",
+    "force-implicit-batch-dim",
+    "input-tensor-from-meta",
+    "is-classifier",
+    "layer-device-precision",
+    "mean-file",
+    "network-input-order",
+    "output-io-formats",
+    "parse-func",
+    "scaling-compute-hw",
+    "scaling-filter",
+    "secondary-reinfer-interval",
+    "segmentation-output-order",
+    "uff-input-blob-name",
+    "uff-input-dims",
+    "uff-input-order",
+    "use-dla-core",
+];
+
+const PROPERTY: &str = "property";
+const CLASS_ATTRS_ALL: &str = "class-attrs-all";
+const CLASS_ATTRS_PREFIX: &str = "class-attrs-";
+
+fn set_class_attr(
+    attrs: &mut ClassAttrs,
+    group: &str,
+    key: &str,
+    value: &str,
+) -> Result<(), ConfigError> {
+    fn parse<T: Value>(key: &str, value: &str) -> Result<T, ConfigError> {
+        T::parse(value).ok_or_else(|| invalid(key, value))
+    }
+    match key {
+        "threshold" | "pre-cluster-threshold" => attrs.pre_cluster_threshold = parse(key, value)?,
+        "post-cluster-threshold" => attrs.post_cluster_threshold = parse(key, value)?,
+        "nms-iou-threshold" => attrs.nms_iou_threshold = parse(key, value)?,
+        "topk" => attrs.topk = usize::try_from(parse::<i32>(key, value)?).ok(),
+        "eps" => attrs.eps = parse(key, value)?,
+        "group-threshold" => attrs.group_threshold = parse(key, value)?,
+        "minBoxes" => attrs.min_boxes = parse(key, value)?,
+        "dbscan-min-score" => attrs.dbscan_min_score = parse(key, value)?,
+        "detected-min-w" => attrs.detected_min_w = parse(key, value)?,
+        "detected-min-h" => attrs.detected_min_h = parse(key, value)?,
+        "detected-max-w" => attrs.detected_max_w = parse(key, value)?,
+        "detected-max-h" => attrs.detected_max_h = parse(key, value)?,
+        "roi-top-offset" => attrs.roi_top_offset = parse(key, value)?,
+        "roi-bottom-offset" => attrs.roi_bottom_offset = parse(key, value)?,
+        _ => {
+            return Err(ConfigError::UnknownKey {
+                group: group.to_string(),
+                key: key.to_string(),
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Keys of `attrs` which differ from `d`.
+fn class_attr_entries(attrs: &ClassAttrs, d: &ClassAttrs) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut push = |key: &str, value: String, default: bool| {
+        if !default {
+            entries.push((key.to_string(), value));
+        }
+    };
+    push(
+        "pre-cluster-threshold",
+        attrs.pre_cluster_threshold.format(),
+        attrs.pre_cluster_threshold == d.pre_cluster_threshold,
+    );
+    push(
+        "post-cluster-threshold",
+        attrs.post_cluster_threshold.format(),
+        attrs.post_cluster_threshold == d.post_cluster_threshold,
+    );
+    push(
+        "nms-iou-threshold",
+        attrs.nms_iou_threshold.format(),
+        attrs.nms_iou_threshold == d.nms_iou_threshold,
+    );
+    push(
+        "topk",
+        attrs.topk.map_or(-1, |k| k as i64).to_string(),
+        attrs.topk == d.topk,
+    );
+    push("eps", attrs.eps.format(), attrs.eps == d.eps);
+    push(
+        "group-threshold",
+        attrs.group_threshold.format(),
+        attrs.group_threshold == d.group_threshold,
+    );
+    push(
+        "minBoxes",
+        attrs.min_boxes.format(),
+        attrs.min_boxes == d.min_boxes,
+    );
+    push(
+        "dbscan-min-score",
+        attrs.dbscan_min_score.format(),
+        attrs.dbscan_min_score == d.dbscan_min_score,
+    );
+    push(
+        "detected-min-w",
+        attrs.detected_min_w.format(),
+        attrs.detected_min_w == d.detected_min_w,
+    );
+    push(
+        "detected-min-h",
+        attrs.detected_min_h.format(),
+        attrs.detected_min_h == d.detected_min_h,
+    );
+    push(
+        "detected-max-w",
+        attrs.detected_max_w.format(),
+        attrs.detected_max_w == d.detected_max_w,
+    );
+    push(
+        "detected-max-h",
+        attrs.detected_max_h.format(),
+        attrs.detected_max_h == d.detected_max_h,
+    );
+    push(
+        "roi-top-offset",
+        attrs.roi_top_offset.format(),
+        attrs.roi_top_offset == d.roi_top_offset,
+    );
+    push(
+        "roi-bottom-offset",
+        attrs.roi_bottom_offset.format(),
+        attrs.roi_bottom_offset == d.roi_bottom_offset,
+    );
+    entries
+}
+
+/// Configuration of nvinfer: `[property]`, `[class-attrs-all]` and
+/// `[class-attrs-<class-id>]`.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct InferConfig {
+    pub property: Property,
+    pub class_attrs_all: ClassAttrs,
+    pub class_attrs: BTreeMap<u32, ClassAttrs>,
+}
+
+impl InferConfig {
+    fn from_groups<'a>(
+        groups: impl IntoIterator<Item = (&'a str, Vec<(&'a str, String)>)>,
+    ) -> Result<InferConfig, ConfigError> {
+        let mut config = InferConfig::default();
+        // Groups of classes are applied after `class-attrs-all`, whose values they inherit.
+        let mut classes = Vec::new();
+        for (group, entries) in groups {
+            if group == PROPERTY {
+                for (key, value) in &entries {
+                    config.property.set(key, value)?;
+                }
+            } else if group == CLASS_ATTRS_ALL {
+                for (key, value) in &entries {
+                    set_class_attr(&mut config.class_attrs_all, group, key, value)?;
+                }
+            } else if let Some(class_id) = group
+                .strip_prefix(CLASS_ATTRS_PREFIX)
+                .and_then(|id| id.parse::<u32>().ok())
+            {
+                classes.push((group, class_id, entries));
+            } else {
+                return Err(ConfigError::UnknownGroup(group.to_string()));
+            }
+        }
+        for (group, class_id, entries) in classes {
+            let attrs = config
+                .class_attrs
+                .entry(class_id)
+                .or_insert(config.class_attrs_all);
+            for (key, value) in &entries {
+                set_class_attr(attrs, group, key, value)?;
+            }
+        }
+        Ok(config)
+    }
+
+    fn groups(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut groups = vec![(PROPERTY.to_string(), self.property.entries())];
+        let all = class_attr_entries(&self.class_attrs_all, &ClassAttrs::default());
+        if !all.is_empty() {
+            groups.push((CLASS_ATTRS_ALL.to_string(), all));
+        }
+        for (class_id, attrs) in &self.class_attrs {
+            groups.push((
+                format!("{}{}", CLASS_ATTRS_PREFIX, class_id),
+                class_attr_entries(attrs, &self.class_attrs_all),
+            ));
+        }
+        groups
+    }
+
+    pub fn from_ini(s: &str) -> Result<InferConfig, ConfigError> {
+        let ini = ini::Ini::load_from_str(s).map_err(|e| ConfigError::Ini(e.to_string()))?;
+        Self::from_groups(ini.iter().filter_map(|(group, props)| {
+            Some((
+                group?,
+                props.iter().map(|(k, v)| (k, v.to_string())).collect(),
+            ))
+        }))
+    }
+
+    pub fn from_yaml(s: &str) -> Result<InferConfig, ConfigError> {
+        let groups: BTreeMap<String, BTreeMap<String, serde_yaml::Value>> =
+            serde_yaml::from_str(s)?;
+        let groups = groups
+            .iter()
+            .map(|(group, props)| {
+                let entries = props
+                    .iter()
+                    .map(|(k, v)| Ok((k.as_str(), yaml_to_string(k, v)?)))
+                    .collect::<Result<Vec<_>, ConfigError>>()?;
+                Ok((group.as_str(), entries))
+            })
+            .collect::<Result<Vec<_>, ConfigError>>()?;
+        Self::from_groups(groups)
+    }
+
+    /// Parses YAML if the extension is `yml` or `yaml`, INI otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<InferConfig, ConfigError> {
+        let s = std::fs::read_to_string(path.as_ref())?;
+        if is_yaml(path.as_ref()) {
+            Self::from_yaml(&s)
+        } else {
+            Self::from_ini(&s)
+        }
+    }
+
+    pub fn to_ini(&self) -> String {
+        let mut ini = ini::Ini::new();
+        for (group, entries) in self.groups() {
+            let mut section = ini.with_section(Some(group));
+            for (key, value) in entries {
+                section.set(key, value);
+            }
+        }
+        let mut buf = Vec::new();
+        ini.write_to(&mut buf)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(buf).unwrap_or_default()
+    }
+
+    pub fn to_yaml(&self) -> Result<String, ConfigError> {
+        let mut root = serde_yaml::Mapping::new();
+        for (group, entries) in self.groups() {
+            let mut props = serde_yaml::Mapping::new();
+            for (key, value) in entries {
+                props.insert(key.into(), string_to_yaml(value));
+            }
+            root.insert(group.into(), props.into());
+        }
+        Ok(serde_yaml::to_string(&root)?)
+    }
+
+    /// Writes YAML if the extension is `yml` or `yaml`, INI otherwise.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let s = if is_yaml(path.as_ref()) {
+            self.to_yaml()?
+        } else {
+            self.to_ini()
+        };
+        Ok(std::fs::write(path, s)?)
+    }
+
+    /// Attributes of `class_id`, as used by nvinfer.
+    pub fn attrs(&self, class_id: u32) -> &ClassAttrs {
+        self.class_attrs
+            .get(&class_id)
+            .unwrap_or(&self.class_attrs_all)
+    }
+
+    /// Clustering of nvinfer, for [`cluster`](super::cluster) on detections parsed in Rust.
+    pub fn cluster_config(&self) -> ClusterConfig {
+        ClusterConfig {
+            cluster_mode: self.property.cluster_mode.unwrap_or_default(),
+            class_attrs_all: self.class_attrs_all,
+            class_attrs: self.class_attrs.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+
+    /// Checks the constraints between keys.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let p = &self.property;
+        let missing = |key, required_by| Err(ConfigError::Missing { key, required_by });
+        let invalid = |key: &str, reason| {
+            Err(ConfigError::Invalid {
+                key: key.to_string(),
+                reason,
+            })
+        };
+
+        if p.onnx_file.is_none()
+            && p.model_engine_file.is_none()
+            && p.model_file.is_none()
+            && p.uff_file.is_none()
+            && p.tlt_encoded_model.is_none()
+            && p.custom_network_config.is_none()
+            && p.engine_create_func_name.is_none()
+        {
+            return Err(ConfigError::MissingModel);
+        }
+        if p.tlt_encoded_model.is_some() && p.tlt_model_key.is_none() {
+            return missing("tlt-model-key", "tlt-encoded-model");
+        }
+        if p.network_mode == Some(NetworkMode::Int8)
+            && p.int8_calib_file.is_none()
+            && p.model_engine_file.is_none()
+        {
+            return missing("int8-calib-file", "network-mode");
+        }
+        for (set, required_by) in [
+            (p.parse_bbox_func_name.is_some(), "parse-bbox-func-name"),
+            (
+                p.parse_bbox_instance_mask_func_name.is_some(),
+                "parse-bbox-instance-mask-func-name",
+            ),
+            (
+                p.parse_classifier_func_name.is_some(),
+                "parse-classifier-func-name",
+            ),
+            (
+                p.engine_create_func_name.is_some(),
+                "engine-create-func-name",
+            ),
+        ] {
+            if set && p.custom_lib_path.is_none() {
+                return missing("custom-lib-path", required_by);
+            }
+        }
+        if p.batch_size == Some(0) {
+            return invalid("batch-size", "must be at least 1");
+        }
+        if let Some(offsets) = &p.offsets {
+            let channels = match p.model_color_format {
+                Some(ModelColorFormat::Gray) => 1,
+                _ => 3,
+            };
+            if offsets.len() != channels {
+                return invalid(
+                    "offsets",
+                    "must have a value per channel of model-color-format",
+                );
+            }
+        }
+        if p.symmetric_padding == Some(true) && p.maintain_aspect_ratio != Some(true) {
+            return missing("maintain-aspect-ratio", "symmetric-padding");
+        }
+        for (min, max, key) in [
+            (
+                p.input_object_min_width,
+                p.input_object_max_width,
+                "input-object-max-width",
+            ),
+            (
+                p.input_object_min_height,
+                p.input_object_max_height,
+                "input-object-max-height",
+            ),
+        ] {
+            if let (Some(min), Some(max)) = (min, max) {
+                if max > 0 && max < min {
+                    return invalid(key, "smaller than the minimum");
+                }
+            }
+        }
+        if let Some(infer_dims) = &p.infer_dims {
+            if infer_dims.len() != 3 || infer_dims.contains(&0) {
+                return invalid("infer-dims", "must be 3 non-zero dimensions");
+            }
+        }
+
+        let detector = matches!(p.network_type, None | Some(NetworkType::Detector));
+        if detector && p.num_detected_classes.is_none() {
+            return missing("num-detected-classes", "network-type");
+        }
+        if let (Some(n), Some((class_id, _))) =
+            (p.num_detected_classes, self.class_attrs.last_key_value())
+        {
+            if *class_id >= n {
+                return invalid(
+                    &format!("{}{}", CLASS_ATTRS_PREFIX, class_id),
+                    "class id not below num-detected-classes",
+                );
+            }
+        }
+        for attrs in std::iter::once(&self.class_attrs_all).chain(self.class_attrs.values()) {
+            for (key, value) in [
+                ("pre-cluster-threshold", attrs.pre_cluster_threshold),
+                ("post-cluster-threshold", attrs.post_cluster_threshold),
+                ("nms-iou-threshold", attrs.nms_iou_threshold),
+            ] {
+                if !(0.0..=1.0).contains(&value) {
+                    return invalid(key, "must be between 0 and 1");
+                }
+            }
+        }
+        if let Some(t) = p.classifier_threshold {
+            if !(0.0..=1.0).contains(&t) {
+                return invalid("classifier-threshold", "must be between 0 and 1");
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the files of the configuration exist, relative paths being resolved
+    /// from `base_dir` as nvinfer does from the directory of the configuration file.
+    /// `model-engine-file` is skipped since nvinfer builds it when missing.
+    pub fn check_files<P: AsRef<Path>>(&self, base_dir: P) -> Result<(), ConfigError> {
+        let p = &self.property;
+        for (key, path) in [
+            ("onnx-file", &p.onnx_file),
+            ("model-file", &p.model_file),
+            ("proto-file", &p.proto_file),
+            ("uff-file", &p.uff_file),
+            ("tlt-encoded-model", &p.tlt_encoded_model),
+            ("int8-calib-file", &p.int8_calib_file),
+            ("labelfile-path", &p.labelfile_path),
+            ("custom-network-config", &p.custom_network_config),
+            ("custom-lib-path", &p.custom_lib_path),
+        ] {
+            if let Some(path) = path {
+                let path = base_dir.as_ref().join(path);
+                if !path.exists() {
+                    return Err(ConfigError::MissingFile { key, path });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml" | "yaml")
+    )
+}
+
+fn yaml_to_string(key: &str, value: &serde_yaml::Value) -> Result<String, ConfigError> {
+    use serde_yaml::Value as Y;
+    match value {
+        Y::Bool(b) => Ok(b.format()),
+        Y::Number(n) => Ok(n.to_string()),
+        Y::String(s) => Ok(s.clone()),
+        Y::Sequence(v) => Ok(v
+            .iter()
+            .map(|v| yaml_to_string(key, v))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(";")),
+        _ => Err(invalid(key, &format!("{:?}", value))),
+    }
+}
+
+/// Numbers are written as YAML numbers, everything else as strings.
+fn string_to_yaml(value: String) -> serde_yaml::Value {
+    if let Ok(v) = value.parse::<i64>() {
+        v.into()
+    } else if let Some(v) = value.parse::<f64>().ok().filter(|v| v.is_finite()) {
+        v.into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INI: &str = "\
+[property]
+gpu-id=0
+net-scale-factor=0.0039215697906911373
+offsets=0;0;0
+onnx-file=model.onnx
+model-engine-file=model.onnx_b1_gpu0_fp16.engine
+labelfile-path=labels.txt
+batch-size=1
+network-mode=2
+process-mode=1
+num-detected-classes=4
+interval=0
+gie-unique-id=1
+operate-on-class-ids=0;2
+cluster-mode=2
+maintain-aspect-ratio=1
+symmetric-padding=1
+parse-bbox-func-name=NvDsInferParseYolo
+custom-lib-path=libparser.so
+output-blob-names=boxes;scores
+is-classifier=0
+parse-func=4
+scaling-filter=1
+
+[class-attrs-all]
+pre-cluster-threshold=0.25
+nms-iou-threshold=0.45
+topk=300
+
+[class-attrs-2]
+threshold=0.6
+";
+
+    const YAML: &str = "\
+property:
+  gpu-id: 0
+  onnx-file: model.onnx
+  network-type: 1
+  process-mode: 2
+  operate-on-gie-id: 1
+  offsets: [1, 2, 3]
+  maintain-aspect-ratio: true
+  classifier-threshold: 0.5
+  classifier-async-mode: 1
+";
+
+    #[test]
+    fn ini_round_trip() {
+        let config = InferConfig::from_ini(INI).unwrap();
+        let p = &config.property;
+        assert_eq!(p.network_mode, Some(NetworkMode::Fp16));
+        assert_eq!(p.cluster_mode, Some(ClusterMode::Nms));
+        assert_eq!(p.operate_on_class_ids, Some(vec![0, 2]));
+        assert_eq!(
+            p.output_blob_names,
+            Some(vec!["boxes".to_string(), "scores".to_string()])
+        );
+        assert_eq!(p.other["is-classifier"], "0");
+        assert_eq!(p.other["parse-func"], "4");
+        assert_eq!(config.class_attrs_all.topk, Some(300));
+        assert_eq!(config.attrs(2).pre_cluster_threshold, 0.6);
+        assert_eq!(config.attrs(2).nms_iou_threshold, 0.45);
+        assert_eq!(config.attrs(1), &config.class_attrs_all);
+        config.validate().unwrap();
+
+        assert_eq!(InferConfig::from_ini(&config.to_ini()).unwrap(), config);
+        assert_eq!(
+            InferConfig::from_yaml(&config.to_yaml().unwrap()).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        let config = InferConfig::from_yaml(YAML).unwrap();
+        let p = &config.property;
+        assert_eq!(p.network_type, Some(NetworkType::Classifier));
+        assert_eq!(p.offsets, Some(vec![1.0, 2.0, 3.0]));
+        assert_eq!(p.maintain_aspect_ratio, Some(true));
+        assert_eq!(p.operate_on_gie_id, Some(1));
+        config.validate().unwrap();
+
+        assert_eq!(
+            InferConfig::from_yaml(&config.to_yaml().unwrap()).unwrap(),
+            config
+        );
+        assert_eq!(InferConfig::from_ini(&config.to_ini()).unwrap(), config);
+    }
+
+    #[test]
+    fn unknown_and_invalid_keys() {
+        assert!(matches!(
+            InferConfig::from_ini("[property]\nfoo=1\n"),
+            Err(ConfigError::UnknownKey { .. })
+        ));
+        assert!(matches!(
+            InferConfig::from_ini("[bar]\n"),
+            Err(ConfigError::UnknownGroup(_))
+        ));
+        assert!(matches!(
+            InferConfig::from_ini("[property]\nbatch-size=x\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            InferConfig::from_ini("[class-attrs-all]\ntopk=x\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_constraints() {
+        let validate = |s: &str| InferConfig::from_ini(s).unwrap().validate();
+        assert!(matches!(
+            validate("[property]\nnum-detected-classes=2\n"),
+            Err(ConfigError::MissingModel)
+        ));
+        assert!(matches!(
+            validate("[property]\nonnx-file=a\nnum-detected-classes=2\nparse-bbox-func-name=f\n"),
+            Err(ConfigError::Missing {
+                key: "custom-lib-path",
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate("[property]\nonnx-file=a\n"),
+            Err(ConfigError::Missing {
+                key: "num-detected-classes",
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate(
+                "[property]\nonnx-file=a\nnum-detected-classes=2\n[class-attrs-3]\nthreshold=0.1\n"
+            ),
+            Err(ConfigError::Invalid { .. })
+        ));
+        // Operating on other components does not need process-mode=2.
+        validate("[property]\nonnx-file=a\nnum-detected-classes=2\noperate-on-gie-id=1\noperate-on-class-ids=0\n")
+            .unwrap();
+    }
+}
@@ -0,0 +1,583 @@
+//! Custom processors of nvinferserver written in Rust, for extra inputs and
+//! post-processing of outputs such as the states of recurrent networks.
+//!
+//! A processor implements [`CustomProcessor`] and is exported from a `cdylib` under the
+//! name set as `custom_process_funcion` in the `extra` block of the nvinferserver
+//! configuration, along with `custom_lib { path }`:
+//!
+//! ```ignore
+//! use nvidia_deepstream::infer::custom_process::{
+//!     BatchArray, BatchBuffer, CustomProcessor, Options,
+//! };
+//! use nvidia_deepstream::infer::Status;
+//!
+//! struct LstmState {
+//!     state: Vec<f32>,
+//! }
+//!
+//! impl LstmState {
+//!     fn new(_config: &str) -> Result<LstmState, Status> {
+//!         ...
+//!     }
+//! }
+//!
+//! impl CustomProcessor for LstmState {
+//!     fn require_infer_loop(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     fn extra_input_process(
+//!         &mut self,
+//!         _primary_inputs: &[&BatchBuffer],
+//!         extra_inputs: &mut [&mut BatchBuffer],
+//!         _options: Option<&Options>,
+//!     ) -> Result<(), Status> {
+//!         ...
+//!     }
+//!
+//!     fn inference_done(
+//!         &mut self,
+//!         outputs: &BatchArray,
+//!         _options: Option<&Options>,
+//!     ) -> Result<(), Status> {
+//!         ...
+//!     }
+//! }
+//!
+//! nvidia_deepstream::export_custom_processor!(CreateInferServerCustomProcess, LstmState::new);
+//! ```
+
+use super::tensor::{TensorError, TensorView};
+use super::{DataType, Status, TensorElement};
+use crate::meta::BatchMeta;
+use crate::WrapperExt;
+use std::ffi::CString;
+use std::os::raw::c_void;
+
+/// Option key of the unique id of the nvinferserver instance.
+pub const OPTION_NVDS_UNIQUE_ID: &str = "nvds_unique_id";
+/// Option key of the `NvDsBatchMeta` of the batch.
+pub const OPTION_NVDS_BATCH_META: &str = "nvds_batch_meta";
+/// Option key of the source ids of the frames of the batch.
+pub const OPTION_NVDS_STREAM_IDS: &str = "nvds_stream_ids";
+/// Option key of the `GstBuffer` of the batch.
+pub const OPTION_NVDS_GST_BUFFER: &str = "nvds_gst_buffer";
+
+/// `nvdsinferserver::InferMemType`
+#[repr(i32)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemType {
+    None = 0,
+    GpuCuda = 1,
+    #[default]
+    Cpu = 2,
+    CpuCuda = 3,
+    NvSurface = 5,
+    NvSurfaceArray = 6,
+}
+
+impl MemType {
+    pub fn from_i32(v: i32) -> Option<MemType> {
+        match v {
+            0 => Some(MemType::None),
+            1 => Some(MemType::GpuCuda),
+            2 => Some(MemType::Cpu),
+            3 => Some(MemType::CpuCuda),
+            5 => Some(MemType::NvSurface),
+            6 => Some(MemType::NvSurfaceArray),
+            _ => None,
+        }
+    }
+
+    /// Whether the memory is accessible from the CPU.
+    pub fn is_host(&self) -> bool {
+        matches!(self, MemType::Cpu | MemType::CpuCuda)
+    }
+}
+
+/// Input or output tensor of a batch, `nvdsinferserver::IBatchBuffer`.
+#[repr(transparent)]
+pub struct BatchBuffer(nvidia_deepstream_sys::IBatchBuffer);
+
+impl BatchBuffer {
+    /// # Safety
+    /// `ptr` must be a valid `IBatchBuffer` for `'a`.
+    pub unsafe fn from_ptr<'a>(ptr: *const nvidia_deepstream_sys::IBatchBuffer) -> &'a BatchBuffer {
+        &*(ptr as *const BatchBuffer)
+    }
+
+    fn desc(&self) -> nvidia_deepstream_sys::NvDsRsInferBufferDesc {
+        unsafe {
+            let mut desc = std::mem::MaybeUninit::uninit();
+            nvidia_deepstream_sys::nvds_rs_batch_buffer_desc(&self.0, desc.as_mut_ptr());
+            desc.assume_init()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        let desc = self.desc();
+        if desc.name.is_null() {
+            return "";
+        }
+        unsafe {
+            std::str::from_utf8(std::slice::from_raw_parts(desc.name as _, desc.name_len))
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn mem_type(&self) -> Option<MemType> {
+        MemType::from_i32(self.desc().mem_type)
+    }
+
+    pub fn device_id(&self) -> i64 {
+        self.desc().device_id
+    }
+
+    /// Element type, `None` for the types without a counterpart in nvinfer.
+    pub fn data_type(&self) -> Option<DataType> {
+        match self.desc().data_type as u32 {
+            nvidia_deepstream_sys::NvDsInferDataType_FLOAT => Some(DataType::Float),
+            nvidia_deepstream_sys::NvDsInferDataType_HALF => Some(DataType::Half),
+            nvidia_deepstream_sys::NvDsInferDataType_INT8 => Some(DataType::Int8),
+            nvidia_deepstream_sys::NvDsInferDataType_INT32 => Some(DataType::Int32),
+            _ => None,
+        }
+    }
+
+    /// Dimensions, which exclude the batch dimension if [`batch_size`](Self::batch_size)
+    /// is not 0.
+    pub fn dims(&self) -> Vec<usize> {
+        let desc = self.desc();
+        desc.d[..(desc.num_dims as usize).min(desc.d.len())]
+            .iter()
+            .map(|&d| d.max(0) as usize)
+            .collect()
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.desc().num_elements as _
+    }
+
+    /// Size of an element in bytes.
+    pub fn element_size(&self) -> usize {
+        self.desc().element_size as _
+    }
+
+    pub fn is_input(&self) -> bool {
+        self.desc().is_input
+    }
+
+    pub fn batch_size(&self) -> u32 {
+        unsafe { nvidia_deepstream_sys::nvds_rs_batch_buffer_batch_size(&self.0) }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        unsafe { nvidia_deepstream_sys::nvds_rs_batch_buffer_total_bytes(&self.0) }
+    }
+
+    /// Data of the frame `batch_idx` of the batch, in host or device memory depending on
+    /// [`mem_type`](Self::mem_type).
+    pub fn buffer_ptr(&self, batch_idx: u32) -> *mut c_void {
+        unsafe { nvidia_deepstream_sys::nvds_rs_batch_buffer_ptr(&self.0, batch_idx) }
+    }
+
+    fn host_ptr<T: TensorElement>(&self, batch_idx: u32) -> Result<*mut T, TensorError> {
+        if !matches!(self.mem_type(), Some(m) if m.is_host()) {
+            return Err(TensorError::DeviceMemory);
+        }
+        match self.data_type() {
+            Some(actual) if actual != T::DATA_TYPE => Err(TensorError::DataType {
+                expected: T::DATA_TYPE,
+                actual,
+            }),
+            None => Err(TensorError::UnsupportedDataType),
+            _ => {
+                let ptr = self.buffer_ptr(batch_idx) as *mut T;
+                if ptr.is_null() {
+                    Err(TensorError::NullBuffer)
+                } else {
+                    Ok(ptr)
+                }
+            }
+        }
+    }
+
+    /// View of the host data of the frame `batch_idx`, shaped by [`dims`](Self::dims).
+    pub fn tensor<T: TensorElement>(
+        &self,
+        batch_idx: u32,
+    ) -> Result<TensorView<'_, T>, TensorError> {
+        let ptr = self.host_ptr::<T>(batch_idx)?;
+        let data = unsafe { std::slice::from_raw_parts(ptr as *const T, self.num_elements()) };
+        TensorView::new(data, &self.dims())
+    }
+
+    /// Host data of the frame `batch_idx`, e.g. to fill extra inputs.
+    pub fn as_mut_slice<T: TensorElement>(
+        &mut self,
+        batch_idx: u32,
+    ) -> Result<&mut [T], TensorError> {
+        let ptr = self.host_ptr::<T>(batch_idx)?;
+        Ok(unsafe { std::slice::from_raw_parts_mut(ptr, self.num_elements()) })
+    }
+}
+
+/// Output tensors of a batch, `nvdsinferserver::IBatchArray`.
+#[repr(transparent)]
+pub struct BatchArray(nvidia_deepstream_sys::IBatchArray);
+
+impl BatchArray {
+    /// # Safety
+    /// `ptr` must be a valid `IBatchArray` for `'a`.
+    pub unsafe fn from_ptr<'a>(ptr: *const nvidia_deepstream_sys::IBatchArray) -> &'a BatchArray {
+        &*(ptr as *const BatchArray)
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { nvidia_deepstream_sys::nvds_rs_batch_array_size(&self.0) as _ }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&BatchBuffer> {
+        if index >= self.len() {
+            return None;
+        }
+        unsafe {
+            let p = nvidia_deepstream_sys::nvds_rs_batch_array_buffer(&self.0, index as _);
+            (!p.is_null()).then(|| BatchBuffer::from_ptr(p))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BatchBuffer> {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&BatchBuffer> {
+        self.iter().find(|b| b.name() == name)
+    }
+}
+
+/// Options of a batch, `nvdsinferserver::IOptions`.
+#[repr(transparent)]
+pub struct Options(nvidia_deepstream_sys::IOptions);
+
+fn is_success(status: nvidia_deepstream_sys::NvDsInferStatus) -> bool {
+    status == Status::Success as _
+}
+
+impl Options {
+    /// # Safety
+    /// `ptr` must be a valid `IOptions` for `'a`.
+    pub unsafe fn from_ptr<'a>(ptr: *const nvidia_deepstream_sys::IOptions) -> &'a Options {
+        &*(ptr as *const Options)
+    }
+
+    pub fn has_value(&self, key: &str) -> bool {
+        match CString::new(key) {
+            Ok(key) => unsafe {
+                nvidia_deepstream_sys::nvds_rs_options_has_value(&self.0, key.as_ptr())
+            },
+            Err(_) => false,
+        }
+    }
+
+    pub fn int(&self, key: &str) -> Option<i64> {
+        let key = CString::new(key).ok()?;
+        let mut v = 0;
+        is_success(unsafe {
+            nvidia_deepstream_sys::nvds_rs_options_get_int(&self.0, key.as_ptr(), &mut v)
+        })
+        .then_some(v)
+    }
+
+    pub fn uint(&self, key: &str) -> Option<u64> {
+        let key = CString::new(key).ok()?;
+        let mut v = 0;
+        is_success(unsafe {
+            nvidia_deepstream_sys::nvds_rs_options_get_uint(&self.0, key.as_ptr(), &mut v)
+        })
+        .then_some(v)
+    }
+
+    pub fn double(&self, key: &str) -> Option<f64> {
+        let key = CString::new(key).ok()?;
+        let mut v = 0.0;
+        is_success(unsafe {
+            nvidia_deepstream_sys::nvds_rs_options_get_double(&self.0, key.as_ptr(), &mut v)
+        })
+        .then_some(v)
+    }
+
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        let key = CString::new(key).ok()?;
+        let mut v = false;
+        is_success(unsafe {
+            nvidia_deepstream_sys::nvds_rs_options_get_bool(&self.0, key.as_ptr(), &mut v)
+        })
+        .then_some(v)
+    }
+
+    pub fn string(&self, key: &str) -> Option<String> {
+        let key = CString::new(key).ok()?;
+        let mut buf = Vec::<u8>::new();
+        loop {
+            let mut len = 0;
+            let status = unsafe {
+                nvidia_deepstream_sys::nvds_rs_options_get_string(
+                    &self.0,
+                    key.as_ptr(),
+                    buf.as_mut_ptr() as _,
+                    buf.len(),
+                    &mut len,
+                )
+            };
+            if !is_success(status) {
+                return None;
+            }
+            if len <= buf.len() {
+                buf.truncate(len);
+                return String::from_utf8(buf).ok();
+            }
+            buf.resize(len, 0);
+        }
+    }
+
+    pub fn uint_array(&self, key: &str) -> Option<Vec<u64>> {
+        let key = CString::new(key).ok()?;
+        let mut values = Vec::new();
+        loop {
+            let mut len = 0;
+            let status = unsafe {
+                nvidia_deepstream_sys::nvds_rs_options_get_uint_array(
+                    &self.0,
+                    key.as_ptr(),
+                    values.as_mut_ptr(),
+                    values.len(),
+                    &mut len,
+                )
+            };
+            if !is_success(status) {
+                return None;
+            }
+            if len <= values.len() {
+                values.truncate(len);
+                return Some(values);
+            }
+            values.resize(len, 0);
+        }
+    }
+
+    /// Object pointer of `key`, whose type depends on the key.
+    pub fn obj(&self, key: &str) -> Option<*mut c_void> {
+        let key = CString::new(key).ok()?;
+        let mut v = std::ptr::null_mut();
+        is_success(unsafe {
+            nvidia_deepstream_sys::nvds_rs_options_get_obj(&self.0, key.as_ptr(), &mut v)
+        })
+        .then_some(v)
+        .filter(|v| !v.is_null())
+    }
+
+    pub fn unique_id(&self) -> Option<i64> {
+        self.int(OPTION_NVDS_UNIQUE_ID)
+    }
+
+    pub fn stream_ids(&self) -> Option<Vec<u64>> {
+        self.uint_array(OPTION_NVDS_STREAM_IDS)
+    }
+
+    pub fn batch_meta(&self) -> Option<&BatchMeta> {
+        self.obj(OPTION_NVDS_BATCH_META).map(|p| unsafe {
+            BatchMeta::from_native_type_ref(&*(p as *const nvidia_deepstream_sys::NvDsBatchMeta))
+        })
+    }
+
+    pub fn gst_buffer(&self) -> Option<&gstreamer::BufferRef> {
+        self.obj(OPTION_NVDS_GST_BUFFER)
+            .map(|p| unsafe { gstreamer::BufferRef::from_ptr(p as _) })
+    }
+}
+
+/// `nvdsinferserver::IInferCustomProcessor`. Calls are serialized by a lock, so a
+/// processor may keep states between batches.
+pub trait CustomProcessor: Send {
+    /// Memory of the primary inputs given to
+    /// [`extra_input_process`](Self::extra_input_process).
+    fn supported_input_mem_type(&self) -> MemType {
+        MemType::Cpu
+    }
+
+    /// Whether a batch waits for the outputs of the previous one, as needed when the
+    /// outputs are fed back as inputs.
+    fn require_infer_loop(&self) -> bool {
+        false
+    }
+
+    /// Fills `extra_inputs`, the inputs of the model other than the primary ones.
+    fn extra_input_process(
+        &mut self,
+        primary_inputs: &[&BatchBuffer],
+        extra_inputs: &mut [&mut BatchBuffer],
+        options: Option<&Options>,
+    ) -> Result<(), Status>;
+
+    fn inference_done(
+        &mut self,
+        outputs: &BatchArray,
+        options: Option<&Options>,
+    ) -> Result<(), Status>;
+
+    fn notify_error(&mut self, _status: Status) {}
+}
+
+/// C ABI used by [`export_custom_processor!`](crate::export_custom_processor).
+#[allow(clippy::missing_safety_doc)]
+pub mod ffi {
+    use super::*;
+    use nvidia_deepstream_sys::{IBatchArray, IBatchBuffer, IOptions, NvDsInferStatus};
+    use std::panic::AssertUnwindSafe;
+    use std::sync::{Mutex, MutexGuard};
+
+    pub use nvidia_deepstream_sys::IInferCustomProcessor;
+
+    unsafe fn lock<'a, P>(processor: *mut c_void) -> MutexGuard<'a, P> {
+        (*(processor as *const Mutex<P>))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    unsafe fn buffers<'a>(ptrs: *const *mut IBatchBuffer, len: usize) -> &'a [*mut IBatchBuffer] {
+        if ptrs.is_null() || len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(ptrs, len)
+        }
+    }
+
+    fn status(f: impl FnOnce() -> Result<(), Status>) -> NvDsInferStatus {
+        match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(Ok(())) => Status::Success as _,
+            Ok(Err(status)) => status as _,
+            Err(_) => Status::UnknownError as _,
+        }
+    }
+
+    unsafe extern "C" fn drop_processor<P>(processor: *mut c_void) {
+        drop(Box::from_raw(processor as *mut Mutex<P>));
+    }
+
+    unsafe extern "C" fn supported_input_mem_type<P: CustomProcessor>(
+        processor: *mut c_void,
+    ) -> i32 {
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            lock::<P>(processor).supported_input_mem_type()
+        }))
+        .unwrap_or(MemType::Cpu) as _
+    }
+
+    unsafe extern "C" fn require_infer_loop<P: CustomProcessor>(processor: *mut c_void) -> bool {
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            lock::<P>(processor).require_infer_loop()
+        }))
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn extra_input_process<P: CustomProcessor>(
+        processor: *mut c_void,
+        primary_inputs: *const *mut IBatchBuffer,
+        num_primary_inputs: usize,
+        extra_inputs: *const *mut IBatchBuffer,
+        num_extra_inputs: usize,
+        options: *const IOptions,
+    ) -> NvDsInferStatus {
+        let primary_inputs = buffers(primary_inputs, num_primary_inputs)
+            .iter()
+            .map(|&p| &*(p as *const BatchBuffer))
+            .collect::<Vec<_>>();
+        let mut extra_inputs = buffers(extra_inputs, num_extra_inputs)
+            .iter()
+            .map(|&p| &mut *(p as *mut BatchBuffer))
+            .collect::<Vec<_>>();
+        let options = (!options.is_null()).then(|| Options::from_ptr(options));
+        status(|| {
+            lock::<P>(processor).extra_input_process(&primary_inputs, &mut extra_inputs, options)
+        })
+    }
+
+    unsafe extern "C" fn inference_done<P: CustomProcessor>(
+        processor: *mut c_void,
+        outputs: *const IBatchArray,
+        options: *const IOptions,
+    ) -> NvDsInferStatus {
+        if outputs.is_null() {
+            return Status::InvalidParams as _;
+        }
+        let outputs = BatchArray::from_ptr(outputs);
+        let options = (!options.is_null()).then(|| Options::from_ptr(options));
+        status(|| lock::<P>(processor).inference_done(outputs, options))
+    }
+
+    unsafe extern "C" fn notify_error<P: CustomProcessor>(
+        processor: *mut c_void,
+        status: NvDsInferStatus,
+    ) {
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            lock::<P>(processor)
+                .notify_error(Status::from_u32(status).unwrap_or(Status::UnknownError))
+        }));
+    }
+
+    /// Creates the processor with `f` from the configuration of nvinferserver. Returns
+    /// null if `f` fails or panics.
+    pub unsafe fn create_processor<P: CustomProcessor>(
+        f: fn(&str) -> Result<P, Status>,
+        config: *const std::os::raw::c_char,
+        config_len: u32,
+    ) -> *mut IInferCustomProcessor {
+        let config = if config.is_null() {
+            ""
+        } else {
+            std::str::from_utf8(std::slice::from_raw_parts(
+                config as *const u8,
+                config_len as _,
+            ))
+            .unwrap_or_default()
+        };
+        let processor = match std::panic::catch_unwind(|| f(config)) {
+            Ok(Ok(processor)) => processor,
+            _ => return std::ptr::null_mut(),
+        };
+        let vtable = nvidia_deepstream_sys::NvDsRsCustomProcessorVTable {
+            drop: drop_processor::<P>,
+            supported_input_mem_type: supported_input_mem_type::<P>,
+            require_infer_loop: require_infer_loop::<P>,
+            extra_input_process: extra_input_process::<P>,
+            inference_done: inference_done::<P>,
+            notify_error: notify_error::<P>,
+        };
+        nvidia_deepstream_sys::nvds_rs_custom_processor_new(
+            Box::into_raw(Box::new(Mutex::new(processor))) as _,
+            &vtable,
+        )
+    }
+}
+
+/// Exports `$f`, a `fn(&str) -> Result<P, Status>` creating a
+/// [`CustomProcessor`](crate::infer::custom_process::CustomProcessor) from the
+/// configuration, as the `CreateCustomProcessorFunc` named `$name`.
+#[macro_export]
+macro_rules! export_custom_processor {
+    ($name:ident, $f:path) => {
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn $name(
+            config: *const std::os::raw::c_char,
+            config_len: u32,
+        ) -> *mut $crate::infer::custom_process::ffi::IInferCustomProcessor {
+            $crate::infer::custom_process::ffi::create_processor($f, config, config_len)
+        }
+    };
+}
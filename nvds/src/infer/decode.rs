@@ -0,0 +1,864 @@
+//! Decoders of the outputs of common detectors into detections in network pixels, and
+//! their scaling to the source frame.
+//!
+//! Detections can be clustered with [`cluster`](super::cluster) before being scaled and
+//! attached as object meta.
+
+use super::classify::{sigmoid, softmax};
+use super::tensor::{TensorError, TensorView};
+use super::{ObjectDetectionInfo, TensorElement};
+
+/// Scaling of the source frame to the network input, `maintain-aspect-ratio` and
+/// `symmetric-padding` of the nvinfer configuration.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Letterbox {
+    pub network_width: u32,
+    pub network_height: u32,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub maintain_aspect_ratio: bool,
+    pub symmetric_padding: bool,
+}
+
+impl Letterbox {
+    /// The source frame is stretched to the network input.
+    pub fn new(
+        network_width: u32,
+        network_height: u32,
+        source_width: u32,
+        source_height: u32,
+    ) -> Letterbox {
+        Letterbox {
+            network_width,
+            network_height,
+            source_width,
+            source_height,
+            maintain_aspect_ratio: false,
+            symmetric_padding: false,
+        }
+    }
+
+    /// Scaling of `source_frame_width` x `source_frame_height` of `frame_meta`.
+    #[cfg(feature = "meta")]
+    pub fn from_frame_meta(
+        network: &super::NetworkInfo,
+        frame_meta: &crate::meta::FrameMeta,
+    ) -> Letterbox {
+        Letterbox::new(
+            network.width(),
+            network.height(),
+            frame_meta.source_frame_width(),
+            frame_meta.source_frame_height(),
+        )
+    }
+
+    /// The aspect ratio is kept, with padding at the right and bottom, or on both sides if
+    /// `symmetric_padding`.
+    pub fn with_aspect_ratio(mut self, symmetric_padding: bool) -> Letterbox {
+        self.maintain_aspect_ratio = true;
+        self.symmetric_padding = symmetric_padding;
+        self
+    }
+
+    /// Scales and offsets from network to source coordinates.
+    fn transform(&self) -> (f32, f32, f32, f32) {
+        let nw = self.network_width as f32;
+        let nh = self.network_height as f32;
+        let sw = self.source_width as f32;
+        let sh = self.source_height as f32;
+        if !self.maintain_aspect_ratio {
+            return (sw / nw, sh / nh, 0.0, 0.0);
+        }
+        let r = (nw / sw).min(nh / sh);
+        if self.symmetric_padding {
+            (1.0 / r, 1.0 / r, (nw - sw * r) / 2.0, (nh - sh * r) / 2.0)
+        } else {
+            (1.0 / r, 1.0 / r, 0.0, 0.0)
+        }
+    }
+
+    /// The detection in source frame pixels, clipped to the frame.
+    pub fn to_source(&self, o: &ObjectDetectionInfo) -> ObjectDetectionInfo {
+        let (sx, sy, ox, oy) = self.transform();
+        let sw = self.source_width as f32;
+        let sh = self.source_height as f32;
+        let left = ((o.left() - ox) * sx).clamp(0.0, sw);
+        let top = ((o.top() - oy) * sy).clamp(0.0, sh);
+        let right = ((o.left() + o.width() - ox) * sx).clamp(0.0, sw);
+        let bottom = ((o.top() + o.height() - oy) * sy).clamp(0.0, sh);
+        ObjectDetectionInfo::new(
+            o.class_id(),
+            left,
+            top,
+            right - left,
+            bottom - top,
+            o.detection_confidence(),
+        )
+    }
+}
+
+fn argmax(v: impl Iterator<Item = f32>) -> Option<(usize, f32)> {
+    v.enumerate().fold(None, |best, (i, x)| match best {
+        Some((_, b)) if b >= x => best,
+        _ => Some((i, x)),
+    })
+}
+
+fn from_center(
+    class_id: usize,
+    cx: f32,
+    cy: f32,
+    w: f32,
+    h: f32,
+    confidence: f32,
+) -> ObjectDetectionInfo {
+    ObjectDetectionInfo::new(class_id as _, cx - w / 2.0, cy - h / 2.0, w, h, confidence)
+}
+
+fn dims<const N: usize, T: Copy>(t: &TensorView<T>) -> Result<[usize; N], TensorError> {
+    t.shape().try_into().map_err(|_| TensorError::Shape)
+}
+
+/// YOLOv5 output decoded by the exported model, `[num_boxes, 5 + num_classes]` of center,
+/// size, objectness and class scores. The confidence is the objectness times the best
+/// class score.
+pub fn yolov5<T: TensorElement>(
+    output: &TensorView<T>,
+    conf_threshold: f32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [n, c] = dims(output)?;
+    if c < 6 {
+        return Err(TensorError::Shape);
+    }
+    Ok((0..n)
+        .filter_map(|i| {
+            let v = |j: usize| output[[i, j]].to_f32();
+            let objectness = v(4);
+            if objectness < conf_threshold {
+                return None;
+            }
+            let (class_id, score) = argmax((5..c).map(v))?;
+            let confidence = objectness * score;
+            (confidence >= conf_threshold)
+                .then(|| from_center(class_id, v(0), v(1), v(2), v(3), confidence))
+        })
+        .collect())
+}
+
+/// Raw anchor-based YOLOv5 head of one `stride`, `[num_anchors * (5 + num_classes),
+/// grid_height, grid_width]` logits, with `anchors` sizes in network pixels.
+pub fn yolov5_grid<T: TensorElement>(
+    output: &TensorView<T>,
+    stride: f32,
+    anchors: &[[f32; 2]],
+    conf_threshold: f32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [channels, grid_h, grid_w] = dims(output)?;
+    if anchors.is_empty() || channels % anchors.len() != 0 || channels / anchors.len() < 6 {
+        return Err(TensorError::Shape);
+    }
+    let k = channels / anchors.len();
+
+    let mut objects = Vec::new();
+    for (a, anchor) in anchors.iter().enumerate() {
+        for y in 0..grid_h {
+            for x in 0..grid_w {
+                let v = |j: usize| sigmoid(output[[a * k + j, y, x]].to_f32());
+                let objectness = v(4);
+                if objectness < conf_threshold {
+                    continue;
+                }
+                let Some((class_id, score)) = argmax((5..k).map(v)) else {
+                    continue;
+                };
+                let confidence = objectness * score;
+                if confidence < conf_threshold {
+                    continue;
+                }
+                objects.push(from_center(
+                    class_id,
+                    (v(0) * 2.0 - 0.5 + x as f32) * stride,
+                    (v(1) * 2.0 - 0.5 + y as f32) * stride,
+                    (v(2) * 2.0).powi(2) * anchor[0],
+                    (v(3) * 2.0).powi(2) * anchor[1],
+                    confidence,
+                ));
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Anchor-free YOLOv8 output, `[4 + num_classes, num_boxes]` of center, size and class
+/// scores. Outputs of `[num_boxes, 4 + num_classes]` can be passed with
+/// `output.permute(&[1, 0])`.
+pub fn yolov8<T: TensorElement>(
+    output: &TensorView<T>,
+    conf_threshold: f32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [c, n] = dims(output)?;
+    if c < 5 {
+        return Err(TensorError::Shape);
+    }
+    Ok((0..n)
+        .filter_map(|i| {
+            let v = |j: usize| output[[j, i]].to_f32();
+            let (class_id, confidence) = argmax((4..c).map(v))?;
+            (confidence >= conf_threshold)
+                .then(|| from_center(class_id, v(0), v(1), v(2), v(3), confidence))
+        })
+        .collect())
+}
+
+/// Prior boxes of SSD, as generated by `PriorBox` layers.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PriorBoxConfig {
+    /// `[width, height]` of the network input.
+    pub image_size: [u32; 2],
+    /// `[width, height]` of every feature map.
+    pub feature_maps: Vec<[u32; 2]>,
+    /// Stride in pixels of every feature map.
+    pub steps: Vec<f32>,
+    pub min_sizes: Vec<f32>,
+    /// Adds a square prior of `sqrt(min_size * max_size)` if not empty.
+    pub max_sizes: Vec<f32>,
+    /// Aspect ratios other than 1, along with their inverse.
+    pub aspect_ratios: Vec<Vec<f32>>,
+    pub clip: bool,
+}
+
+/// Error of [`PriorBoxConfig::priors`] and [`retinanet_anchors`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PriorBoxError {
+    /// `field` has fewer entries than `feature_maps`.
+    TooShort { field: &'static str, len: usize },
+    /// A dimension of `image_size` is 0.
+    ImageSize,
+    /// A stride of the anchors is 0.
+    Stride,
+}
+
+impl std::fmt::Display for PriorBoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriorBoxError::TooShort { field, len } => {
+                write!(
+                    f,
+                    "{} has {} entries, fewer than the feature maps",
+                    field, len
+                )
+            }
+            PriorBoxError::ImageSize => write!(f, "image size is empty"),
+            PriorBoxError::Stride => write!(f, "stride is 0"),
+        }
+    }
+}
+
+impl std::error::Error for PriorBoxError {}
+
+impl PriorBoxConfig {
+    fn validate(&self) -> Result<(), PriorBoxError> {
+        if self.image_size.contains(&0) {
+            return Err(PriorBoxError::ImageSize);
+        }
+        let n = self.feature_maps.len();
+        let required = [
+            ("steps", self.steps.len()),
+            ("min_sizes", self.min_sizes.len()),
+        ];
+        let optional = [
+            ("max_sizes", self.max_sizes.len()),
+            ("aspect_ratios", self.aspect_ratios.len()),
+        ];
+        let short = required
+            .into_iter()
+            .chain(optional.into_iter().filter(|&(_, len)| len != 0))
+            .find(|&(_, len)| len < n);
+        match short {
+            Some((field, len)) => Err(PriorBoxError::TooShort { field, len }),
+            None => Ok(()),
+        }
+    }
+
+    /// Normalized center and size of the priors, in the order of the feature maps,
+    /// then of the cells, then of the priors of a cell.
+    ///
+    /// `steps` and `min_sizes` need an entry per feature map, as do `max_sizes` and
+    /// `aspect_ratios` if not empty.
+    pub fn priors(&self) -> Result<Vec<[f32; 4]>, PriorBoxError> {
+        self.validate()?;
+        let [iw, ih] = self.image_size.map(|v| v as f32);
+        let mut priors = Vec::new();
+        for (k, &[fw, fh]) in self.feature_maps.iter().enumerate() {
+            let step = self.steps[k];
+            let (sw, sh) = (self.min_sizes[k] / iw, self.min_sizes[k] / ih);
+            for i in 0..fh {
+                for j in 0..fw {
+                    let cx = (j as f32 + 0.5) * step / iw;
+                    let cy = (i as f32 + 0.5) * step / ih;
+                    priors.push([cx, cy, sw, sh]);
+                    if let Some(max_size) = self.max_sizes.get(k) {
+                        let s = (self.min_sizes[k] * max_size).sqrt();
+                        priors.push([cx, cy, s / iw, s / ih]);
+                    }
+                    for ar in self.aspect_ratios.get(k).into_iter().flatten() {
+                        let r = ar.sqrt();
+                        priors.push([cx, cy, sw * r, sh / r]);
+                        priors.push([cx, cy, sw / r, sh * r]);
+                    }
+                }
+            }
+        }
+        if self.clip {
+            priors
+                .iter_mut()
+                .flatten()
+                .for_each(|v| *v = v.clamp(0.0, 1.0));
+        }
+        Ok(priors)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SsdParams {
+    /// Center and size variances of the box encoding.
+    pub variances: [f32; 2],
+    /// Class skipped in the scores.
+    pub background_class: Option<u32>,
+    /// Whether the scores are logits.
+    pub softmax: bool,
+    pub conf_threshold: f32,
+}
+
+impl Default for SsdParams {
+    fn default() -> Self {
+        SsdParams {
+            variances: [0.1, 0.2],
+            background_class: Some(0),
+            softmax: false,
+            conf_threshold: 0.5,
+        }
+    }
+}
+
+/// SSD outputs of `[num_priors, 4]` box offsets and `[num_priors, num_classes]` scores,
+/// with `priors` of [`PriorBoxConfig::priors`]. Every class above the threshold gives a
+/// detection.
+pub fn ssd<T: TensorElement>(
+    loc: &TensorView<T>,
+    conf: &TensorView<T>,
+    priors: &[[f32; 4]],
+    params: &SsdParams,
+    network_width: u32,
+    network_height: u32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [n, 4] = dims(loc)? else {
+        return Err(TensorError::Shape);
+    };
+    let [nc, c] = dims(conf)?;
+    if n != nc || n != priors.len() {
+        return Err(TensorError::Shape);
+    }
+    let (nw, nh) = (network_width as f32, network_height as f32);
+    let [v0, v1] = params.variances;
+
+    let mut objects = Vec::new();
+    let mut scores = vec![0.0; c];
+    for (i, p) in priors.iter().enumerate() {
+        scores
+            .iter_mut()
+            .enumerate()
+            .for_each(|(j, s)| *s = conf[[i, j]].to_f32());
+        if params.softmax {
+            softmax(&mut scores);
+        }
+        let d = |j: usize| loc[[i, j]].to_f32();
+        let cx = p[0] + d(0) * v0 * p[2];
+        let cy = p[1] + d(1) * v0 * p[3];
+        let w = p[2] * (d(2) * v1).exp();
+        let h = p[3] * (d(3) * v1).exp();
+        for (class_id, &score) in scores.iter().enumerate() {
+            if score >= params.conf_threshold && params.background_class != Some(class_id as u32) {
+                objects.push(from_center(
+                    class_id,
+                    cx * nw,
+                    cy * nh,
+                    w * nw,
+                    h * nh,
+                    score,
+                ));
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// DETR outputs of `[num_queries, num_classes + 1]` class logits, the last class being
+/// "no object", and `[num_queries, 4]` normalized center and size.
+pub fn detr<T: TensorElement>(
+    logits: &TensorView<T>,
+    boxes: &TensorView<T>,
+    conf_threshold: f32,
+    network_width: u32,
+    network_height: u32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [q, c] = dims(logits)?;
+    let [qb, 4] = dims(boxes)? else {
+        return Err(TensorError::Shape);
+    };
+    if q != qb || c < 2 {
+        return Err(TensorError::Shape);
+    }
+    let (nw, nh) = (network_width as f32, network_height as f32);
+
+    let mut scores = vec![0.0; c];
+    Ok((0..q)
+        .filter_map(|i| {
+            scores
+                .iter_mut()
+                .enumerate()
+                .for_each(|(j, s)| *s = logits[[i, j]].to_f32());
+            softmax(&mut scores);
+            let (class_id, confidence) = argmax(scores[..c - 1].iter().copied())?;
+            let b = |j: usize| boxes[[i, j]].to_f32();
+            (confidence >= conf_threshold).then(|| {
+                from_center(
+                    class_id,
+                    b(0) * nw,
+                    b(1) * nh,
+                    b(2) * nw,
+                    b(3) * nh,
+                    confidence,
+                )
+            })
+        })
+        .collect())
+}
+
+/// Anchors of RetinaNet as corners in network pixels, in the order of the `strides`,
+/// then of the cells, then of the `ratios` and of the `scales` of `sizes[level]`.
+pub fn retinanet_anchors(
+    network_width: u32,
+    network_height: u32,
+    strides: &[u32],
+    sizes: &[f32],
+    ratios: &[f32],
+    scales: &[f32],
+) -> Result<Vec<[f32; 4]>, PriorBoxError> {
+    if strides.contains(&0) {
+        return Err(PriorBoxError::Stride);
+    }
+    let mut anchors = Vec::new();
+    for (&stride, &size) in strides.iter().zip(sizes) {
+        let base = ratios
+            .iter()
+            .flat_map(|r| {
+                let (hr, wr) = (r.sqrt(), 1.0 / r.sqrt());
+                scales.iter().map(move |s| {
+                    let (w, h) = (wr * size * s, hr * size * s);
+                    [-w / 2.0, -h / 2.0, w / 2.0, h / 2.0].map(f32::round)
+                })
+            })
+            .collect::<Vec<_>>();
+        for y in 0..network_height.div_ceil(stride) {
+            for x in 0..network_width.div_ceil(stride) {
+                let (sx, sy) = ((x * stride) as f32, (y * stride) as f32);
+                anchors.extend(
+                    base.iter()
+                        .map(|a| [a[0] + sx, a[1] + sy, a[2] + sx, a[3] + sy]),
+                );
+            }
+        }
+    }
+    Ok(anchors)
+}
+
+/// RetinaNet outputs of `[num_anchors, num_classes]` class logits and `[num_anchors, 4]`
+/// box deltas, with `anchors` of [`retinanet_anchors`]. Every class above the threshold
+/// gives a detection.
+pub fn retinanet<T: TensorElement>(
+    cls: &TensorView<T>,
+    deltas: &TensorView<T>,
+    anchors: &[[f32; 4]],
+    conf_threshold: f32,
+) -> Result<Vec<ObjectDetectionInfo>, TensorError> {
+    let [n, c] = dims(cls)?;
+    let [nd, 4] = dims(deltas)? else {
+        return Err(TensorError::Shape);
+    };
+    if n != nd || n != anchors.len() {
+        return Err(TensorError::Shape);
+    }
+    let max_delta = (1000.0f32 / 16.0).ln();
+
+    let mut objects = Vec::new();
+    for (i, a) in anchors.iter().enumerate() {
+        let (aw, ah) = (a[2] - a[0], a[3] - a[1]);
+        let (acx, acy) = (a[0] + aw / 2.0, a[1] + ah / 2.0);
+        let d = |j: usize| deltas[[i, j]].to_f32();
+        for class_id in 0..c {
+            let confidence = sigmoid(cls[[i, class_id]].to_f32());
+            if confidence < conf_threshold {
+                continue;
+            }
+            objects.push(from_center(
+                class_id,
+                acx + d(0) * aw,
+                acy + d(1) * ah,
+                aw * d(2).min(max_delta).exp(),
+                ah * d(3).min(max_delta).exp(),
+                confidence,
+            ));
+        }
+    }
+    Ok(objects)
+}
+
+/// Scales `detections` to the source frame and adds them to `frame_meta` as untracked
+/// objects of `unique_component_id`, labelled by their class in `labels`. Returns
+/// `false` if the object meta pool is exhausted.
+#[cfg(feature = "meta")]
+pub fn attach_detections<'d, BM: crate::meta::BatchMetaExt>(
+    detections: impl IntoIterator<Item = &'d ObjectDetectionInfo>,
+    letterbox: &Letterbox,
+    batch_meta: &BM,
+    frame_meta: &mut crate::meta::FrameMeta,
+    unique_component_id: i32,
+    labels: &[&str],
+) -> bool {
+    use crate::meta::osd::{ColorParams, RectParamsBuilder};
+
+    for d in detections {
+        let o = letterbox.to_source(d);
+        let Some(obj_meta) = batch_meta.acquire_obj_meta_from_pool() else {
+            return false;
+        };
+        obj_meta.set_unique_component_id(unique_component_id);
+        obj_meta.set_class_id(o.class_id() as _);
+        obj_meta.set_object_id(nvidia_deepstream_sys::UNTRACKED_OBJECT_ID as _);
+        obj_meta.set_confidence(o.detection_confidence());
+        obj_meta.set_detector_bbox_coords(&crate::bounding_box::Coords::new(
+            o.left(),
+            o.top(),
+            o.width(),
+            o.height(),
+        ));
+        obj_meta.set_rect_params(
+            &RectParamsBuilder::new()
+                .left(o.left())
+                .top(o.top())
+                .width(o.width())
+                .height(o.height())
+                .border_width(3)
+                .border_color(ColorParams::new(1.0, 0.0, 0.0, 1.0))
+                .build(),
+        );
+        if let Some(label) = labels.get(o.class_id() as usize) {
+            obj_meta.set_obj_label(label);
+        }
+        frame_meta.add_obj_meta(obj_meta, None);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view<'a>(data: &'a [f32], shape: &[usize]) -> TensorView<'a, f32> {
+        TensorView::new(data, shape).unwrap()
+    }
+
+    fn rect(o: &ObjectDetectionInfo) -> [f32; 4] {
+        [o.left(), o.top(), o.width(), o.height()]
+    }
+
+    fn assert_close(a: [f32; 4], b: [f32; 4]) {
+        assert!(
+            a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 1e-3),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn yolov5_objectness_and_classes() {
+        #[rustfmt::skip]
+        let data = [
+            10.0, 20.0, 4.0, 6.0, 0.9, 0.1, 0.8,
+            50.0, 50.0, 8.0, 8.0, 0.4, 0.9, 0.1,
+            70.0, 70.0, 8.0, 8.0, 0.9, 0.5, 0.5,
+        ];
+        let objects = yolov5(&view(&data, &[3, 7]), 0.5).unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].class_id(), 1);
+        assert_close(rect(&objects[0]), [8.0, 17.0, 4.0, 6.0]);
+        assert!((objects[0].detection_confidence() - 0.72).abs() < 1e-6);
+
+        assert_eq!(
+            yolov5(&view(&data[..15], &[3, 5]), 0.5).unwrap_err(),
+            TensorError::Shape
+        );
+    }
+
+    #[test]
+    fn yolov5_grid_anchors() {
+        let data = [0.0; 2 * 6 * 2 * 2];
+        let objects = yolov5_grid(
+            &view(&data, &[12, 2, 2]),
+            8.0,
+            &[[10.0, 13.0], [16.0, 30.0]],
+            0.2,
+        )
+        .unwrap();
+        // Every sigmoid is 0.5: centers at (cell + 0.5) * stride, sizes of the anchors.
+        assert_eq!(objects.len(), 8);
+        assert_close(rect(&objects[0]), [-1.0, -2.5, 10.0, 13.0]);
+        assert_close(rect(&objects[7]), [4.0, -3.0, 16.0, 30.0]);
+        assert_eq!(objects[0].detection_confidence(), 0.25);
+
+        assert!(yolov5_grid(&view(&data, &[12, 2, 2]), 8.0, &[], 0.2).is_err());
+    }
+
+    #[test]
+    fn yolov8_transposed() {
+        // Two boxes, as columns.
+        #[rustfmt::skip]
+        let data = [
+            10.0, 0.0,
+            20.0, 0.0,
+            4.0, 1.0,
+            6.0, 1.0,
+            0.1, 0.2,
+            0.7, 0.3,
+        ];
+        let output = view(&data, &[6, 2]);
+        let objects = yolov8(&output, 0.5).unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].class_id(), 1);
+        assert_close(rect(&objects[0]), [8.0, 17.0, 4.0, 6.0]);
+
+        let permuted = output.permute(&[1, 0]).unwrap();
+        assert_eq!(
+            yolov8(&permuted.permute(&[1, 0]).unwrap(), 0.5)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    fn prior_box_config() -> PriorBoxConfig {
+        PriorBoxConfig {
+            image_size: [300, 300],
+            feature_maps: vec![[2, 2]],
+            steps: vec![150.0],
+            min_sizes: vec![30.0],
+            max_sizes: vec![60.0],
+            aspect_ratios: vec![vec![2.0]],
+            clip: true,
+        }
+    }
+
+    #[test]
+    fn priors_of_config() {
+        let priors = prior_box_config().priors().unwrap();
+        // 4 cells of a min size, a max size and 2 aspect ratio priors.
+        assert_eq!(priors.len(), 16);
+        assert_close(priors[0], [0.25, 0.25, 0.1, 0.1]);
+        let s = (30.0f32 * 60.0).sqrt() / 300.0;
+        assert_close(priors[1], [0.25, 0.25, s, s]);
+        let r = 2.0f32.sqrt();
+        assert_close(priors[2], [0.25, 0.25, 0.1 * r, 0.1 / r]);
+        assert_close(priors[15], [0.75, 0.75, 0.1 / r, 0.1 * r]);
+
+        let config = PriorBoxConfig {
+            max_sizes: vec![],
+            aspect_ratios: vec![],
+            ..prior_box_config()
+        };
+        assert_eq!(config.priors().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn priors_of_invalid_config() {
+        let config = PriorBoxConfig {
+            feature_maps: vec![[2, 2], [1, 1]],
+            ..prior_box_config()
+        };
+        assert_eq!(
+            config.priors(),
+            Err(PriorBoxError::TooShort {
+                field: "steps",
+                len: 1
+            })
+        );
+        let config = PriorBoxConfig {
+            min_sizes: vec![],
+            ..prior_box_config()
+        };
+        assert_eq!(
+            config.priors(),
+            Err(PriorBoxError::TooShort {
+                field: "min_sizes",
+                len: 0
+            })
+        );
+        let config = PriorBoxConfig {
+            feature_maps: vec![[2, 2], [1, 1]],
+            steps: vec![150.0, 300.0],
+            min_sizes: vec![30.0, 60.0],
+            ..prior_box_config()
+        };
+        assert_eq!(
+            config.priors(),
+            Err(PriorBoxError::TooShort {
+                field: "max_sizes",
+                len: 1
+            })
+        );
+        let config = PriorBoxConfig {
+            image_size: [0, 300],
+            ..prior_box_config()
+        };
+        assert_eq!(config.priors(), Err(PriorBoxError::ImageSize));
+    }
+
+    #[test]
+    fn ssd_with_priors() {
+        let priors = prior_box_config().priors().unwrap();
+        let mut loc = vec![0.0; 16 * 4];
+        // Offsets of the second prior: shifted right by its width and twice as wide.
+        loc[4..8].copy_from_slice(&[10.0, 0.0, 2.0f32.ln() / 0.2, 0.0]);
+        let conf = (0..16)
+            .flat_map(|i| match i {
+                0 => [0.1, 0.9],
+                1 => [0.3, 0.7],
+                _ => [0.9, 0.1],
+            })
+            .collect::<Vec<f32>>();
+        let objects = ssd(
+            &view(&loc, &[16, 4]),
+            &view(&conf, &[16, 2]),
+            &priors,
+            &SsdParams::default(),
+            300,
+            300,
+        )
+        .unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].class_id(), 1);
+        assert_close(rect(&objects[0]), [60.0, 60.0, 30.0, 30.0]);
+        let s = (30.0f32 * 60.0).sqrt();
+        assert_close(rect(&objects[1]), [75.0, 75.0 - s / 2.0, 2.0 * s, s]);
+
+        // Without a background class, the first score of every other prior is a detection.
+        let params = SsdParams {
+            background_class: None,
+            ..SsdParams::default()
+        };
+        let objects = ssd(
+            &view(&loc, &[16, 4]),
+            &view(&conf, &[16, 2]),
+            &priors,
+            &params,
+            300,
+            300,
+        )
+        .unwrap();
+        assert_eq!(objects.len(), 16);
+
+        assert!(ssd(
+            &view(&loc, &[16, 4]),
+            &view(&conf, &[16, 2]),
+            &priors[1..],
+            &params,
+            300,
+            300,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn detr_skips_no_object() {
+        #[rustfmt::skip]
+        let logits = [
+            0.0, 5.0, 0.0,
+            5.0, 0.0, 0.0,
+            0.0, 0.0, 5.0,
+        ];
+        #[rustfmt::skip]
+        let boxes = [
+            0.5, 0.5, 0.2, 0.2,
+            0.1, 0.1, 0.1, 0.1,
+            0.5, 0.5, 1.0, 1.0,
+        ];
+        let objects = detr(
+            &view(&logits, &[3, 3]),
+            &view(&boxes, &[3, 4]),
+            0.5,
+            100,
+            200,
+        )
+        .unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].class_id(), 1);
+        assert_close(rect(&objects[0]), [40.0, 80.0, 20.0, 40.0]);
+        assert_eq!(objects[1].class_id(), 0);
+        assert!(objects[0].detection_confidence() > 0.98);
+    }
+
+    #[test]
+    fn retinanet_with_anchors() {
+        let anchors = retinanet_anchors(64, 64, &[32], &[32.0], &[1.0], &[1.0]).unwrap();
+        assert_eq!(anchors.len(), 4);
+        assert_eq!(anchors[0], [-16.0, -16.0, 16.0, 16.0]);
+        assert_eq!(anchors[1], [16.0, -16.0, 48.0, 16.0]);
+        assert_eq!(
+            retinanet_anchors(64, 64, &[32], &[32.0], &[0.5, 2.0], &[1.0, 2.0])
+                .unwrap()
+                .len(),
+            16
+        );
+        assert_eq!(
+            retinanet_anchors(64, 64, &[32, 0], &[32.0, 64.0], &[1.0], &[1.0]),
+            Err(PriorBoxError::Stride)
+        );
+
+        let cls = [5.0, -5.0, -5.0, 5.0];
+        let mut deltas = [0.0; 16];
+        deltas[12..].copy_from_slice(&[0.5, 0.0, 2.0f32.ln(), 0.0]);
+        let objects =
+            retinanet(&view(&cls, &[4, 1]), &view(&deltas, &[4, 4]), &anchors, 0.5).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_close(rect(&objects[0]), [-16.0, -16.0, 32.0, 32.0]);
+        assert_close(rect(&objects[1]), [16.0, 16.0, 64.0, 32.0]);
+        assert!((objects[0].detection_confidence() - sigmoid(5.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn letterbox_to_source() {
+        let o = ObjectDetectionInfo::new(3, 10.0, 25.0, 50.0, 50.0, 0.5);
+
+        let stretched = Letterbox::new(100, 100, 200, 50);
+        let s = stretched.to_source(&o);
+        assert_eq!(s.class_id(), 3);
+        assert_eq!(s.detection_confidence(), 0.5);
+        assert_close(rect(&s), [20.0, 12.5, 100.0, 25.0]);
+
+        // 200x100 scaled by 0.5 to 100x50 at the top of the network input.
+        let padded = Letterbox::new(100, 100, 200, 100).with_aspect_ratio(false);
+        assert_close(rect(&padded.to_source(&o)), [20.0, 50.0, 100.0, 50.0]);
+
+        // Padded by 25 at the top and bottom.
+        let symmetric = Letterbox::new(100, 100, 200, 100).with_aspect_ratio(true);
+        assert_close(rect(&symmetric.to_source(&o)), [20.0, 0.0, 100.0, 100.0]);
+
+        // Clipped to the frame.
+        let outside = ObjectDetectionInfo::new(0, -10.0, 90.0, 20.0, 20.0, 1.0);
+        assert_close(
+            rect(&symmetric.to_source(&outside)),
+            [0.0, 100.0, 20.0, 0.0],
+        );
+    }
+}
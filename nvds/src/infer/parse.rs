@@ -0,0 +1,244 @@
+//! Custom output parsers of nvinfer written in Rust.
+//!
+//! A parser is a function with the signature of [`BBoxParseFn`], exported from a
+//! `cdylib` under the name set as `parse-bbox-func-name` in the nvinfer configuration,
+//! along with `custom-lib-path`:
+//!
+//! ```ignore
+//! use nvidia_deepstream::infer::parse::{find_layer, DetectionParams};
+//! use nvidia_deepstream::infer::{LayerInfo, NetworkInfo, ObjectDetectionInfo, Status};
+//!
+//! fn parse_boxes(
+//!     layers: &[LayerInfo],
+//!     network: &NetworkInfo,
+//!     params: &DetectionParams,
+//! ) -> Result<Vec<ObjectDetectionInfo>, Status> {
+//!     let boxes = find_layer(layers, "boxes").and_then(|l| l.as_slice::<f32>());
+//!     ...
+//! }
+//!
+//! nvidia_deepstream::export_bbox_parser!(NvDsInferParseCustomBoxes, parse_boxes);
+//! ```
+
+use super::classify::Classification;
+use super::{LayerInfo, NetworkInfo, ObjectDetectionInfo, Status};
+
+pub type BBoxParseFn =
+    fn(&[LayerInfo], &NetworkInfo, &DetectionParams) -> Result<Vec<ObjectDetectionInfo>, Status>;
+
+/// Classifier parser, given `classifier-threshold`, exported with
+/// [`export_classifier_parser!`](crate::export_classifier_parser) under the name set as
+/// `parse-classifier-func-name`.
+pub type ClassifierParseFn =
+    fn(&[LayerInfo], &NetworkInfo, f32) -> Result<Vec<Classification>, Status>;
+
+pub fn find_layer<'a>(layers: &'a [LayerInfo], name: &str) -> Option<&'a LayerInfo> {
+    layers.iter().find(|l| l.layer_name().as_str() == name)
+}
+
+/// Detection parameters of the nvinfer configuration, `NvDsInferParseDetectionParams`.
+pub struct DetectionParams<'a>(&'a nvidia_deepstream_sys::NvDsInferParseDetectionParams);
+
+impl<'a> DetectionParams<'a> {
+    #[doc(alias = "numClassesConfigured")]
+    pub fn num_classes_configured(&self) -> u32 {
+        unsafe { nvidia_deepstream_sys::nvds_rs_detection_params_num_classes(self.0) }
+    }
+
+    /// `pre-cluster-threshold` of every class.
+    #[doc(alias = "perClassPreclusterThreshold")]
+    pub fn pre_cluster_thresholds(&self) -> &'a [f32] {
+        unsafe {
+            let mut len = 0;
+            let p = nvidia_deepstream_sys::nvds_rs_detection_params_pre_cluster_thresholds(
+                self.0, &mut len,
+            );
+            slice_or_empty(p, len)
+        }
+    }
+
+    /// `post-cluster-threshold` of every class.
+    #[doc(alias = "perClassPostclusterThreshold")]
+    pub fn post_cluster_thresholds(&self) -> &'a [f32] {
+        unsafe {
+            let mut len = 0;
+            let p = nvidia_deepstream_sys::nvds_rs_detection_params_post_cluster_thresholds(
+                self.0, &mut len,
+            );
+            slice_or_empty(p, len)
+        }
+    }
+
+    pub fn pre_cluster_threshold(&self, class_id: u32) -> Option<f32> {
+        self.pre_cluster_thresholds()
+            .get(class_id as usize)
+            .copied()
+    }
+
+    pub fn post_cluster_threshold(&self, class_id: u32) -> Option<f32> {
+        self.post_cluster_thresholds()
+            .get(class_id as usize)
+            .copied()
+    }
+}
+
+unsafe fn slice_or_empty<'a, T>(p: *const T, len: usize) -> &'a [T] {
+    if p.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(p, len)
+    }
+}
+
+/// C ABI used by [`export_bbox_parser!`](crate::export_bbox_parser).
+#[allow(clippy::missing_safety_doc)]
+pub mod ffi {
+    use super::*;
+    use crate::WrapperExt;
+    use std::panic::AssertUnwindSafe;
+
+    pub use nvidia_deepstream_sys::{
+        CppString, NvDsInferAttributeVector, NvDsInferLayerInfoVector, NvDsInferNetworkInfo,
+        NvDsInferObjectDetectionInfoVector, NvDsInferParseDetectionParams,
+    };
+
+    unsafe fn layers<'a>(output_layers_info: *const NvDsInferLayerInfoVector) -> &'a [LayerInfo] {
+        slice_or_empty(
+            nvidia_deepstream_sys::nvds_rs_layer_info_vector_data(output_layers_info)
+                as *const LayerInfo,
+            nvidia_deepstream_sys::nvds_rs_layer_info_vector_size(output_layers_info),
+        )
+    }
+
+    /// Calls `f` and replaces the content of `object_list` with its detections. Panics
+    /// are reported as a parsing failure.
+    pub unsafe fn parse_bbox(
+        f: BBoxParseFn,
+        output_layers_info: *const NvDsInferLayerInfoVector,
+        network_info: *const NvDsInferNetworkInfo,
+        detection_params: *const NvDsInferParseDetectionParams,
+        object_list: *mut NvDsInferObjectDetectionInfoVector,
+    ) -> bool {
+        if output_layers_info.is_null()
+            || network_info.is_null()
+            || detection_params.is_null()
+            || object_list.is_null()
+        {
+            return false;
+        }
+        let layers = layers(output_layers_info);
+        let network = NetworkInfo::from_native_type_ref(&*network_info);
+        let params = DetectionParams(&*detection_params);
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| f(layers, network, &params))) {
+            Ok(Ok(objects)) => {
+                nvidia_deepstream_sys::nvds_rs_object_detection_info_vector_assign(
+                    object_list,
+                    objects.as_ptr() as *const _,
+                    objects.len(),
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Calls `f` and appends its classifications to `attr_list`, with their labels
+    /// separated by spaces as `desc_string`. Panics are reported as a parsing failure.
+    pub unsafe fn parse_classifier(
+        f: ClassifierParseFn,
+        output_layers_info: *const NvDsInferLayerInfoVector,
+        network_info: *const NvDsInferNetworkInfo,
+        classifier_threshold: f32,
+        attr_list: *mut NvDsInferAttributeVector,
+        desc_string: *mut CppString,
+    ) -> bool {
+        if output_layers_info.is_null()
+            || network_info.is_null()
+            || attr_list.is_null()
+            || desc_string.is_null()
+        {
+            return false;
+        }
+        let layers = layers(output_layers_info);
+        let network = NetworkInfo::from_native_type_ref(&*network_info);
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| {
+            f(layers, network, classifier_threshold)
+        })) {
+            Ok(Ok(classifications)) => {
+                for c in &classifications {
+                    let label = c
+                        .attribute_label
+                        .as_deref()
+                        .and_then(|l| std::ffi::CString::new(l).ok());
+                    nvidia_deepstream_sys::nvds_rs_attribute_vector_push(
+                        attr_list,
+                        c.attribute_index,
+                        c.attribute_value,
+                        c.attribute_confidence,
+                        label.as_ref().map_or(std::ptr::null(), |l| l.as_ptr()),
+                    );
+                }
+                let desc = crate::infer::classify::description(&classifications);
+                nvidia_deepstream_sys::nvds_rs_string_assign(
+                    desc_string,
+                    desc.as_ptr() as _,
+                    desc.len(),
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Exports `$f`, a [`BBoxParseFn`](crate::infer::parse::BBoxParseFn), as the
+/// `NvDsInferParseCustomFunc` named `$name`.
+#[macro_export]
+macro_rules! export_bbox_parser {
+    ($name:ident, $f:path) => {
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn $name(
+            output_layers_info: *const $crate::infer::parse::ffi::NvDsInferLayerInfoVector,
+            network_info: *const $crate::infer::parse::ffi::NvDsInferNetworkInfo,
+            detection_params: *const $crate::infer::parse::ffi::NvDsInferParseDetectionParams,
+            object_list: *mut $crate::infer::parse::ffi::NvDsInferObjectDetectionInfoVector,
+        ) -> bool {
+            $crate::infer::parse::ffi::parse_bbox(
+                $f,
+                output_layers_info,
+                network_info,
+                detection_params,
+                object_list,
+            )
+        }
+    };
+}
+
+/// Exports `$f`, a [`ClassifierParseFn`](crate::infer::parse::ClassifierParseFn), as the
+/// `NvDsInferClassiferParseCustomFunc` named `$name`.
+#[macro_export]
+macro_rules! export_classifier_parser {
+    ($name:ident, $f:path) => {
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn $name(
+            output_layers_info: *const $crate::infer::parse::ffi::NvDsInferLayerInfoVector,
+            network_info: *const $crate::infer::parse::ffi::NvDsInferNetworkInfo,
+            classifier_threshold: f32,
+            attr_list: *mut $crate::infer::parse::ffi::NvDsInferAttributeVector,
+            desc_string: *mut $crate::infer::parse::ffi::CppString,
+        ) -> bool {
+            $crate::infer::parse::ffi::parse_classifier(
+                $f,
+                output_layers_info,
+                network_info,
+                classifier_threshold,
+                attr_list,
+                desc_string,
+            )
+        }
+    };
+}
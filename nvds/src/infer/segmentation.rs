@@ -0,0 +1,343 @@
+//! Outputs of segmentation networks, attached by nvinfer as
+//! `NVDSINFER_SEGMENTATION_META` user meta.
+
+use crate::meta::mask::{self, Bitmap, Component};
+use crate::meta::osd::{ColorParams, MaskParamsBuilder, RectParamsBuilder};
+use crate::meta::{BaseMetaType, BatchMetaExt, FrameMeta, MetaType, ObjectMeta, UserMeta};
+use crate::WrapperExt;
+
+crate::wrapper_impl_ref_type!(
+    SegmentationMeta,
+    nvidia_deepstream_sys::NvDsInferSegmentationMeta
+);
+
+impl SegmentationMeta {
+    pub fn unique_id(&self) -> u32 {
+        self.as_native_type_ref().unique_id
+    }
+
+    pub fn classes(&self) -> u32 {
+        self.as_native_type_ref().classes
+    }
+
+    pub fn width(&self) -> u32 {
+        self.as_native_type_ref().width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.as_native_type_ref().height
+    }
+
+    /// Class of every pixel in row-major order, -1 where no class is above
+    /// `segmentation-threshold`.
+    pub fn class_map(&self) -> &[i32] {
+        let n = self.as_native_type_ref();
+        if n.class_map.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(n.class_map, (n.width * n.height) as _) }
+    }
+
+    /// Probabilities of every class, `[classes, height, width]`.
+    pub fn class_probabilities_map(&self) -> &[f32] {
+        let n = self.as_native_type_ref();
+        if n.class_probabilities_map.is_null() {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                n.class_probabilities_map,
+                (n.classes * n.width * n.height) as _,
+            )
+        }
+    }
+
+    /// Probabilities of `class_id` in row-major order.
+    pub fn class_probabilities(&self, class_id: u32) -> Option<&[f32]> {
+        let plane = (self.width() * self.height()) as usize;
+        let start = class_id as usize * plane;
+        self.class_probabilities_map().get(start..start + plane)
+    }
+
+    pub fn map(&self) -> ClassMap<'_> {
+        ClassMap::new(self.width(), self.height(), self.class_map())
+    }
+
+    /// Adds an object for every class with a colour in `colors`, whose mask is drawn by
+    /// nvdsosd with `display-mask=1`, scaled from the class map to the source frame.
+    /// Returns `false` if the object meta pool is exhausted.
+    pub fn attach_overlays<BM: BatchMetaExt>(
+        &self,
+        batch_meta: &BM,
+        frame_meta: &mut FrameMeta,
+        colors: &[ColorParams],
+        unique_component_id: i32,
+    ) -> bool {
+        let map = self.map();
+        let sx = frame_meta.source_frame_width() as f32 / self.width().max(1) as f32;
+        let sy = frame_meta.source_frame_height() as f32 / self.height().max(1) as f32;
+        for (class_id, color) in colors.iter().enumerate() {
+            let Some([left, top, width, height]) = map.bbox(class_id as _) else {
+                continue;
+            };
+            let bitmap = map.bitmap(class_id as _);
+            let Some(obj_meta) = batch_meta.acquire_obj_meta_from_pool() else {
+                return false;
+            };
+            obj_meta.set_unique_component_id(unique_component_id);
+            obj_meta.set_class_id(class_id as _);
+            obj_meta.set_object_id(nvidia_deepstream_sys::UNTRACKED_OBJECT_ID as _);
+            obj_meta.set_confidence(1.0);
+            obj_meta.set_rect_params(
+                &RectParamsBuilder::new()
+                    .left(left as f32 * sx)
+                    .top(top as f32 * sy)
+                    .width(width as f32 * sx)
+                    .height(height as f32 * sy)
+                    .border_width(0)
+                    .border_color(*color)
+                    .build(),
+            );
+            obj_meta.set_mask_params(
+                MaskParamsBuilder::new()
+                    .data(&bitmap.crop(left, top, width, height).to_mask())
+                    .width(width)
+                    .height(height)
+                    .threshold(0.5)
+                    .build(),
+            );
+            frame_meta.add_obj_meta(obj_meta, None);
+        }
+        true
+    }
+}
+
+/// Class map of `width` x `height` pixels in row-major order.
+#[derive(Clone, Copy, Debug)]
+pub struct ClassMap<'a> {
+    width: u32,
+    height: u32,
+    data: &'a [i32],
+}
+
+impl<'a> ClassMap<'a> {
+    pub fn new(width: u32, height: u32, data: &'a [i32]) -> ClassMap<'a> {
+        let len = (width as usize * height as usize).min(data.len());
+        ClassMap {
+            width,
+            height,
+            data: &data[..len],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn data(&self) -> &'a [i32] {
+        self.data
+    }
+
+    pub fn class_at(&self, x: u32, y: u32) -> Option<i32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.data
+            .get(y as usize * self.width as usize + x as usize)
+            .copied()
+    }
+
+    /// Number of pixels of every class below `classes`.
+    pub fn class_areas(&self, classes: u32) -> Vec<u32> {
+        let mut areas = vec![0; classes as usize];
+        for &c in self.data {
+            if let Some(a) = usize::try_from(c).ok().and_then(|c| areas.get_mut(c)) {
+                *a += 1;
+            }
+        }
+        areas
+    }
+
+    pub fn bitmap(&self, class_id: i32) -> Bitmap {
+        let mut bitmap = Bitmap::new(self.width, self.height);
+        for (i, _) in self.data.iter().enumerate().filter(|(_, &c)| c == class_id) {
+            bitmap.set(i as u32 % self.width, i as u32 / self.width, true);
+        }
+        bitmap
+    }
+
+    /// `[left, top, width, height]` of the pixels of `class_id`.
+    pub fn bbox(&self, class_id: i32) -> Option<[u32; 4]> {
+        let mut b: Option<[u32; 4]> = None;
+        for (i, _) in self.data.iter().enumerate().filter(|(_, &c)| c == class_id) {
+            let (x, y) = (i as u32 % self.width, i as u32 / self.width);
+            b = Some(match b {
+                None => [x, y, x, y],
+                Some([l, t, r, b]) => [l.min(x), t.min(y), r.max(x), b.max(y)],
+            });
+        }
+        b.map(|[l, t, r, b]| [l, t, r - l + 1, b - t + 1])
+    }
+
+    /// Connected regions of `class_id` of at least `min_area` pixels.
+    pub fn components(&self, class_id: i32, min_area: u32) -> Vec<Component> {
+        self.bitmap(class_id)
+            .components()
+            .into_iter()
+            .filter(|c| c.area >= min_area)
+            .collect()
+    }
+
+    /// Outlines of the regions of `class_id` of at least `min_area` pixels, simplified
+    /// with a tolerance of `epsilon` pixels if positive.
+    pub fn polygons(&self, class_id: i32, min_area: u32, epsilon: f32) -> Vec<Vec<[u32; 2]>> {
+        self.bitmap(class_id)
+            .contours(min_area)
+            .into_iter()
+            .map(|c| {
+                if epsilon > 0.0 {
+                    mask::simplify_polygon(&c, epsilon)
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    /// RGBA image with the colour of `palette` of every class, transparent where the
+    /// class has no colour.
+    pub fn colorize(&self, palette: &[[u8; 4]]) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|&c| {
+                usize::try_from(c)
+                    .ok()
+                    .and_then(|c| palette.get(c))
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+impl UserMeta {
+    pub fn segmentation_meta(&self) -> Option<&SegmentationMeta> {
+        if self.base_meta().meta_type() != MetaType::Base(BaseMetaType::InferSegmentationMeta) {
+            return None;
+        }
+        unsafe {
+            self.user_meta_data::<nvidia_deepstream_sys::NvDsInferSegmentationMeta>()
+                .map(SegmentationMeta::from_native_type_ref)
+        }
+    }
+}
+
+impl FrameMeta {
+    /// Segmentation meta attached by primary nvinfer instances.
+    pub fn segmentation_outputs(&self) -> impl Iterator<Item = &SegmentationMeta> {
+        self.frame_user_meta_list()
+            .map(|l| l.iter())
+            .into_iter()
+            .flatten()
+            .filter_map(UserMeta::segmentation_meta)
+    }
+}
+
+impl ObjectMeta {
+    /// Segmentation meta attached by secondary nvinfer instances.
+    pub fn segmentation_outputs(&self) -> impl Iterator<Item = &SegmentationMeta> {
+        self.obj_user_meta_list()
+            .map(|l| l.iter())
+            .into_iter()
+            .flatten()
+            .filter_map(UserMeta::segmentation_meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 5x4 map: a class 0 L-shape, a class 1 square and a class 2 pixel touching the
+    // right border, -1 elsewhere.
+    const MAP: [i32; 20] = [
+        0, -1, -1, 1, 1, //
+        0, -1, -1, 1, 1, //
+        0, 0, -1, -1, 2, //
+        -1, -1, -1, -1, -1,
+    ];
+
+    #[test]
+    fn class_at() {
+        let map = ClassMap::new(5, 4, &MAP);
+        assert_eq!(map.class_at(0, 0), Some(0));
+        assert_eq!(map.class_at(4, 2), Some(2));
+        assert_eq!(map.class_at(4, 3), Some(-1));
+        assert_eq!(map.class_at(5, 0), None);
+        assert_eq!(map.class_at(0, 4), None);
+        assert_eq!(map.class_at(u32::MAX, u32::MAX), None);
+
+        // Rows missing from the data.
+        let short = ClassMap::new(5, 8, &MAP);
+        assert_eq!(short.data().len(), 20);
+        assert_eq!(short.class_at(0, 5), None);
+        let small = ClassMap::new(2, 2, &MAP);
+        assert_eq!(small.data(), [0, -1, -1, 1]);
+        // `y * width` wraps around to 0 in u32.
+        assert_eq!(small.class_at(1, 1 << 31), None);
+    }
+
+    #[test]
+    fn areas_and_bboxes() {
+        let map = ClassMap::new(5, 4, &MAP);
+        assert_eq!(map.class_areas(4), [4, 4, 1, 0]);
+        assert_eq!(map.class_areas(2), [4, 4]);
+        assert_eq!(map.bbox(0), Some([0, 0, 2, 3]));
+        assert_eq!(map.bbox(1), Some([3, 0, 2, 2]));
+        assert_eq!(map.bbox(2), Some([4, 2, 1, 1]));
+        assert_eq!(map.bbox(-1), Some([0, 0, 5, 4]));
+        assert_eq!(map.bbox(3), None);
+        assert_eq!(map.bitmap(1).area(), 4);
+        assert!(map.bitmap(1).get(4, 1));
+    }
+
+    #[test]
+    fn components_and_polygons() {
+        let map = ClassMap::new(5, 4, &MAP);
+        let c = map.components(0, 1);
+        assert_eq!(c.len(), 1);
+        assert_eq!((c[0].left, c[0].top, c[0].width, c[0].height), (0, 0, 2, 3));
+        assert_eq!(c[0].area, 4);
+        assert!(map.components(0, 5).is_empty());
+        assert!(map.components(3, 0).is_empty());
+
+        assert_eq!(
+            map.polygons(1, 1, 0.0),
+            [vec![[3, 0], [4, 0], [4, 1], [3, 1]]]
+        );
+        assert_eq!(map.polygons(2, 1, 0.5), [vec![[4, 2]]]);
+        assert_eq!(
+            map.polygons(0, 1, 0.0),
+            [vec![[0, 0], [0, 1], [1, 2], [0, 2], [0, 1]]]
+        );
+        assert_eq!(map.polygons(0, 1, 0.5), [vec![[0, 0], [1, 2], [0, 2]]]);
+        assert!(map.polygons(0, 5, 0.5).is_empty());
+    }
+
+    #[test]
+    fn colorize() {
+        let map = ClassMap::new(5, 4, &MAP);
+        let palette = [[255, 0, 0, 255], [0, 255, 0, 128]];
+        let rgba = map.colorize(&palette);
+        assert_eq!(rgba.len(), 5 * 4 * 4);
+        assert_eq!(rgba[..4], palette[0]);
+        assert_eq!(rgba[4..8], [0; 4]);
+        assert_eq!(rgba[12..16], palette[1]);
+        // Class 2 has no colour.
+        assert_eq!(rgba[4 * 14..4 * 15], [0; 4]);
+    }
+}
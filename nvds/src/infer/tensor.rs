@@ -0,0 +1,414 @@
+//! Typed n-dimensional views over layer buffers.
+
+use super::{DataType, LayerInfo, TensorElement};
+use std::ops::{Index, Range};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum TensorError {
+    NullBuffer,
+    /// No output layer `index` among the `len` layers.
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    DataType {
+        expected: DataType,
+        actual: DataType,
+    },
+    /// The shape does not match the number of elements.
+    Shape,
+    /// The buffer is not accessible from the CPU.
+    DeviceMemory,
+    UnsupportedDataType,
+}
+
+impl std::fmt::Display for TensorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorError::NullBuffer => write!(f, "layer buffer is null"),
+            TensorError::IndexOutOfRange { index, len } => {
+                write!(f, "layer index {} out of range for {} layers", index, len)
+            }
+            TensorError::DataType { expected, actual } => {
+                write!(f, "expected {:?} layer, got {:?}", expected, actual)
+            }
+            TensorError::Shape => write!(f, "shape does not match the number of elements"),
+            TensorError::DeviceMemory => write!(f, "buffer is not in host memory"),
+            TensorError::UnsupportedDataType => write!(f, "unsupported data type"),
+        }
+    }
+}
+
+impl std::error::Error for TensorError {}
+
+/// Memory layout of image-like tensors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layout {
+    Chw,
+    Hwc,
+}
+
+/// Strided view of `T` elements with a row-major logical order.
+#[derive(Clone, Debug)]
+pub struct TensorView<'a, T> {
+    data: &'a [T],
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+impl<'a, T: Copy> TensorView<'a, T> {
+    /// Contiguous row-major view of `data`.
+    pub fn new(data: &'a [T], shape: &[usize]) -> Result<TensorView<'a, T>, TensorError> {
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(TensorError::Shape);
+        }
+        Ok(TensorView {
+            data,
+            offset: 0,
+            shape: shape.to_vec(),
+            strides: row_major_strides(shape),
+        })
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Strides in elements.
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn offset_of(&self, index: &[usize]) -> Option<usize> {
+        if index.len() != self.shape.len() {
+            return None;
+        }
+        index
+            .iter()
+            .zip(self.shape.iter().zip(self.strides.iter()))
+            .try_fold(self.offset, |acc, (&i, (&n, &s))| {
+                (i < n).then_some(acc + i * s)
+            })
+    }
+
+    pub fn get(&self, index: &[usize]) -> Option<T> {
+        self.data.get(self.offset_of(index)?).copied()
+    }
+
+    /// View of the element `index` of `axis`, with that axis removed, e.g. a row of a
+    /// `[num_boxes, 4]` tensor.
+    pub fn select(&self, axis: usize, index: usize) -> Option<TensorView<'a, T>> {
+        if index >= *self.shape.get(axis)? {
+            return None;
+        }
+        let mut view = self.clone();
+        view.offset += index * view.strides[axis];
+        view.shape.remove(axis);
+        view.strides.remove(axis);
+        Some(view)
+    }
+
+    /// View of the `range` of `axis`.
+    pub fn slice(&self, axis: usize, range: Range<usize>) -> Option<TensorView<'a, T>> {
+        if range.start > range.end || range.end > *self.shape.get(axis)? {
+            return None;
+        }
+        let mut view = self.clone();
+        view.offset += range.start * view.strides[axis];
+        view.shape[axis] = range.len();
+        Some(view)
+    }
+
+    /// View with the axes reordered, `axes[i]` being the axis of `self` that becomes
+    /// axis `i`.
+    pub fn permute(&self, axes: &[usize]) -> Option<TensorView<'a, T>> {
+        let mut seen = vec![false; self.ndim()];
+        if axes.len() != self.ndim() {
+            return None;
+        }
+        for &a in axes {
+            if std::mem::replace(seen.get_mut(a)?, true) {
+                return None;
+            }
+        }
+        Some(TensorView {
+            data: self.data,
+            offset: self.offset,
+            shape: axes.iter().map(|&a| self.shape[a]).collect(),
+            strides: axes.iter().map(|&a| self.strides[a]).collect(),
+        })
+    }
+
+    fn permute_last_3(&self, last: [usize; 3]) -> Option<TensorView<'a, T>> {
+        let n = self.ndim().checked_sub(3)?;
+        let axes = (0..n).chain(last.iter().map(|a| n + a)).collect::<Vec<_>>();
+        self.permute(&axes)
+    }
+
+    /// Reinterprets the last three axes, in the `from` layout, as `C, H, W`.
+    pub fn to_chw(&self, from: Layout) -> Option<TensorView<'a, T>> {
+        match from {
+            Layout::Chw => self.permute_last_3([0, 1, 2]),
+            Layout::Hwc => self.permute_last_3([2, 0, 1]),
+        }
+    }
+
+    /// Reinterprets the last three axes, in the `from` layout, as `H, W, C`.
+    pub fn to_hwc(&self, from: Layout) -> Option<TensorView<'a, T>> {
+        match from {
+            Layout::Chw => self.permute_last_3([1, 2, 0]),
+            Layout::Hwc => self.permute_last_3([0, 1, 2]),
+        }
+    }
+
+    /// Contiguous view with another shape of the same number of elements.
+    pub fn reshape(&self, shape: &[usize]) -> Result<TensorView<'a, T>, TensorError> {
+        let data = self.as_slice().ok_or(TensorError::Shape)?;
+        TensorView::new(data, shape)
+    }
+
+    /// The elements as a slice if the view is contiguous.
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        let contiguous = self
+            .shape
+            .iter()
+            .zip(self.strides.iter().zip(row_major_strides(&self.shape)))
+            .all(|(&n, (&s, expected))| n <= 1 || s == expected);
+        if contiguous {
+            self.data.get(self.offset..self.offset + self.len())
+        } else {
+            None
+        }
+    }
+
+    /// Elements in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).map(move |mut flat| {
+            let mut offset = self.offset;
+            for (&n, &s) in self.shape.iter().zip(self.strides.iter()).rev() {
+                offset += flat % n * s;
+                flat /= n;
+            }
+            self.data[offset]
+        })
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        match self.as_slice() {
+            Some(s) => s.to_vec(),
+            None => self.iter().collect(),
+        }
+    }
+}
+
+impl<'a, T: TensorElement> TensorView<'a, T> {
+    /// Elements converted to `f32` in row-major order, e.g. for half layers.
+    pub fn to_f32_vec(&self) -> Vec<f32> {
+        self.iter().map(TensorElement::to_f32).collect()
+    }
+}
+
+impl<'a, T: Copy, const N: usize> Index<[usize; N]> for TensorView<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: [usize; N]) -> &T {
+        &self.data[self.offset_of(&index).expect("tensor index out of bounds")]
+    }
+}
+
+impl<'a, T: Copy> Index<&[usize]> for TensorView<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: &[usize]) -> &T {
+        &self.data[self.offset_of(index).expect("tensor index out of bounds")]
+    }
+}
+
+impl LayerInfo {
+    /// View of the host buffer of the layer, shaped by `inferDims`, which excludes the
+    /// batch dimension.
+    pub fn tensor<T: TensorElement>(&self) -> Result<TensorView<'_, T>, TensorError> {
+        unsafe { self.tensor_with_buffer(self.buffer() as _) }
+    }
+
+    /// View of `buffer` with the data type and dimensions of the layer, e.g. a buffer
+    /// of `out_buf_ptrs_host`.
+    pub(crate) unsafe fn tensor_with_buffer<'a, T: TensorElement>(
+        &self,
+        buffer: *const T,
+    ) -> Result<TensorView<'a, T>, TensorError> {
+        if buffer.is_null() {
+            return Err(TensorError::NullBuffer);
+        }
+        if self.data_type() != T::DATA_TYPE {
+            return Err(TensorError::DataType {
+                expected: T::DATA_TYPE,
+                actual: self.data_type(),
+            });
+        }
+        let dims = self.infer_dims();
+        let data = std::slice::from_raw_parts(buffer, dims.num_elements() as _);
+        let shape = dims.d().iter().map(|&d| d as usize).collect::<Vec<_>>();
+        TensorView::new(data, &shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> Vec<f32> {
+        (0..24).map(|i| i as f32).collect()
+    }
+
+    #[test]
+    fn contiguous() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        assert_eq!(t.shape(), [2, 3, 4]);
+        assert_eq!(t.strides(), [12, 4, 1]);
+        assert_eq!(t.ndim(), 3);
+        assert_eq!(t.len(), 24);
+        assert_eq!(t.get(&[1, 2, 3]), Some(23.0));
+        assert_eq!(t.get(&[1, 3, 0]), None);
+        assert_eq!(t.get(&[1, 2]), None);
+        assert_eq!(t[[1, 0, 2]], 14.0);
+        assert_eq!(t[&[0, 1, 0][..]], 4.0);
+        assert_eq!(t.as_slice(), Some(&data[..]));
+        assert_eq!(t.iter().collect::<Vec<_>>(), data);
+        assert_eq!(t.to_f32_vec(), data);
+
+        assert_eq!(
+            TensorView::new(&data, &[5, 5]).err(),
+            Some(TensorError::Shape)
+        );
+        let empty = TensorView::new(&data[..0], &[0, 4]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[test]
+    fn select_and_slice() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let row = t.select(1, 2).unwrap();
+        assert_eq!(row.shape(), [2, 4]);
+        assert_eq!(row.strides(), [12, 1]);
+        assert_eq!(row.to_vec(), [8.0, 9.0, 10.0, 11.0, 20.0, 21.0, 22.0, 23.0]);
+        assert_eq!(row.as_slice(), None);
+        assert_eq!(row[[1, 1]], 21.0);
+        assert_eq!(t.select(0, 1).unwrap().as_slice(), Some(&data[12..]));
+        assert!(t.select(1, 3).is_none());
+        assert!(t.select(3, 0).is_none());
+
+        let columns = t.slice(2, 1..3).unwrap();
+        assert_eq!(columns.shape(), [2, 3, 2]);
+        assert_eq!(columns[[0, 1, 0]], 5.0);
+        assert_eq!(columns[[1, 2, 1]], 22.0);
+        assert_eq!(columns.select(0, 1).unwrap().to_vec()[..2], [13.0, 14.0]);
+        assert_eq!(t.slice(0, 1..2).unwrap().as_slice(), Some(&data[12..]));
+        assert_eq!(t.slice(1, 3..3).unwrap().len(), 0);
+        assert!(t.slice(1, 2..4).is_none());
+        assert!(t.slice(3, 0..1).is_none());
+    }
+
+    #[test]
+    fn permute() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let p = t.permute(&[2, 0, 1]).unwrap();
+        assert_eq!(p.shape(), [4, 2, 3]);
+        assert_eq!(p.strides(), [1, 12, 4]);
+        assert_eq!(p[[3, 1, 2]], t[[1, 2, 3]]);
+        assert_eq!(p.get(&[1, 0, 2]), Some(9.0));
+        assert_eq!(p.as_slice(), None);
+        assert_eq!(p.iter().take(4).collect::<Vec<_>>(), [0.0, 4.0, 8.0, 12.0]);
+        assert_eq!(p.to_vec().len(), 24);
+
+        assert!(t.permute(&[0, 1]).is_none());
+        assert!(t.permute(&[0, 1, 1]).is_none());
+        assert!(t.permute(&[0, 1, 3]).is_none());
+        assert_eq!(t.permute(&[0, 1, 2]).unwrap().as_slice(), Some(&data[..]));
+    }
+
+    #[test]
+    fn layouts() {
+        let data = data();
+        // 2x3 image with 4 channels.
+        let hwc = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let chw = hwc.to_chw(Layout::Hwc).unwrap();
+        assert_eq!(chw.shape(), [4, 2, 3]);
+        assert_eq!(chw[[1, 1, 2]], hwc[[1, 2, 1]]);
+        assert_eq!(chw.to_hwc(Layout::Chw).unwrap().to_vec(), data);
+        assert_eq!(hwc.to_hwc(Layout::Hwc).unwrap().as_slice(), Some(&data[..]));
+
+        let batch = TensorView::new(&data, &[2, 1, 3, 4]).unwrap();
+        let hwc = batch.to_hwc(Layout::Chw).unwrap();
+        assert_eq!(hwc.shape(), [2, 3, 4, 1]);
+        assert_eq!(hwc[[1, 2, 3, 0]], 23.0);
+        assert!(TensorView::new(&data, &[4, 6])
+            .unwrap()
+            .to_chw(Layout::Hwc)
+            .is_none());
+    }
+
+    #[test]
+    fn reshape() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let r = t.reshape(&[6, 4]).unwrap();
+        assert_eq!(r[[5, 3]], 23.0);
+        let r = t.select(0, 1).unwrap().reshape(&[4, 3]).unwrap();
+        assert_eq!(r[[0, 0]], 12.0);
+        assert_eq!(t.reshape(&[5, 4]).err(), Some(TensorError::Shape));
+        let p = t.permute(&[1, 0, 2]).unwrap();
+        assert_eq!(p.reshape(&[24]).err(), Some(TensorError::Shape));
+        assert_eq!(TensorView::new(&p.to_vec(), &[24]).unwrap()[[4]], 12.0);
+    }
+
+    #[test]
+    fn errors() {
+        let e = TensorError::IndexOutOfRange { index: 3, len: 2 };
+        assert_eq!(e.to_string(), "layer index 3 out of range for 2 layers");
+        assert_ne!(e, TensorError::NullBuffer);
+    }
+
+    #[test]
+    #[should_panic(expected = "tensor index out of bounds")]
+    fn index_out_of_bounds() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let _ = t.slice(2, 0..2).unwrap()[[0, 0, 2]];
+    }
+
+    #[test]
+    #[should_panic(expected = "tensor index out of bounds")]
+    fn index_wrong_rank() {
+        let data = data();
+        let t = TensorView::new(&data, &[2, 3, 4]).unwrap();
+        let _ = t[&[0, 0][..]];
+    }
+}
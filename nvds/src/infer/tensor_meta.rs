@@ -0,0 +1,119 @@
+//! Raw output tensors attached by nvinfer with `output-tensor-meta=1` as
+//! `NVDSINFER_TENSOR_OUTPUT_META` user meta.
+
+use super::tensor::{TensorError, TensorView};
+use super::{LayerInfo, NetworkInfo, TensorElement};
+use crate::meta::{BaseMetaType, FrameMeta, MetaType, ObjectMeta, UserMeta};
+use crate::WrapperExt;
+
+crate::wrapper_impl_ref_type!(TensorOutputMeta, nvidia_deepstream_sys::NvDsInferTensorMeta);
+
+impl TensorOutputMeta {
+    /// `gie-unique-id` of the nvinfer instance which attached the meta.
+    pub fn unique_id(&self) -> u32 {
+        self.as_native_type_ref().unique_id
+    }
+
+    pub fn num_output_layers(&self) -> u32 {
+        self.as_native_type_ref().num_output_layers
+    }
+
+    pub fn output_layers_info(&self) -> &[LayerInfo] {
+        let n = self.as_native_type_ref();
+        if n.output_layers_info.is_null() {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                n.output_layers_info as *const LayerInfo,
+                n.num_output_layers as _,
+            )
+        }
+    }
+
+    pub fn layer_index(&self, layer_name: &str) -> Option<usize> {
+        self.output_layers_info()
+            .iter()
+            .position(|l| l.layer_name().as_str() == layer_name)
+    }
+
+    pub fn gpu_id(&self) -> i32 {
+        self.as_native_type_ref().gpu_id
+    }
+
+    pub fn network_info(&self) -> &NetworkInfo {
+        NetworkInfo::from_native_type_ref(&self.as_native_type_ref().network_info)
+    }
+
+    /// Host buffer of the output layer `index`.
+    pub unsafe fn out_buf_ptr_host(&self, index: usize) -> *mut () {
+        let n = self.as_native_type_ref();
+        if n.out_buf_ptrs_host.is_null() || index >= n.num_output_layers as usize {
+            return std::ptr::null_mut();
+        }
+        *n.out_buf_ptrs_host.add(index) as _
+    }
+
+    /// Device buffer of the output layer `index`.
+    pub unsafe fn out_buf_ptr_dev(&self, index: usize) -> *mut () {
+        let n = self.as_native_type_ref();
+        if n.out_buf_ptrs_dev.is_null() || index >= n.num_output_layers as usize {
+            return std::ptr::null_mut();
+        }
+        *n.out_buf_ptrs_dev.add(index) as _
+    }
+
+    /// View of the host buffer of the output layer `index`.
+    pub fn tensor<T: TensorElement>(&self, index: usize) -> Result<TensorView<'_, T>, TensorError> {
+        let layers = self.output_layers_info();
+        let layer = layers.get(index).ok_or(TensorError::IndexOutOfRange {
+            index,
+            len: layers.len(),
+        })?;
+        unsafe { layer.tensor_with_buffer(self.out_buf_ptr_host(index) as _) }
+    }
+
+    /// Output layers with their host buffer views.
+    pub fn tensors<T: TensorElement>(
+        &self,
+    ) -> impl Iterator<Item = (&LayerInfo, Result<TensorView<'_, T>, TensorError>)> {
+        self.output_layers_info()
+            .iter()
+            .enumerate()
+            .map(move |(i, layer)| (layer, self.tensor(i)))
+    }
+}
+
+impl UserMeta {
+    pub fn tensor_output_meta(&self) -> Option<&TensorOutputMeta> {
+        if self.base_meta().meta_type() != MetaType::Base(BaseMetaType::TensorOutputMeta) {
+            return None;
+        }
+        unsafe {
+            self.user_meta_data::<nvidia_deepstream_sys::NvDsInferTensorMeta>()
+                .map(TensorOutputMeta::from_native_type_ref)
+        }
+    }
+}
+
+impl FrameMeta {
+    /// Tensor output meta attached by primary nvinfer instances.
+    pub fn tensor_outputs(&self) -> impl Iterator<Item = &TensorOutputMeta> {
+        self.frame_user_meta_list()
+            .map(|l| l.iter())
+            .into_iter()
+            .flatten()
+            .filter_map(UserMeta::tensor_output_meta)
+    }
+}
+
+impl ObjectMeta {
+    /// Tensor output meta attached by secondary nvinfer instances.
+    pub fn tensor_outputs(&self) -> impl Iterator<Item = &TensorOutputMeta> {
+        self.obj_user_meta_list()
+            .map(|l| l.iter())
+            .into_iter()
+            .flatten()
+            .filter_map(UserMeta::tensor_output_meta)
+    }
+}
@@ -1,8 +1,17 @@
 use std::fmt::Formatter;
 
 pub mod bounding_box;
+pub mod error;
 pub mod mem;
 
+pub use error::Error;
+
+#[cfg(feature = "best_shot")]
+pub mod best_shot;
+
+#[cfg(feature = "event_engine")]
+pub mod event_engine;
+
 #[cfg(feature = "helper")]
 pub mod helper;
 
@@ -15,18 +24,39 @@ pub mod logger;
 #[cfg(feature = "meta")]
 pub mod meta;
 
+#[cfg(feature = "msgbroker")]
+pub mod msgbroker;
+
+#[cfg(feature = "msgconv")]
+pub mod msgconv;
+
 #[cfg(feature = "obj_encode")]
 pub mod obj_encode;
 
+#[cfg(feature = "overlay")]
+pub mod overlay;
+
+#[cfg(feature = "reid")]
+pub mod reid;
+
+#[cfg(feature = "skeleton")]
+pub mod skeleton;
+
 #[cfg(feature = "surface")]
 pub mod surface;
 
 #[cfg(feature = "surface_transform")]
 pub mod surface_transform;
 
+#[cfg(feature = "timestamp")]
+pub mod timestamp;
+
 #[cfg(feature = "utils")]
 pub mod utils;
 
+#[cfg(feature = "world")]
+pub mod world;
+
 #[cfg(feature = "yaml")]
 pub mod yaml;
 
@@ -1,6 +1,8 @@
 pub mod audio;
 pub mod dewarper;
 pub mod latency;
+pub mod mask;
+pub mod misc;
 pub mod optical_flow;
 pub mod osd;
 pub mod schema;
@@ -12,6 +14,15 @@ use gstreamer::glib::GStr;
 use std::marker::PhantomData;
 use std::ptr::NonNull;
 
+/// Copies `s` into a NUL terminated label array, truncated to fit.
+fn copy_label(dst: &mut [std::os::raw::c_char], s: &str) {
+    let n = s.len().min(dst.len() - 1);
+    for (d, s) in dst.iter_mut().zip(&s.as_bytes()[..n]) {
+        *d = *s as _;
+    }
+    dst[n] = 0;
+}
+
 crate::wrapper_impl_ref_type!(RoiMeta, nvidia_deepstream_sys::NvDsRoiMeta);
 
 impl RoiMeta {
@@ -184,8 +195,8 @@ impl<'a, T: WrapperExt> MetaList<'a, T> {
         }
     }
 
-    pub fn iter(&self) -> MetaListIterator<T> {
-        MetaListIterator::<T> {
+    pub fn iter(&self) -> MetaListIterator<'a, T> {
+        MetaListIterator::<'a, T> {
             current: Some(self.list),
             phantom: PhantomData,
         }
@@ -566,6 +577,21 @@ impl FrameMeta {
         self.as_native_type_ref().misc_frame_info
     }
 
+    pub fn set_misc_frame_info(&mut self, value: [i64; 4usize]) {
+        self.as_native_type_mut().misc_frame_info = value;
+    }
+
+    pub fn misc_frame_info_as<S: misc::MiscSchema>(&self) -> Result<S, misc::MiscError> {
+        misc::read(&self.as_native_type_ref().misc_frame_info)
+    }
+
+    pub fn set_misc_frame_info_as<S: misc::MiscSchema>(
+        &mut self,
+        value: &S,
+    ) -> Result<(), misc::MiscError> {
+        misc::write(&mut self.as_native_type_mut().misc_frame_info, value)
+    }
+
     pub fn pipeline_width(&self) -> u32 {
         self.as_native_type_ref().pipeline_width
     }
@@ -712,6 +738,35 @@ impl ObjectMeta {
         self.as_native_type_ref().tracker_confidence
     }
 
+    pub fn set_unique_component_id(&mut self, id: i32) {
+        self.as_native_type_mut().unique_component_id = id;
+    }
+
+    pub fn set_class_id(&mut self, class_id: i32) {
+        self.as_native_type_mut().class_id = class_id;
+    }
+
+    pub fn set_object_id(&mut self, object_id: u64) {
+        self.as_native_type_mut().object_id = object_id;
+    }
+
+    pub fn set_confidence(&mut self, confidence: f32) {
+        self.as_native_type_mut().confidence = confidence;
+    }
+
+    pub fn set_detector_bbox_coords(&mut self, coords: &crate::bounding_box::Coords) {
+        self.as_native_type_mut().detector_bbox_info.org_bbox_coords = coords.as_native_type();
+    }
+
+    pub fn set_rect_params(&mut self, params: &osd::RectParams) {
+        self.as_native_type_mut().rect_params = params.as_native_type();
+    }
+
+    /// Truncated to `MAX_LABEL_SIZE - 1` bytes.
+    pub fn set_obj_label(&mut self, label: &str) {
+        copy_label(&mut self.as_native_type_mut().obj_label, label);
+    }
+
     pub fn rect_params(&self) -> &osd::RectParams {
         osd::RectParams::from_native_type_ref(&self.as_native_type_ref().rect_params)
     }
@@ -720,6 +775,28 @@ impl ObjectMeta {
         osd::MaskParams::from_native_type_ref(&self.as_native_type_ref().mask_params)
     }
 
+    /// Replaces the mask of this object. The previous mask buffer is released.
+    pub fn set_mask_params(&mut self, params: osd::MaskParams) {
+        osd::MaskParams::from_native_type_mut(&mut self.as_native_type_mut().mask_params)
+            .drop_ref();
+        self.as_native_type_mut().mask_params = params.to_glib_full();
+    }
+
+    /// Returns the mask resized to the object's bounding box and thresholded.
+    pub fn mask_bitmap(&self) -> Option<mask::Bitmap> {
+        let params = self.mask_params();
+        let width = self.rect_params().width().round() as u32;
+        let height = self.rect_params().height().round() as u32;
+        mask::resize(
+            params.data(),
+            params.width(),
+            params.height(),
+            width,
+            height,
+        )
+        .and_then(|m| mask::Bitmap::from_mask(&m, width, height, params.threshold()))
+    }
+
     pub fn text_params(&self) -> &osd::TextParams {
         osd::TextParams::from_native_type_ref(&self.as_native_type_ref().text_params)
     }
@@ -742,6 +819,21 @@ impl ObjectMeta {
         self.as_native_type_ref().misc_obj_info
     }
 
+    pub fn set_misc_obj_info(&mut self, value: [i64; 4usize]) {
+        self.as_native_type_mut().misc_obj_info = value;
+    }
+
+    pub fn misc_obj_info_as<S: misc::MiscSchema>(&self) -> Result<S, misc::MiscError> {
+        misc::read(&self.as_native_type_ref().misc_obj_info)
+    }
+
+    pub fn set_misc_obj_info_as<S: misc::MiscSchema>(
+        &mut self,
+        value: &S,
+    ) -> Result<(), misc::MiscError> {
+        misc::write(&mut self.as_native_type_mut().misc_obj_info, value)
+    }
+
     pub fn add_classifier_meta(&self, meta: &ClassifierMeta) {
         unsafe {
             nvidia_deepstream_sys::nvds_add_classifier_meta_to_object(
@@ -830,6 +922,14 @@ impl ClassifierMeta {
         unsafe { GStr::from_ptr(self.as_native_type_ref().classifier_type) }
     }
 
+    pub fn set_num_labels(&mut self, num_labels: u32) {
+        self.as_native_type_mut().num_labels = num_labels;
+    }
+
+    pub fn set_unique_component_id(&mut self, id: i32) {
+        self.as_native_type_mut().unique_component_id = id;
+    }
+
     pub fn add_label_info_meta(&self, meta: &LabelInfo) {
         unsafe {
             nvidia_deepstream_sys::nvds_add_label_info_meta_to_classifier(
@@ -899,6 +999,39 @@ impl LabelInfo {
         self.as_native_type_ref().result_prob
     }
 
+    pub fn set_num_classes(&mut self, num_classes: u32) {
+        self.as_native_type_mut().num_classes = num_classes;
+    }
+
+    /// Labels longer than `MAX_LABEL_SIZE - 1` bytes are stored in `pResult_label`,
+    /// which is released with the meta.
+    pub fn set_result_label(&mut self, label: &str) {
+        let n = self.as_native_type_mut();
+        unsafe {
+            nvidia_deepstream_sys::g_free(n.pResult_label as _);
+            n.pResult_label = std::ptr::null_mut();
+            if label.len() < n.result_label.len() {
+                copy_label(&mut n.result_label, label);
+            } else {
+                copy_label(&mut n.result_label, "");
+                n.pResult_label =
+                    nvidia_deepstream_sys::g_strndup(label.as_ptr() as _, label.len() as _);
+            }
+        }
+    }
+
+    pub fn set_result_class_id(&mut self, class_id: u32) {
+        self.as_native_type_mut().result_class_id = class_id;
+    }
+
+    pub fn set_label_id(&mut self, label_id: u32) {
+        self.as_native_type_mut().label_id = label_id;
+    }
+
+    pub fn set_result_prob(&mut self, prob: f32) {
+        self.as_native_type_mut().result_prob = prob;
+    }
+
     pub fn copy_to(&self, dst_label_info: &mut LabelInfo) {
         unsafe {
             nvidia_deepstream_sys::nvds_copy_label_info_meta(
@@ -985,6 +1118,23 @@ impl DisplayMeta {
         &self.as_native_type_ref().misc_osd_data
     }
 
+    pub fn set_misc_osd_data(&mut self, value: &[i64]) {
+        let data = &mut self.as_native_type_mut().misc_osd_data;
+        let len = std::cmp::min(data.len(), value.len());
+        data[..len].copy_from_slice(&value[..len]);
+    }
+
+    pub fn misc_osd_data_as<S: misc::MiscSchema>(&self) -> Result<S, misc::MiscError> {
+        misc::read(&self.as_native_type_ref().misc_osd_data)
+    }
+
+    pub fn set_misc_osd_data_as<S: misc::MiscSchema>(
+        &mut self,
+        value: &S,
+    ) -> Result<(), misc::MiscError> {
+        misc::write(&mut self.as_native_type_mut().misc_osd_data, value)
+    }
+
     pub fn copy_to(&self, dst_display_meta: &mut DisplayMeta) {
         unsafe {
             nvidia_deepstream_sys::nvds_copy_display_meta(
@@ -1002,6 +1152,8 @@ pub struct DisplayMetaBuilder<'a> {
     arrow_params: Option<&'a mut [osd::ArrowParamsBuilder]>,
     circle_params: Option<&'a mut [osd::CircleParamsBuilder]>,
     misc_osd_data: Option<&'a [i64]>,
+    misc_osd_layout: misc::MiscLayout,
+    misc_osd_values: Vec<(usize, i64)>,
 }
 
 impl<'a> DisplayMetaBuilder<'a> {
@@ -1013,6 +1165,8 @@ impl<'a> DisplayMetaBuilder<'a> {
             arrow_params: None,
             circle_params: None,
             misc_osd_data: None,
+            misc_osd_layout: misc::MiscLayout::new(misc::MISC_LEN),
+            misc_osd_values: Vec::new(),
         }
     }
 
@@ -1046,6 +1200,20 @@ impl<'a> DisplayMetaBuilder<'a> {
         self
     }
 
+    /// Stores `value` in the slots of its schema. Fails if the slots collide with
+    /// a schema given earlier to this builder.
+    pub fn misc_osd_data_as<S: misc::MiscSchema>(
+        mut self,
+        value: &S,
+    ) -> Result<Self, misc::MiscError> {
+        self.misc_osd_layout.register::<S>()?;
+        let mut slots = [0i64; misc::MISC_LEN];
+        value.write(&mut slots);
+        self.misc_osd_values
+            .extend(S::FIELDS.iter().map(|f| (f.slot, slots[f.slot])));
+        Ok(self)
+    }
+
     pub fn build<BM: BatchMetaExt>(
         self,
         display_meta_pool_batch_meta: &BM,
@@ -1143,6 +1311,10 @@ impl<'a> DisplayMetaBuilder<'a> {
                     }
                 }
 
+                for (slot, value) in self.misc_osd_values {
+                    display_meta.as_native_type_mut().misc_osd_data[slot] = value;
+                }
+
                 display_meta as _
             })
     }
@@ -1198,7 +1370,7 @@ impl UserMeta {
             })
     }
 
-    extern "C" fn base_meta_copy_func<T: Clone>(
+    pub(crate) extern "C" fn base_meta_copy_func<T: Clone>(
         data: nvidia_deepstream_sys::gpointer,
         _: nvidia_deepstream_sys::gpointer,
     ) -> nvidia_deepstream_sys::gpointer {
@@ -1217,7 +1389,7 @@ impl UserMeta {
         }
     }
 
-    extern "C" fn base_meta_release_func<T: Clone>(
+    pub(crate) extern "C" fn base_meta_release_func<T: Clone>(
         data: nvidia_deepstream_sys::gpointer,
         _: nvidia_deepstream_sys::gpointer,
     ) {
@@ -89,7 +89,7 @@ impl FrameLatencyInfo {
 
 pub trait BufferExt {
     fn set_input_system_timestamp(&self, element_name: &GStr) -> Option<&UserMeta>;
-    fn set_output_system_timestamp(&self, element_name: &GStr) -> Result<(), bool>;
+    fn set_output_system_timestamp(&self, element_name: &GStr) -> Result<(), crate::Error>;
     fn measure_buffer_latency(&self, latency_info: &FrameLatencyInfo) -> u32;
     fn add_reference_timestamp_meta(&self, element_name: &GStr, frame_id: u32);
 }
@@ -105,7 +105,7 @@ impl BufferExt for gstreamer::Buffer {
         }
     }
 
-    fn set_output_system_timestamp(&self, element_name: &GStr) -> Result<(), bool> {
+    fn set_output_system_timestamp(&self, element_name: &GStr) -> Result<(), crate::Error> {
         unsafe {
             if nvidia_deepstream_sys::nvds_set_output_system_timestamp(
                 self.as_ptr() as _,
@@ -114,7 +114,9 @@ impl BufferExt for gstreamer::Buffer {
             {
                 Ok(())
             } else {
-                Err(false)
+                Err(crate::Error::Meta {
+                    function: "nvds_set_output_system_timestamp",
+                })
             }
         }
     }
@@ -0,0 +1,657 @@
+/// Binary mask in row-major order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    data: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(width: u32, height: u32) -> Bitmap {
+        Bitmap {
+            width,
+            height,
+            data: vec![false; width as usize * height as usize],
+        }
+    }
+
+    pub fn from_mask(mask: &[f32], width: u32, height: u32, threshold: f32) -> Option<Bitmap> {
+        if mask.len() < width as usize * height as usize {
+            return None;
+        }
+        Some(Bitmap {
+            width,
+            height,
+            data: mask[..width as usize * height as usize]
+                .iter()
+                .map(|v| *v > threshold)
+                .collect(),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn data(&self) -> &[bool] {
+        &self.data
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.data[(y * self.width + x) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, value: bool) {
+        if x < self.width && y < self.height {
+            self.data[(y * self.width + x) as usize] = value;
+        }
+    }
+
+    pub fn area(&self) -> u32 {
+        self.data.iter().filter(|v| **v).count() as _
+    }
+
+    /// Returns the mask as 0.0/1.0 values, e.g. to be drawn through `MaskParamsBuilder`.
+    pub fn to_mask(&self) -> Vec<f32> {
+        self.data
+            .iter()
+            .map(|v| if *v { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    pub fn resize(&self, width: u32, height: u32) -> Bitmap {
+        let mut r = Bitmap::new(width, height);
+        if self.width == 0 || self.height == 0 {
+            return r;
+        }
+        for y in 0..height {
+            let sy = (y as u64 * self.height as u64 / height as u64) as u32;
+            for x in 0..width {
+                let sx = (x as u64 * self.width as u64 / width as u64) as u32;
+                r.data[(y * width + x) as usize] = self.get(sx, sy);
+            }
+        }
+        r
+    }
+
+    /// IoU of two masks of the same size.
+    pub fn iou(&self, other: &Bitmap) -> f32 {
+        self.iou_with_offset((0, 0), other, (0, 0))
+    }
+
+    /// IoU of two masks placed at `offset` and `other_offset` in frame coordinates,
+    /// e.g. the left/top of the objects' bounding boxes.
+    pub fn iou_with_offset(
+        &self,
+        offset: (i32, i32),
+        other: &Bitmap,
+        other_offset: (i32, i32),
+    ) -> f32 {
+        let left = std::cmp::max(offset.0, other_offset.0);
+        let top = std::cmp::max(offset.1, other_offset.1);
+        let right = std::cmp::min(
+            offset.0 + self.width as i32,
+            other_offset.0 + other.width as i32,
+        );
+        let bottom = std::cmp::min(
+            offset.1 + self.height as i32,
+            other_offset.1 + other.height as i32,
+        );
+
+        let mut intersection = 0u32;
+        for y in top..bottom {
+            for x in left..right {
+                if self.get((x - offset.0) as _, (y - offset.1) as _)
+                    && other.get((x - other_offset.0) as _, (y - other_offset.1) as _)
+                {
+                    intersection += 1;
+                }
+            }
+        }
+
+        let union = self.area() + other.area() - intersection;
+        if union > 0 {
+            intersection as f32 / union as f32
+        } else {
+            0.0
+        }
+    }
+
+    pub fn to_rle(&self) -> Rle {
+        Rle::from_bitmap(self)
+    }
+
+    /// The part of the mask at `left`, `top`, clipped to the mask.
+    pub fn crop(&self, left: u32, top: u32, width: u32, height: u32) -> Bitmap {
+        let width = width.min(self.width.saturating_sub(left));
+        let height = height.min(self.height.saturating_sub(top));
+        let mut r = Bitmap::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                r.data[(y * width + x) as usize] = self.get(left + x, top + y);
+            }
+        }
+        r
+    }
+
+    /// 8-connected components, in raster order of their first pixel.
+    pub fn components(&self) -> Vec<Component> {
+        let mut labelled = vec![false; self.data.len()];
+        let mut components = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..self.data.len() {
+            if !self.data[start] || labelled[start] {
+                continue;
+            }
+            let (sx, sy) = (start as u32 % self.width, start as u32 / self.width);
+            let mut c = Component {
+                left: sx,
+                top: sy,
+                width: 1,
+                height: 1,
+                area: 0,
+                start: (sx, sy),
+            };
+            let (mut right, mut bottom) = (sx, sy);
+            labelled[start] = true;
+            stack.push((sx, sy));
+            while let Some((x, y)) = stack.pop() {
+                c.area += 1;
+                c.left = c.left.min(x);
+                right = right.max(x);
+                bottom = bottom.max(y);
+                for (nx, ny) in self.neighbours(x, y) {
+                    let i = (ny * self.width + nx) as usize;
+                    if self.data[i] && !labelled[i] {
+                        labelled[i] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            c.width = right - c.left + 1;
+            c.height = bottom - c.top + 1;
+            components.push(c);
+        }
+        components
+    }
+
+    fn neighbours(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        CLOCKWISE.iter().filter_map(move |&(dx, dy)| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            (nx >= 0 && ny >= 0 && nx < self.width as i64 && ny < self.height as i64)
+                .then_some((nx as u32, ny as u32))
+        })
+    }
+
+    fn is_set(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && self.get(x as _, y as _)
+    }
+
+    /// Outer boundary pixels of `component`, clockwise, by Moore neighbour tracing.
+    /// Holes are not traced.
+    pub fn contour(&self, component: &Component) -> Vec<[u32; 2]> {
+        let start = (component.start.0 as i64, component.start.1 as i64);
+        let mut contour = vec![[start.0 as u32, start.1 as u32]];
+        // The west neighbour of the first pixel in raster order is unset.
+        let (mut cur, mut backtrack) = (start, 0);
+        let mut second = None;
+        while let Some(d) = (1..=8)
+            .map(|i| (backtrack + i) % 8)
+            .find(|&d| self.is_set(cur.0 + CLOCKWISE[d].0, cur.1 + CLOCKWISE[d].1))
+        {
+            let next = (cur.0 + CLOCKWISE[d].0, cur.1 + CLOCKWISE[d].1);
+            if cur == start && second == Some(next) {
+                contour.pop();
+                break;
+            }
+            if second.is_none() {
+                second = Some(next);
+            }
+            // The unset neighbour checked before `next`, seen from `next`.
+            let (px, py) = CLOCKWISE[(d + 7) % 8];
+            let (bx, by) = (cur.0 + px - next.0, cur.1 + py - next.1);
+            backtrack = CLOCKWISE.iter().position(|&n| n == (bx, by)).unwrap_or(0);
+            cur = next;
+            contour.push([cur.0 as u32, cur.1 as u32]);
+        }
+        contour
+    }
+
+    /// Outer contours of every component of at least `min_area` pixels.
+    pub fn contours(&self, min_area: u32) -> Vec<Vec<[u32; 2]>> {
+        self.components()
+            .iter()
+            .filter(|c| c.area >= min_area)
+            .map(|c| self.contour(c))
+            .collect()
+    }
+}
+
+/// Neighbour offsets, clockwise from the west.
+const CLOCKWISE: [(i64, i64); 8] = [
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+];
+
+/// Connected component of a [`Bitmap`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Component {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Number of pixels.
+    pub area: u32,
+    start: (u32, u32),
+}
+
+/// Douglas-Peucker simplification of a closed polygon, removing vertices closer than
+/// `epsilon` to the simplified outline.
+pub fn simplify_polygon(points: &[[u32; 2]], epsilon: f32) -> Vec<[u32; 2]> {
+    fn distance(p: [u32; 2], a: [u32; 2], b: [u32; 2]) -> f32 {
+        let [px, py, ax, ay, bx, by] = [p[0], p[1], a[0], a[1], b[0], b[1]].map(|v| v as f32);
+        let (dx, dy) = (bx - ax, by - ay);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            ((px - ax).powi(2) + (py - ay).powi(2)).sqrt()
+        } else {
+            (dy * px - dx * py + bx * ay - by * ax).abs() / len
+        }
+    }
+
+    fn simplify(points: &[[u32; 2]], epsilon: f32, out: &mut Vec<[u32; 2]>) {
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let farthest = points[1..points.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (i + 1, distance(p, first, last)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match farthest {
+            Some((i, d)) if d > epsilon => {
+                simplify(&points[..=i], epsilon, out);
+                simplify(&points[i..], epsilon, out);
+            }
+            _ => out.push(first),
+        }
+    }
+
+    if points.len() < 4 {
+        return points.to_vec();
+    }
+    // Split the closed outline at the vertex farthest from the first one.
+    let far = (1..points.len())
+        .max_by_key(|&i| {
+            let [dx, dy] = [0, 1].map(|k| points[i][k].abs_diff(points[0][k]) as u64);
+            dx * dx + dy * dy
+        })
+        .unwrap_or(0);
+    let mut out = Vec::new();
+    simplify(&points[..=far], epsilon, &mut out);
+    let mut rest = points[far..].to_vec();
+    rest.push(points[0]);
+    simplify(&rest, epsilon, &mut out);
+    out
+}
+
+/// Resizes a float mask with bilinear interpolation, the same way nvdsosd scales
+/// instance masks to the object bounding box.
+pub fn resize(
+    mask: &[f32],
+    width: u32,
+    height: u32,
+    dst_width: u32,
+    dst_height: u32,
+) -> Option<Vec<f32>> {
+    if mask.len() < width as usize * height as usize || width == 0 || height == 0 {
+        return None;
+    }
+    let at = |x: usize, y: usize| mask[y * width as usize + x];
+    let scale_x = width as f32 / dst_width as f32;
+    let scale_y = height as f32 / dst_height as f32;
+
+    let mut r = Vec::with_capacity(dst_width as usize * dst_height as usize);
+    for y in 0..dst_height {
+        let fy = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (height - 1) as f32);
+        let y0 = fy as usize;
+        let y1 = std::cmp::min(y0 + 1, height as usize - 1);
+        let dy = fy - y0 as f32;
+        for x in 0..dst_width {
+            let fx = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (width - 1) as f32);
+            let x0 = fx as usize;
+            let x1 = std::cmp::min(x0 + 1, width as usize - 1);
+            let dx = fx - x0 as f32;
+            let top = at(x0, y0) * (1.0 - dx) + at(x1, y0) * dx;
+            let bottom = at(x0, y1) * (1.0 - dx) + at(x1, y1) * dx;
+            r.push(top * (1.0 - dy) + bottom * dy);
+        }
+    }
+    Some(r)
+}
+
+/// Uncompressed run-length encoding compatible with the COCO format
+/// (column-major, starting with a run of zeros).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rle {
+    width: u32,
+    height: u32,
+    counts: Vec<u32>,
+}
+
+impl Rle {
+    pub fn new(width: u32, height: u32, counts: Vec<u32>) -> Rle {
+        Rle {
+            width,
+            height,
+            counts,
+        }
+    }
+
+    pub fn from_bitmap(bitmap: &Bitmap) -> Rle {
+        let mut counts = Vec::new();
+        let mut current = false;
+        let mut run = 0u32;
+        for x in 0..bitmap.width {
+            for y in 0..bitmap.height {
+                let v = bitmap.data[(y * bitmap.width + x) as usize];
+                if v != current {
+                    counts.push(run);
+                    run = 0;
+                    current = v;
+                }
+                run += 1;
+            }
+        }
+        counts.push(run);
+        Rle {
+            width: bitmap.width,
+            height: bitmap.height,
+            counts,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    pub fn area(&self) -> u32 {
+        self.counts.iter().skip(1).step_by(2).sum()
+    }
+
+    /// Runs past the end of the mask are clipped.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut r = Bitmap::new(self.width, self.height);
+        let len = r.data.len() as u64;
+        let mut index = 0u64;
+        let mut value = false;
+        for &count in &self.counts {
+            let end = std::cmp::min(index + count as u64, len);
+            for i in index..end {
+                let x = i / self.height as u64;
+                let y = i % self.height as u64;
+                r.data[(y * self.width as u64 + x) as usize] = value;
+            }
+            index = end;
+            value = !value;
+        }
+        r
+    }
+
+    pub fn iou(&self, other: &Rle) -> f32 {
+        self.to_bitmap().iou(&other.to_bitmap())
+    }
+
+    /// Compressed string representation as produced by pycocotools `encode`.
+    pub fn to_coco_string(&self) -> String {
+        let mut r = String::new();
+        for i in 0..self.counts.len() {
+            let mut x = self.counts[i] as i64;
+            if i > 2 {
+                x -= self.counts[i - 2] as i64;
+            }
+            let mut more = true;
+            while more {
+                let mut c = x & 0x1f;
+                x >>= 5;
+                more = if c & 0x10 != 0 { x != -1 } else { x != 0 };
+                if more {
+                    c |= 0x20;
+                }
+                r.push((c as u8 + 48) as char);
+            }
+        }
+        r
+    }
+
+    /// Returns `None` if `s` is not a valid string of counts.
+    pub fn from_coco_string(width: u32, height: u32, s: &str) -> Option<Rle> {
+        // A difference of two `u32` counts fits in 7 chunks of 5 bits.
+        const MAX_CHUNKS: u32 = 7;
+
+        let s = s.as_bytes();
+        let mut counts = Vec::<u32>::new();
+        let mut p = 0;
+        while p < s.len() {
+            let mut x = 0i64;
+            let mut k = 0;
+            let mut more = true;
+            while more {
+                if k == MAX_CHUNKS {
+                    return None;
+                }
+                let c = s.get(p)?.checked_sub(48).filter(|c| *c < 64)? as i64;
+                x |= (c & 0x1f) << (5 * k);
+                more = c & 0x20 != 0;
+                p += 1;
+                k += 1;
+                if !more && c & 0x10 != 0 {
+                    x |= -1 << (5 * k);
+                }
+            }
+            if counts.len() > 2 {
+                x += counts[counts.len() - 2] as i64;
+            }
+            counts.push(u32::try_from(x).ok()?);
+        }
+        Some(Rle {
+            width,
+            height,
+            counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitmap(rows: &[&str]) -> Bitmap {
+        let mut b = Bitmap::new(rows[0].len() as u32, rows.len() as u32);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                b.set(x as u32, y as u32, c == '#');
+            }
+        }
+        b
+    }
+
+    #[test]
+    fn components() {
+        let b = bitmap(&["#....", "#..#.", "##..#", "....."]);
+        let c = b.components();
+        assert_eq!(c.len(), 2);
+        assert_eq!(
+            (c[0].left, c[0].top, c[0].width, c[0].height, c[0].area),
+            (0, 0, 2, 3, 4)
+        );
+        // Diagonal neighbours are connected.
+        assert_eq!(
+            (c[1].left, c[1].top, c[1].width, c[1].height, c[1].area),
+            (3, 1, 2, 2, 2)
+        );
+        assert!(bitmap(&["...", "..."]).components().is_empty());
+
+        // A hole does not split the component.
+        let ring = bitmap(&["#####", "#...#", "#...#", "#####"]);
+        let c = ring.components();
+        assert_eq!(c.len(), 1);
+        assert_eq!((c[0].width, c[0].height, c[0].area), (5, 4, 14));
+    }
+
+    #[test]
+    fn l_shape_contour() {
+        let b = bitmap(&[".....", ".#...", ".#...", ".###.", "....."]);
+        let c = b.components();
+        // The one pixel wide arms are traced down and back up.
+        assert_eq!(
+            b.contour(&c[0]),
+            [[1, 1], [1, 2], [2, 3], [3, 3], [2, 3], [1, 3], [1, 2]]
+        );
+        assert_eq!(b.contours(6).len(), 0);
+        assert_eq!(b.contours(5).len(), 1);
+
+        let b = bitmap(&["...", ".#.", "..."]);
+        assert_eq!(b.contour(&b.components()[0]), [[1, 1]]);
+    }
+
+    #[test]
+    fn hole_contour() {
+        let b = bitmap(&["#####", "#...#", "#...#", "#####"]);
+        assert_eq!(
+            b.contour(&b.components()[0]),
+            [
+                [0, 0],
+                [1, 0],
+                [2, 0],
+                [3, 0],
+                [4, 0],
+                [4, 1],
+                [4, 2],
+                [4, 3],
+                [3, 3],
+                [2, 3],
+                [1, 3],
+                [0, 3],
+                [0, 2],
+                [0, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn border_contour() {
+        let b = bitmap(&["###", "###"]);
+        assert_eq!(
+            b.contour(&b.components()[0]),
+            [[0, 0], [1, 0], [2, 0], [2, 1], [1, 1], [0, 1]]
+        );
+        let b = bitmap(&["..#", ".##", "###"]);
+        assert_eq!(
+            b.contour(&b.components()[0]),
+            [[2, 0], [2, 1], [2, 2], [1, 2], [0, 2], [1, 1]]
+        );
+    }
+
+    #[test]
+    fn simplify() {
+        let square = [
+            [1, 1],
+            [2, 1],
+            [3, 1],
+            [3, 2],
+            [3, 3],
+            [2, 3],
+            [1, 3],
+            [1, 2],
+        ];
+        assert_eq!(
+            simplify_polygon(&square, 0.5),
+            [[1, 1], [3, 1], [3, 3], [1, 3]]
+        );
+        // A bump of 1 pixel is kept below its height only.
+        let bump = [[0, 0], [2, 0], [3, 1], [4, 0], [6, 0], [6, 4], [0, 4]];
+        assert_eq!(simplify_polygon(&bump, 0.5), bump);
+        assert_eq!(
+            simplify_polygon(&bump, 1.5),
+            [[0, 0], [6, 0], [6, 4], [0, 4]]
+        );
+        assert_eq!(simplify_polygon(&square[..3], 10.0), square[..3]);
+        assert!(simplify_polygon(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let b = bitmap(&["..##.", ".###.", "#...#", "....."]);
+        let rle = b.to_rle();
+        // Column-major runs, starting with zeros.
+        assert_eq!(rle.counts(), [2, 1, 2, 1, 2, 2, 2, 2, 4, 1, 1]);
+        assert_eq!(rle.area(), b.area());
+        let s = rle.to_coco_string();
+        let decoded = Rle::from_coco_string(5, 4, &s).unwrap();
+        assert_eq!(decoded, rle);
+        assert_eq!(decoded.to_bitmap(), b);
+
+        for b in [
+            bitmap(&["....", "...."]),
+            bitmap(&["####", "####"]),
+            bitmap(&["#...", "...#"]),
+        ] {
+            let s = b.to_rle().to_coco_string();
+            assert_eq!(Rle::from_coco_string(4, 2, &s).unwrap().to_bitmap(), b);
+        }
+    }
+
+    #[test]
+    fn coco_string() {
+        assert_eq!(Rle::new(3, 3, vec![3, 2, 4]).to_coco_string(), "324");
+        // The fourth count is encoded as the difference to the second, -4.
+        assert_eq!(Rle::new(3, 3, vec![1, 5, 2, 1]).to_coco_string(), "152L");
+        assert_eq!(
+            Rle::from_coco_string(3, 3, "152L").unwrap().counts(),
+            [1, 5, 2, 1]
+        );
+        let large = Rle::new(1 << 16, 1 << 16, vec![0, u32::MAX, 1, 0]);
+        assert_eq!(
+            Rle::from_coco_string(1 << 16, 1 << 16, &large.to_coco_string()).unwrap(),
+            large
+        );
+    }
+
+    #[test]
+    fn malformed_coco_string() {
+        // Continuation bit on the last chunk.
+        assert_eq!(Rle::from_coco_string(2, 2, "P"), None);
+        // Too many chunks for a count.
+        assert_eq!(Rle::from_coco_string(2, 2, &"P".repeat(20)), None);
+        // Outside of the alphabet.
+        assert_eq!(Rle::from_coco_string(2, 2, " "), None);
+        assert_eq!(Rle::from_coco_string(2, 2, "\u{7f}"), None);
+        // Negative count.
+        assert_eq!(Rle::from_coco_string(2, 2, "L"), None);
+    }
+
+    #[test]
+    fn rle_past_the_end() {
+        let b = Rle::new(2, 2, vec![1, u32::MAX, u32::MAX]).to_bitmap();
+        assert_eq!(b, bitmap(&[".#", "##"]));
+        let b = Rle::new(u32::MAX, 0, vec![u32::MAX, u32::MAX]).to_bitmap();
+        assert_eq!(b.area(), 0);
+    }
+}
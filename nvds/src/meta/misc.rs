@@ -0,0 +1,206 @@
+pub const MISC_LEN: usize = nvidia_deepstream_sys::MAX_USER_FIELDS as _;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub slot: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MiscError {
+    OutOfBounds {
+        field: &'static str,
+        slot: usize,
+        len: usize,
+    },
+    Collision {
+        field: &'static str,
+        other: &'static str,
+        slot: usize,
+    },
+}
+
+impl std::fmt::Display for MiscError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MiscError::OutOfBounds { field, slot, len } => write!(
+                f,
+                "field `{}` uses slot {} but only {} slots are available",
+                field, slot, len
+            ),
+            MiscError::Collision { field, other, slot } => {
+                write!(
+                    f,
+                    "field `{}` collides with `{}` at slot {}",
+                    field, other, slot
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MiscError {}
+
+pub trait MiscValue: Sized {
+    fn from_slot(v: i64) -> Self;
+    fn to_slot(self) -> i64;
+}
+
+macro_rules! impl_misc_value_int {
+    ($t:ty) => {
+        impl MiscValue for $t {
+            fn from_slot(v: i64) -> Self {
+                v as _
+            }
+
+            fn to_slot(self) -> i64 {
+                self as _
+            }
+        }
+    };
+}
+
+impl_misc_value_int!(i64);
+impl_misc_value_int!(u64);
+impl_misc_value_int!(i32);
+impl_misc_value_int!(u32);
+impl_misc_value_int!(i16);
+impl_misc_value_int!(u16);
+
+impl MiscValue for bool {
+    fn from_slot(v: i64) -> Self {
+        v != 0
+    }
+
+    fn to_slot(self) -> i64 {
+        self as _
+    }
+}
+
+impl MiscValue for f64 {
+    fn from_slot(v: i64) -> Self {
+        f64::from_bits(v as _)
+    }
+
+    fn to_slot(self) -> i64 {
+        self.to_bits() as _
+    }
+}
+
+impl MiscValue for f32 {
+    fn from_slot(v: i64) -> Self {
+        f32::from_bits(v as _)
+    }
+
+    fn to_slot(self) -> i64 {
+        self.to_bits() as _
+    }
+}
+
+/// Mapping of named fields to misc slots, usually implemented with `misc_schema!`.
+pub trait MiscSchema: Sized {
+    const FIELDS: &'static [Field];
+
+    fn read(slots: &[i64]) -> Self;
+    fn write(&self, slots: &mut [i64]);
+}
+
+/// Slots claimed by the components sharing one kind of misc data.
+pub struct MiscLayout {
+    len: usize,
+    fields: Vec<Field>,
+}
+
+impl MiscLayout {
+    pub fn new(len: usize) -> MiscLayout {
+        MiscLayout {
+            len,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn register<S: MiscSchema>(&mut self) -> Result<(), MiscError> {
+        self.register_fields(S::FIELDS)
+    }
+
+    pub fn register_fields(&mut self, fields: &'static [Field]) -> Result<(), MiscError> {
+        let start = self.fields.len();
+        for field in fields {
+            if field.slot >= self.len {
+                self.fields.truncate(start);
+                return Err(MiscError::OutOfBounds {
+                    field: field.name,
+                    slot: field.slot,
+                    len: self.len,
+                });
+            }
+            if let Some(other) = self.fields.iter().find(|f| f.slot == field.slot) {
+                let err = MiscError::Collision {
+                    field: field.name,
+                    other: other.name,
+                    slot: field.slot,
+                };
+                self.fields.truncate(start);
+                return Err(err);
+            }
+            self.fields.push(*field);
+        }
+        Ok(())
+    }
+}
+
+pub fn read<S: MiscSchema>(slots: &[i64]) -> Result<S, MiscError> {
+    MiscLayout::new(slots.len()).register::<S>()?;
+    Ok(S::read(slots))
+}
+
+pub fn write<S: MiscSchema>(slots: &mut [i64], value: &S) -> Result<(), MiscError> {
+    MiscLayout::new(slots.len()).register::<S>()?;
+    value.write(slots);
+    Ok(())
+}
+
+/// Declares a struct whose fields are stored in the given misc slots.
+///
+/// ```ignore
+/// misc_schema! {
+///     pub struct ZoneInfo {
+///         pub zone_id: u32 = 0,
+///         pub crossed: bool = 1,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! misc_schema {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $ty:ty = $slot:expr),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($fvis $field: $ty),*
+        }
+
+        impl $crate::meta::misc::MiscSchema for $name {
+            const FIELDS: &'static [$crate::meta::misc::Field] = &[
+                $($crate::meta::misc::Field { name: stringify!($field), slot: $slot }),*
+            ];
+
+            fn read(slots: &[i64]) -> Self {
+                $name {
+                    $($field: $crate::meta::misc::MiscValue::from_slot(slots[$slot])),*
+                }
+            }
+
+            fn write(&self, slots: &mut [i64]) {
+                $(slots[$slot] = $crate::meta::misc::MiscValue::to_slot(self.$field);)*
+            }
+        }
+    };
+}
@@ -384,7 +384,7 @@ impl MaskParams {
             unsafe {
                 std::slice::from_raw_parts(
                     self.as_native_type_ref().data,
-                    self.as_native_type_ref().size as _,
+                    self.as_native_type_ref().size as usize / std::mem::size_of::<f32>(),
                 )
             }
         } else {
@@ -403,16 +403,39 @@ impl MaskParams {
     pub fn height(&self) -> u32 {
         self.as_native_type_ref().height
     }
+
+    pub fn to_bitmap(&self) -> Option<super::mask::Bitmap> {
+        super::mask::Bitmap::from_mask(self.data(), self.width(), self.height(), self.threshold())
+    }
+
+    pub fn to_glib_full(mut self) -> nvidia_deepstream_sys::NvOSD_MaskParams {
+        let ret = *self.as_native_type_ref();
+        self.as_native_type_mut().data = null_mut();
+        self.as_native_type_mut().size = 0;
+        ret
+    }
+
+    pub(crate) fn drop_ref(&mut self) {
+        unsafe {
+            glib::ffi::g_free(self.as_native_type_ref().data as _);
+        }
+    }
 }
 
-pub struct MaskParamsBuilder {
-    data: Option<&'static [f32]>,
+impl Drop for MaskParams {
+    fn drop(&mut self) {
+        self.drop_ref();
+    }
+}
+
+pub struct MaskParamsBuilder<'a> {
+    data: Option<&'a [f32]>,
     threshold: Option<f32>,
     width: Option<u32>,
     height: Option<u32>,
 }
 
-impl MaskParamsBuilder {
+impl<'a> MaskParamsBuilder<'a> {
     pub fn new() -> Self {
         MaskParamsBuilder {
             data: None,
@@ -422,7 +445,15 @@ impl MaskParamsBuilder {
         }
     }
 
-    pub fn data(mut self, data: &'static [f32]) -> Self {
+    #[cfg(feature = "infer")]
+    pub fn from_instance_mask_info(info: &'a crate::infer::InstanceMaskInfo) -> Self {
+        MaskParamsBuilder::new()
+            .data(info.mask())
+            .width(info.mask_width())
+            .height(info.mask_height())
+    }
+
+    pub fn data(mut self, data: &'a [f32]) -> Self {
         self.data = Some(data);
         self
     }
@@ -442,10 +473,20 @@ impl MaskParamsBuilder {
         self
     }
 
+    /// Build MaskParams. The mask data is copied into a glib allocated buffer.
     pub fn build(self) -> MaskParams {
+        let (data, size) = match self.data {
+            Some(v) if !v.is_empty() => unsafe {
+                let size = std::mem::size_of_val(v);
+                let p = glib::ffi::g_malloc(size) as *mut f32;
+                std::ptr::copy_nonoverlapping(v.as_ptr(), p, v.len());
+                (p, size)
+            },
+            _ => (null_mut(), 0),
+        };
         MaskParams::from_native_type(nvidia_deepstream_sys::NvOSD_MaskParams {
-            data: self.data.map_or(std::ptr::null_mut(), |v| v.as_ptr() as _),
-            size: self.data.map_or(0, |v| v.len() as _),
+            data,
+            size: size as _,
             threshold: self.threshold.unwrap_or_default(),
             width: self.width.unwrap_or_default(),
             height: self.height.unwrap_or_default(),
@@ -1,3 +1,4 @@
+use super::{BaseMetaType, BatchMetaExt, FrameMeta, MetaType, UserMeta};
 use crate::{duplicate_glib_string, glib_free, WrapperExt};
 use gstreamer::glib::translate::ToGlibPtr;
 use gstreamer::glib::GStr;
@@ -42,6 +43,9 @@ pub enum PayloadType {
     #[default]
     DeepStream = nvidia_deepstream_sys::NvDsPayloadType_NVDS_PAYLOAD_DEEPSTREAM as _,
     DeepstreamMinimal = nvidia_deepstream_sys::NvDsPayloadType_NVDS_PAYLOAD_DEEPSTREAM_MINIMAL as _,
+    #[cfg(feature = "v6_3")]
+    DeepstreamProtobuf =
+        nvidia_deepstream_sys::NvDsPayloadType_NVDS_PAYLOAD_DEEPSTREAM_PROTOBUF as _,
     Reserved = nvidia_deepstream_sys::NvDsPayloadType_NVDS_PAYLOAD_RESERVED as _,
     Custom = nvidia_deepstream_sys::NvDsPayloadType_NVDS_PAYLOAD_CUSTOM as _,
 }
@@ -394,7 +398,7 @@ impl Clone for FaceObject {
                 gender: duplicate_glib_string(self.as_native_type_ref().gender),
                 hair: duplicate_glib_string(self.as_native_type_ref().hair),
                 cap: duplicate_glib_string(self.as_native_type_ref().cap),
-                glasses: duplicate_glib_string(self.as_native_type_ref().gender),
+                glasses: duplicate_glib_string(self.as_native_type_ref().glasses),
                 facialhair: duplicate_glib_string(self.as_native_type_ref().facialhair),
                 name: duplicate_glib_string(self.as_native_type_ref().name),
                 eyecolor: duplicate_glib_string(self.as_native_type_ref().eyecolor),
@@ -410,7 +414,7 @@ impl Drop for FaceObject {
             glib_free(self.as_native_type_ref().gender);
             glib_free(self.as_native_type_ref().hair);
             glib_free(self.as_native_type_ref().cap);
-            glib_free(self.as_native_type_ref().gender);
+            glib_free(self.as_native_type_ref().glasses);
             glib_free(self.as_native_type_ref().facialhair);
             glib_free(self.as_native_type_ref().name);
             glib_free(self.as_native_type_ref().eyecolor);
@@ -601,7 +605,7 @@ impl FaceObjectExt {
 }
 
 #[cfg(feature = "v6_2")]
-use v6_2::*;
+pub use v6_2::*;
 #[cfg(feature = "v6_2")]
 mod v6_2 {
     use crate::WrapperExt;
@@ -609,9 +613,11 @@ mod v6_2 {
     crate::wrapper_impl_ref_type!(Embedding, nvidia_deepstream_sys::NvDsEmbedding);
 
     impl Embedding {
-        // TODO: check the safety of this impl
         #[doc(alias = "embedding_length")]
         pub fn vector(&self) -> &[f32] {
+            if self.as_native_type_ref().embedding_vector.is_null() {
+                return &[];
+            }
             unsafe {
                 std::slice::from_raw_parts(
                     self.as_native_type().embedding_vector as *const f32,
@@ -626,9 +632,34 @@ mod v6_2 {
         }
     }
 
+    /// Copies `vector` into memory allocated with `g_malloc`, which is owned and freed
+    /// by the [`EventMsgMeta`](super::EventMsgMeta) holding it.
+    pub(super) unsafe fn duplicate_embedding(
+        vector: &[f32],
+    ) -> nvidia_deepstream_sys::NvDsEmbedding {
+        if vector.is_empty() {
+            return std::mem::zeroed();
+        }
+        let p = nvidia_deepstream_sys::g_malloc(std::mem::size_of_val(vector) as _) as *mut f32;
+        std::ptr::copy_nonoverlapping(vector.as_ptr(), p, vector.len());
+        nvidia_deepstream_sys::NvDsEmbedding {
+            embedding_vector: p as _,
+            embedding_length: vector.len() as _,
+        }
+    }
+
     crate::wrapper_impl_ref_type!(Joint, nvidia_deepstream_sys::NvDsJoint);
 
     impl Joint {
+        pub fn new(x: f32, y: f32, z: f32, confidence: f32) -> Joint {
+            Joint(nvidia_deepstream_sys::NvDsJoint {
+                x,
+                y,
+                z,
+                confidence,
+            })
+        }
+
         pub fn x(&self) -> f32 {
             self.as_native_type().x
         }
@@ -646,14 +677,18 @@ mod v6_2 {
     crate::wrapper_impl_ref_type!(Joints, nvidia_deepstream_sys::NvDsJoints);
 
     impl Joints {
-        // TODO: check the safety of this impl
-        pub fn joints(&self) -> impl Iterator<Item = &Joint> {
-            let nvds_joints = unsafe {
-                std::slice::from_raw_parts(
-                    self.as_native_type_ref().joints as *const _,
-                    self.len().try_into().unwrap(),
-                )
-            };
+        pub fn joints(&self) -> impl ExactSizeIterator<Item = &Joint> {
+            let nvds_joints: &[nvidia_deepstream_sys::NvDsJoint] =
+                if self.as_native_type_ref().joints.is_null() || self.len() <= 0 {
+                    &[]
+                } else {
+                    unsafe {
+                        std::slice::from_raw_parts(
+                            self.as_native_type_ref().joints as *const _,
+                            self.len() as _,
+                        )
+                    }
+                };
             nvds_joints.iter().map(Joint::from_native_type_ref)
         }
 
@@ -661,11 +696,42 @@ mod v6_2 {
         pub fn len(&self) -> i32 {
             self.as_native_type().num_joints
         }
+
+        /// 0 for 2D, 1 for 2.5D and 2 for 3D poses.
+        pub fn pose_type(&self) -> i32 {
+            self.as_native_type().pose_type
+        }
+    }
+
+    /// Copies `joints` into memory allocated with `g_malloc`, which is owned and freed
+    /// by the [`EventMsgMeta`](super::EventMsgMeta) holding it.
+    pub(super) unsafe fn duplicate_joints<'a>(
+        pose_type: i32,
+        joints: impl ExactSizeIterator<Item = &'a Joint>,
+    ) -> nvidia_deepstream_sys::NvDsJoints {
+        let len = joints.len();
+        if len == 0 {
+            return nvidia_deepstream_sys::NvDsJoints {
+                pose_type,
+                ..std::mem::zeroed()
+            };
+        }
+        let p = nvidia_deepstream_sys::g_malloc(
+            (len * std::mem::size_of::<nvidia_deepstream_sys::NvDsJoint>()) as _,
+        ) as *mut nvidia_deepstream_sys::NvDsJoint;
+        for (i, joint) in joints.enumerate() {
+            p.add(i).write(joint.as_native_type());
+        }
+        nvidia_deepstream_sys::NvDsJoints {
+            joints: p,
+            num_joints: len as _,
+            pose_type,
+        }
     }
 }
 
 #[cfg(feature = "v6_4")]
-use v6_4::*;
+pub use v6_4::*;
 #[cfg(feature = "v6_4")]
 mod v6_4 {
     use crate::WrapperExt;
@@ -673,24 +739,65 @@ mod v6_4 {
     crate::wrapper_impl_ref_type!(ConvexHull, nvidia_deepstream_sys::NvDsConvexHull);
 
     impl ConvexHull {
+        /// Number of vertices.
         #[doc(alias = "numFilled")]
         pub fn num_filled(&self) -> u32 {
             self.as_native_type().numFilled
         }
 
+        /// Flattened `x, y` image coordinates, two per vertex.
         pub fn points(&self) -> &[i32] {
+            if self.as_native_type_ref().points.is_null() {
+                return &[];
+            }
             unsafe {
                 std::slice::from_raw_parts(
                     self.as_native_type_ref().points as *const i32,
-                    self.num_filled().try_into().unwrap(),
+                    self.num_filled() as usize * 2,
                 )
             }
         }
+
+        pub fn vertices(&self) -> impl ExactSizeIterator<Item = [i32; 2]> + '_ {
+            self.points().chunks_exact(2).map(|p| [p[0], p[1]])
+        }
+    }
+
+    /// Copies `vertices` into memory allocated with `g_malloc`, which is owned and
+    /// freed by the [`EventMsgMeta`](super::EventMsgMeta) holding it.
+    pub(super) unsafe fn duplicate_convex_hull(
+        vertices: impl ExactSizeIterator<Item = [i32; 2]>,
+    ) -> nvidia_deepstream_sys::NvDsConvexHull {
+        let len = vertices.len();
+        if len == 0 {
+            return std::mem::zeroed();
+        }
+        let p = nvidia_deepstream_sys::g_malloc((len * std::mem::size_of::<[i32; 2]>()) as _)
+            as *mut i32;
+        for (i, [x, y]) in vertices.enumerate() {
+            p.add(2 * i).write(x);
+            p.add(2 * i + 1).write(y);
+        }
+        nvidia_deepstream_sys::NvDsConvexHull {
+            points: p as _,
+            numPoints: len as _,
+            numFilled: len as _,
+        }
     }
 
     crate::wrapper_impl_ref_type!(Tracking3d, nvidia_deepstream_sys::NvDs3DTracking);
 
     impl Tracking3d {
+        /// The convex hull is set with
+        /// [`EventMsgMetaBuilder::convex_hull`](super::EventMsgMetaBuilder::convex_hull).
+        pub fn new(visibility: f32, pt_img_feet: [f32; 2], pt_world_feet: [f32; 2]) -> Tracking3d {
+            let mut tracking = Tracking3d::default();
+            tracking.0.visibility = visibility;
+            tracking.0.ptImgFeet = pt_img_feet;
+            tracking.0.ptWorldFeet = pt_world_feet;
+            tracking
+        }
+
         #[doc(alias = "convexHull")]
         pub fn convex_hull(&self) -> ConvexHull {
             ConvexHull::from_native_type(self.as_native_type().convexHull)
@@ -714,9 +821,67 @@ mod v6_4 {
 
 crate::wrapper_impl_ref_type!(EventMsgMetaBase, nvidia_deepstream_sys::NvDsEventMsgMeta);
 
+/// Object types which can be stored in `extMsg`, with the `objType` nvmsgconv
+/// expects for them.
+pub trait EventMsgObject: Clone {
+    const OBJ_TYPE: ObjectType;
+}
+
+impl EventMsgObject for VehicleObject {
+    const OBJ_TYPE: ObjectType = ObjectType::Vehicle;
+}
+
+impl EventMsgObject for PersonObject {
+    const OBJ_TYPE: ObjectType = ObjectType::Person;
+}
+
+impl EventMsgObject for FaceObject {
+    const OBJ_TYPE: ObjectType = ObjectType::Face;
+}
+
+#[repr(transparent)]
 pub struct EventMsgMeta<T: Clone>(EventMsgMetaBase, core::marker::PhantomData<T>);
 
+impl EventMsgMeta<VehicleObject> {
+    pub fn vehicle(builder: EventMsgMetaBuilder, object: VehicleObject) -> Box<Self> {
+        Self::with_object(builder, object)
+    }
+}
+
+impl EventMsgMeta<PersonObject> {
+    pub fn person(builder: EventMsgMetaBuilder, object: PersonObject) -> Box<Self> {
+        Self::with_object(builder, object)
+    }
+}
+
+impl EventMsgMeta<FaceObject> {
+    pub fn face(builder: EventMsgMetaBuilder, object: FaceObject) -> Box<Self> {
+        Self::with_object(builder, object)
+    }
+}
+
+impl<T: EventMsgObject> EventMsgMeta<T> {
+    pub fn with_object(builder: EventMsgMetaBuilder, object: T) -> Box<Self> {
+        builder
+            .obj_type(T::OBJ_TYPE)
+            .build_with_ext_msg(Box::new(object))
+    }
+
+    /// Returns `extMsg` if `objType` matches `T`.
+    pub fn object(&self) -> Option<&T> {
+        if self.obj_type() == T::OBJ_TYPE {
+            unsafe { self.ext_msg() }
+        } else {
+            None
+        }
+    }
+}
+
 impl<T: Clone> EventMsgMeta<T> {
+    pub(crate) fn base(&self) -> &EventMsgMetaBase {
+        &self.0
+    }
+
     pub fn type_(&self) -> EventType {
         unsafe { std::mem::transmute(self.0.as_native_type_ref().type_) }
     }
@@ -793,6 +958,28 @@ impl<T: Clone> EventMsgMeta<T> {
         unsafe { GStr::from_ptr(self.0.as_native_type_ref().videoPath) }
     }
 
+    #[cfg(feature = "v6_2")]
+    pub fn pose(&self) -> &Joints {
+        Joints::from_native_type_ref(&self.0.as_native_type_ref().pose)
+    }
+
+    #[cfg(feature = "v6_2")]
+    pub fn embedding(&self) -> &Embedding {
+        Embedding::from_native_type_ref(&self.0.as_native_type_ref().embedding)
+    }
+
+    #[cfg(feature = "v6_4")]
+    #[doc(alias = "has3DTracking")]
+    pub fn has_3d_tracking(&self) -> bool {
+        self.0.as_native_type_ref().has3DTracking
+    }
+
+    #[cfg(feature = "v6_4")]
+    #[doc(alias = "singleView3DTracking")]
+    pub fn single_view_3d_tracking(&self) -> &Tracking3d {
+        Tracking3d::from_native_type_ref(&self.0.as_native_type_ref().singleView3DTracking)
+    }
+
     pub unsafe fn ext_msg(&self) -> Option<&T> {
         if self.0.as_native_type_ref().extMsgSize as usize == std::mem::size_of::<T>() {
             NonNull::new(self.0.as_native_type_ref().extMsg as *mut T).map(|p| p.as_ref())
@@ -801,38 +988,47 @@ impl<T: Clone> EventMsgMeta<T> {
         }
     }
 
+    /// Attaches the event to `frame_meta` as `NVDS_EVENT_MSG_META` user meta, which
+    /// is what nvmsgconv looks for.
+    pub fn attach_to_frame<'b, BM: BatchMetaExt>(
+        self: Box<Self>,
+        batch_meta: &'b BM,
+        frame_meta: &FrameMeta,
+    ) -> Option<&'b UserMeta> {
+        UserMeta::new(batch_meta, MetaType::Base(BaseMetaType::EventMsgMeta), self).map(
+            |user_meta| {
+                frame_meta.add_user_meta(user_meta);
+                user_meta
+            },
+        )
+    }
+
+    /// Copy function of the user meta holding this event, `data` is the `NvDsUserMeta`.
+    #[deprecated(note = "set by `UserMeta::new`, e.g. through `attach_to_frame`")]
     pub extern "C" fn base_meta_copy_func(
         data: nvidia_deepstream_sys::gpointer,
-        _: nvidia_deepstream_sys::gpointer,
+        user_data: nvidia_deepstream_sys::gpointer,
     ) -> nvidia_deepstream_sys::gpointer {
-        unsafe {
-            let event_msg_meta = data as *mut EventMsgMeta<T>;
-            if event_msg_meta == std::ptr::null_mut() {
-                return std::ptr::null_mut();
-            }
-            let boxed = Box::new((*event_msg_meta).clone());
-            Box::into_raw(boxed) as _
-        }
+        UserMeta::base_meta_copy_func::<Self>(data, user_data)
     }
 
+    /// Release function of the user meta holding this event, `data` is the `NvDsUserMeta`.
+    #[deprecated(note = "set by `UserMeta::new`, e.g. through `attach_to_frame`")]
     pub extern "C" fn base_meta_release_func(
         data: nvidia_deepstream_sys::gpointer,
-        _: nvidia_deepstream_sys::gpointer,
+        user_data: nvidia_deepstream_sys::gpointer,
     ) {
-        unsafe {
-            let event_msg_meta = data as *mut EventMsgMeta<T>;
-            if event_msg_meta == std::ptr::null_mut() {
-                return;
-            }
-            let boxed = Box::from_raw(event_msg_meta);
-            drop(boxed);
-        }
+        UserMeta::base_meta_release_func::<Self>(data, user_data)
     }
 }
 
 impl<T: Clone> Clone for EventMsgMeta<T> {
     fn clone(&self) -> Self {
         unsafe {
+            let (ext_msg, ext_msg_size) = match self.ext_msg() {
+                Some(x) => (Box::into_raw(Box::new(x.clone())), std::mem::size_of::<T>()),
+                None => (std::ptr::null_mut(), 0),
+            };
             EventMsgMeta::<T>(
                 EventMsgMetaBase::from_native_type(nvidia_deepstream_sys::NvDsEventMsgMeta {
                     type_: self.0.as_native_type_ref().type_,
@@ -849,23 +1045,26 @@ impl<T: Clone> Clone for EventMsgMeta<T> {
                     frameId: self.0.as_native_type_ref().frameId,
                     confidence: self.0.as_native_type_ref().confidence,
                     trackingId: self.0.as_native_type_ref().trackingId,
-                    ts: nvidia_deepstream_sys::strdup(self.0.as_native_type_ref().ts),
-                    objectId: nvidia_deepstream_sys::strdup(self.0.as_native_type_ref().objectId),
-                    sensorStr: nvidia_deepstream_sys::strdup(self.0.as_native_type_ref().sensorStr),
-                    otherAttrs: nvidia_deepstream_sys::strdup(
-                        self.0.as_native_type_ref().otherAttrs,
-                    ),
-                    videoPath: nvidia_deepstream_sys::strdup(self.0.as_native_type_ref().videoPath),
-                    extMsg: std::ptr::null_mut(), // Box::into_raw(x),
-                    extMsgSize: self.0.as_native_type_ref().extMsgSize,
+                    ts: duplicate_glib_string(self.0.as_native_type_ref().ts),
+                    objectId: duplicate_glib_string(self.0.as_native_type_ref().objectId),
+                    sensorStr: duplicate_glib_string(self.0.as_native_type_ref().sensorStr),
+                    otherAttrs: duplicate_glib_string(self.0.as_native_type_ref().otherAttrs),
+                    videoPath: duplicate_glib_string(self.0.as_native_type_ref().videoPath),
+                    extMsg: ext_msg as _,
+                    extMsgSize: ext_msg_size as _,
                     #[cfg(feature = "v6_2")]
-                    pose: self.0.as_native_type_ref().pose,
+                    pose: duplicate_joints(self.pose().pose_type(), self.pose().joints()),
                     #[cfg(feature = "v6_2")]
-                    embedding: self.0.as_native_type_ref().embedding,
+                    embedding: duplicate_embedding(self.embedding().vector()),
                     #[cfg(feature = "v6_4")]
                     has3DTracking: self.0.as_native_type_ref().has3DTracking,
                     #[cfg(feature = "v6_4")]
-                    singleView3DTracking: self.0.as_native_type_ref().singleView3DTracking,
+                    singleView3DTracking: nvidia_deepstream_sys::NvDs3DTracking {
+                        convexHull: duplicate_convex_hull(
+                            self.single_view_3d_tracking().convex_hull().vertices(),
+                        ),
+                        ..self.0.as_native_type_ref().singleView3DTracking
+                    },
                 }),
                 core::marker::PhantomData,
             )
@@ -876,13 +1075,27 @@ impl<T: Clone> Clone for EventMsgMeta<T> {
 impl<T: Clone> Drop for EventMsgMeta<T> {
     fn drop(&mut self) {
         unsafe {
-            let x = Box::from_raw(self.0.as_native_type_ref().extMsg as *mut T);
-            nvidia_deepstream_sys::g_free(self.0.as_native_type_ref().ts as _);
-            nvidia_deepstream_sys::g_free(self.0.as_native_type_ref().objectId as _);
-            nvidia_deepstream_sys::g_free(self.0.as_native_type_ref().sensorStr as _);
-            nvidia_deepstream_sys::g_free(self.0.as_native_type_ref().otherAttrs as _);
-            nvidia_deepstream_sys::g_free(self.0.as_native_type_ref().videoPath as _);
-            drop(x);
+            let ext_msg = self.0.as_native_type_ref().extMsg as *mut T;
+            if ext_msg != std::ptr::null_mut() {
+                drop(Box::from_raw(ext_msg));
+            }
+            glib_free(self.0.as_native_type_ref().ts);
+            glib_free(self.0.as_native_type_ref().objectId);
+            glib_free(self.0.as_native_type_ref().sensorStr);
+            glib_free(self.0.as_native_type_ref().otherAttrs);
+            glib_free(self.0.as_native_type_ref().videoPath);
+            #[cfg(feature = "v6_2")]
+            glib_free(self.0.as_native_type_ref().pose.joints);
+            #[cfg(feature = "v6_2")]
+            glib_free(self.0.as_native_type_ref().embedding.embedding_vector);
+            #[cfg(feature = "v6_4")]
+            glib_free(
+                self.0
+                    .as_native_type_ref()
+                    .singleView3DTracking
+                    .convexHull
+                    .points,
+            );
         }
     }
 }
@@ -910,11 +1123,17 @@ pub struct EventMsgMetaBuilder<'a> {
     #[cfg(feature = "v6_2")]
     pose: Option<Joints>,
     #[cfg(feature = "v6_2")]
+    pose_joints: Option<(i32, Vec<Joint>)>,
+    #[cfg(feature = "v6_2")]
     embedding: Option<Embedding>,
+    #[cfg(feature = "v6_2")]
+    embedding_vector: Option<&'a [f32]>,
     #[cfg(feature = "v6_4")]
     has_3d_tracking: bool,
     #[cfg(feature = "v6_4")]
     single_view_3d_tracking: Option<Tracking3d>,
+    #[cfg(feature = "v6_4")]
+    convex_hull: Option<&'a [[i32; 2]]>,
 }
 
 impl<'a> EventMsgMetaBuilder<'a> {
@@ -942,11 +1161,17 @@ impl<'a> EventMsgMetaBuilder<'a> {
             #[cfg(feature = "v6_2")]
             pose: None,
             #[cfg(feature = "v6_2")]
+            pose_joints: None,
+            #[cfg(feature = "v6_2")]
             embedding: None,
+            #[cfg(feature = "v6_2")]
+            embedding_vector: None,
             #[cfg(feature = "v6_4")]
             has_3d_tracking: false,
             #[cfg(feature = "v6_4")]
             single_view_3d_tracking: None,
+            #[cfg(feature = "v6_4")]
+            convex_hull: None,
         }
     }
 
@@ -1045,30 +1270,60 @@ impl<'a> EventMsgMetaBuilder<'a> {
         self
     }
 
+    /// The joints are copied.
     #[cfg(feature = "v6_2")]
     pub fn pose(mut self, value: Joints) -> Self {
         self.pose = Some(value);
         self
     }
 
+    /// The joints are copied, takes precedence over [`EventMsgMetaBuilder::pose`].
+    #[cfg(feature = "v6_2")]
+    pub fn pose_joints(mut self, pose_type: i32, joints: &[Joint]) -> Self {
+        let joints = joints
+            .iter()
+            .map(|j| Joint::new(j.x(), j.y(), j.z(), j.confidence()))
+            .collect();
+        self.pose_joints = Some((pose_type, joints));
+        self
+    }
+
+    /// The vector is copied.
     #[cfg(feature = "v6_2")]
     pub fn embedding(mut self, value: Embedding) -> Self {
         self.embedding = Some(value);
         self
     }
 
+    /// The vector is copied, takes precedence over [`EventMsgMetaBuilder::embedding`].
+    #[cfg(feature = "v6_2")]
+    pub fn embedding_vector(mut self, value: &'a [f32]) -> Self {
+        self.embedding_vector = Some(value);
+        self
+    }
+
     #[cfg(feature = "v6_4")]
     pub fn has_3d_tracking(mut self, value: bool) -> Self {
         self.has_3d_tracking = value;
         self
     }
 
+    /// The convex hull is copied.
     #[cfg(feature = "v6_4")]
     pub fn single_view_3d_tracking(mut self, value: Tracking3d) -> Self {
         self.single_view_3d_tracking = Some(value);
         self
     }
 
+    /// Image coordinates of the convex hull of the object, copied into
+    /// `singleView3DTracking` and taking precedence over the hull of
+    /// [`EventMsgMetaBuilder::single_view_3d_tracking`].
+    #[cfg(feature = "v6_4")]
+    pub fn convex_hull(mut self, value: &'a [[i32; 2]]) -> Self {
+        self.convex_hull = Some(value);
+        self
+    }
+
     pub fn build(self) -> Box<EventMsgMeta<()>> {
         self.internal_build(None)
     }
@@ -1106,16 +1361,39 @@ impl<'a> EventMsgMetaBuilder<'a> {
                 extMsg: ext_msg as _,
                 extMsgSize: ext_msg_size as _,
                 #[cfg(feature = "v6_2")]
-                pose: self.pose.unwrap_or_default().as_native_type(),
+                pose: unsafe {
+                    match (self.pose_joints.as_ref(), self.pose.as_ref()) {
+                        (Some((pose_type, joints)), _) => {
+                            duplicate_joints(*pose_type, joints.iter())
+                        }
+                        (None, Some(pose)) => duplicate_joints(pose.pose_type(), pose.joints()),
+                        (None, None) => duplicate_joints(0, [].iter()),
+                    }
+                },
                 #[cfg(feature = "v6_2")]
-                embedding: self.embedding.unwrap_or_default().as_native_type(),
+                embedding: unsafe {
+                    duplicate_embedding(
+                        self.embedding_vector
+                            .or(self.embedding.as_ref().map(|e| e.vector()))
+                            .unwrap_or_default(),
+                    )
+                },
                 #[cfg(feature = "v6_4")]
                 has3DTracking: self.has_3d_tracking,
                 #[cfg(feature = "v6_4")]
-                singleView3DTracking: self
-                    .single_view_3d_tracking
-                    .unwrap_or_default()
-                    .as_native_type(),
+                singleView3DTracking: {
+                    let tracking = self.single_view_3d_tracking.unwrap_or_default();
+                    let convex_hull = unsafe {
+                        match self.convex_hull {
+                            Some(hull) => duplicate_convex_hull(hull.iter().copied()),
+                            None => duplicate_convex_hull(tracking.convex_hull().vertices()),
+                        }
+                    };
+                    nvidia_deepstream_sys::NvDs3DTracking {
+                        convexHull: convex_hull,
+                        ..tracking.as_native_type()
+                    }
+                },
             }),
             core::marker::PhantomData,
         ))
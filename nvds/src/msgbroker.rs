@@ -0,0 +1,513 @@
+//! Protocol adapters with the same operations as the `nvds_msgapi_*` API loaded by
+//! nvmsgbroker.
+//!
+//! Adapters implement [`ProtocolAdapter`] and can be used directly from Rust, or be
+//! exported from a `cdylib` with [`export_msgapi_adapter!`](crate::export_msgapi_adapter)
+//! and loaded with the `proto-lib` property of nvmsgbroker.
+
+pub mod file;
+pub mod memory;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Error {
+    Err = 1,
+    UnknownTopic = 2,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Err => write!(f, "message broker error"),
+            Error::UnknownTopic => write!(f, "unknown topic"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConnectionEvent {
+    ServiceDown = 0,
+    Disconnect = 1,
+    Success = 2,
+}
+
+pub type ConnectCallback = Box<dyn FnMut(ConnectionEvent) + Send>;
+pub type SendCallback = Box<dyn FnOnce(Result<(), Error>) + Send>;
+/// Called with the topic and the payload of every received message.
+pub type SubscribeCallback = Box<dyn FnMut(Result<(&str, &[u8]), Error>) + Send>;
+
+pub trait ProtocolAdapter: Sized + Send {
+    const PROTOCOL_NAME: &'static str;
+    const VERSION: &'static str = "4.0";
+
+    /// `connection_str` is the `conn-str` of nvmsgbroker, `config_path` its `config`.
+    fn connect(
+        connection_str: Option<&str>,
+        config_path: Option<&str>,
+        on_event: Option<ConnectCallback>,
+    ) -> Result<Self, Error>;
+
+    fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), Error>;
+
+    /// Sends without blocking; `done` may be called from [`ProtocolAdapter::do_work`].
+    fn send_async(&mut self, topic: &str, payload: &[u8], done: SendCallback) -> Result<(), Error> {
+        done(self.send(topic, payload));
+        Ok(())
+    }
+
+    fn subscribe(&mut self, _topics: &[&str], _callback: SubscribeCallback) -> Result<(), Error> {
+        Err(Error::Err)
+    }
+
+    /// Called periodically by nvmsgbroker to let the adapter make progress.
+    fn do_work(&mut self) {}
+
+    fn disconnect(self) -> Result<(), Error>;
+
+    /// Identifies connections which can be shared, `None` disables sharing.
+    fn connection_signature(_connection_str: &str, _config_path: Option<&str>) -> Option<String> {
+        None
+    }
+}
+
+/// C ABI used by [`export_msgapi_adapter!`](crate::export_msgapi_adapter).
+///
+/// The handle is a boxed `Mutex<A>` locked by every call, so the callbacks given to
+/// the adapter must not call back into the API. Panics are reported as errors.
+#[allow(clippy::missing_safety_doc)]
+pub mod ffi {
+    use super::*;
+    use std::panic::AssertUnwindSafe;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
+
+    pub type NvDsMsgApiHandle = *mut c_void;
+    pub type NvDsMsgApiErrorType = c_int;
+    pub const NVDS_MSGAPI_OK: NvDsMsgApiErrorType = 0;
+
+    pub type ConnectCb = Option<unsafe extern "C" fn(NvDsMsgApiHandle, ConnectionEvent)>;
+    pub type SendCb = Option<unsafe extern "C" fn(*mut c_void, NvDsMsgApiErrorType)>;
+    pub type SubscribeCb = Option<
+        unsafe extern "C" fn(NvDsMsgApiErrorType, *mut c_void, c_int, *mut c_char, *mut c_void),
+    >;
+
+    struct UserPtr(*mut c_void);
+
+    unsafe impl Send for UserPtr {}
+
+    fn status(r: Result<(), Error>) -> NvDsMsgApiErrorType {
+        match r {
+            Ok(()) => NVDS_MSGAPI_OK,
+            Err(e) => e as _,
+        }
+    }
+
+    fn catch_status(f: impl FnOnce() -> NvDsMsgApiErrorType) -> NvDsMsgApiErrorType {
+        std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(Error::Err as _)
+    }
+
+    unsafe fn lock<'a, A>(h: NvDsMsgApiHandle) -> Option<MutexGuard<'a, A>> {
+        (h as *const Mutex<A>)
+            .as_ref()
+            .map(|m| m.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+        if s.is_null() {
+            None
+        } else {
+            CStr::from_ptr(s).to_str().ok()
+        }
+    }
+
+    pub unsafe fn connect<A: ProtocolAdapter>(
+        connection_str: *const c_char,
+        connect_cb: ConnectCb,
+        config_path: *const c_char,
+    ) -> NvDsMsgApiHandle {
+        let handle = Arc::new(AtomicPtr::<c_void>::new(std::ptr::null_mut()));
+        let on_event = connect_cb.map(|cb| {
+            let handle = handle.clone();
+            Box::new(move |event| cb(handle.load(Ordering::Acquire), event)) as ConnectCallback
+        });
+        let connect = || A::connect(to_str(connection_str), to_str(config_path), on_event);
+        match std::panic::catch_unwind(AssertUnwindSafe(connect)) {
+            Ok(Ok(adapter)) => {
+                let p = Box::into_raw(Box::new(Mutex::new(adapter))) as NvDsMsgApiHandle;
+                handle.store(p, Ordering::Release);
+                p
+            }
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    pub unsafe fn send<A: ProtocolAdapter>(
+        h: NvDsMsgApiHandle,
+        topic: *const c_char,
+        payload: *const u8,
+        len: usize,
+    ) -> NvDsMsgApiErrorType {
+        catch_status(|| {
+            let (Some(mut adapter), Some(topic)) = (lock::<A>(h), to_str(topic)) else {
+                return Error::Err as _;
+            };
+            status(adapter.send(topic, payload_slice(payload, len)))
+        })
+    }
+
+    pub unsafe fn send_async<A: ProtocolAdapter>(
+        h: NvDsMsgApiHandle,
+        topic: *const c_char,
+        payload: *const u8,
+        len: usize,
+        send_cb: SendCb,
+        user_ptr: *mut c_void,
+    ) -> NvDsMsgApiErrorType {
+        let user_ptr = UserPtr(user_ptr);
+        catch_status(move || {
+            let (Some(mut adapter), Some(topic)) = (lock::<A>(h), to_str(topic)) else {
+                return Error::Err as _;
+            };
+            let done = Box::new(move |r| {
+                if let Some(cb) = send_cb {
+                    let user_ptr = user_ptr;
+                    cb(user_ptr.0, status(r));
+                }
+            });
+            status(adapter.send_async(topic, payload_slice(payload, len), done))
+        })
+    }
+
+    pub unsafe fn subscribe<A: ProtocolAdapter>(
+        h: NvDsMsgApiHandle,
+        topics: *mut *mut c_char,
+        num_topics: c_int,
+        cb: SubscribeCb,
+        user_ctx: *mut c_void,
+    ) -> NvDsMsgApiErrorType {
+        let user_ctx = UserPtr(user_ctx);
+        catch_status(move || {
+            let (Some(mut adapter), Some(cb)) = (lock::<A>(h), cb) else {
+                return Error::Err as _;
+            };
+            let topics = if topics.is_null() || num_topics <= 0 {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(topics, num_topics as _)
+                    .iter()
+                    .filter_map(|t| to_str(*t))
+                    .collect()
+            };
+            let callback = Box::new(move |r: Result<(&str, &[u8]), Error>| {
+                let user_ctx = &user_ctx;
+                match r {
+                    Ok((topic, payload)) => {
+                        let topic = std::ffi::CString::new(topic).unwrap_or_default();
+                        cb(
+                            NVDS_MSGAPI_OK,
+                            payload.as_ptr() as _,
+                            payload.len() as _,
+                            topic.as_ptr() as _,
+                            user_ctx.0,
+                        )
+                    }
+                    Err(e) => cb(
+                        e as _,
+                        std::ptr::null_mut(),
+                        0,
+                        std::ptr::null_mut(),
+                        user_ctx.0,
+                    ),
+                }
+            });
+            status(adapter.subscribe(&topics, callback))
+        })
+    }
+
+    pub unsafe fn do_work<A: ProtocolAdapter>(h: NvDsMsgApiHandle) {
+        let _ = std::panic::catch_unwind(|| {
+            if let Some(mut adapter) = lock::<A>(h) {
+                adapter.do_work();
+            }
+        });
+    }
+
+    pub unsafe fn disconnect<A: ProtocolAdapter>(h: NvDsMsgApiHandle) -> NvDsMsgApiErrorType {
+        if h.is_null() {
+            return Error::Err as _;
+        }
+        let adapter = Box::from_raw(h as *mut Mutex<A>);
+        catch_status(move || {
+            let adapter = adapter.into_inner().unwrap_or_else(|e| e.into_inner());
+            status(adapter.disconnect())
+        })
+    }
+
+    pub unsafe fn connection_signature<A: ProtocolAdapter>(
+        connection_str: *const c_char,
+        config_path: *const c_char,
+        output: *mut c_char,
+        max_len: c_int,
+    ) -> NvDsMsgApiErrorType {
+        if output.is_null() || max_len <= 0 {
+            return Error::Err as _;
+        }
+        catch_status(|| {
+            let signature = to_str(connection_str)
+                .and_then(|c| A::connection_signature(c, to_str(config_path)))
+                .unwrap_or_default();
+            let len = std::cmp::min(signature.len(), max_len as usize - 1);
+            std::ptr::copy_nonoverlapping(signature.as_ptr() as *const c_char, output, len);
+            *output.add(len) = 0;
+            NVDS_MSGAPI_OK
+        })
+    }
+
+    unsafe fn payload_slice<'a>(payload: *const u8, len: usize) -> &'a [u8] {
+        if payload.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(payload, len)
+        }
+    }
+}
+
+/// Exports a [`ProtocolAdapter`] as the `nvds_msgapi_*` symbols of a `cdylib`.
+///
+/// ```ignore
+/// nvidia_deepstream::export_msgapi_adapter!(MyHttpAdapter);
+/// ```
+#[macro_export]
+macro_rules! export_msgapi_adapter {
+    ($A:ty) => {
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_connect(
+            connection_str: *mut std::ffi::c_char,
+            connect_cb: $crate::msgbroker::ffi::ConnectCb,
+            config_path: *mut std::ffi::c_char,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiHandle {
+            $crate::msgbroker::ffi::connect::<$A>(connection_str, connect_cb, config_path)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_send(
+            h: $crate::msgbroker::ffi::NvDsMsgApiHandle,
+            topic: *mut std::ffi::c_char,
+            payload: *const u8,
+            len: usize,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiErrorType {
+            $crate::msgbroker::ffi::send::<$A>(h, topic, payload, len)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_send_async(
+            h: $crate::msgbroker::ffi::NvDsMsgApiHandle,
+            topic: *mut std::ffi::c_char,
+            payload: *const u8,
+            len: usize,
+            send_cb: $crate::msgbroker::ffi::SendCb,
+            user_ptr: *mut std::ffi::c_void,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiErrorType {
+            $crate::msgbroker::ffi::send_async::<$A>(h, topic, payload, len, send_cb, user_ptr)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_subscribe(
+            h: $crate::msgbroker::ffi::NvDsMsgApiHandle,
+            topics: *mut *mut std::ffi::c_char,
+            num_topics: std::ffi::c_int,
+            cb: $crate::msgbroker::ffi::SubscribeCb,
+            user_ctx: *mut std::ffi::c_void,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiErrorType {
+            $crate::msgbroker::ffi::subscribe::<$A>(h, topics, num_topics, cb, user_ctx)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_do_work(h: $crate::msgbroker::ffi::NvDsMsgApiHandle) {
+            $crate::msgbroker::ffi::do_work::<$A>(h)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_disconnect(
+            h: $crate::msgbroker::ffi::NvDsMsgApiHandle,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiErrorType {
+            $crate::msgbroker::ffi::disconnect::<$A>(h)
+        }
+
+        #[no_mangle]
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe extern "C" fn nvds_msgapi_connection_signature(
+            connection_str: *mut std::ffi::c_char,
+            config_path: *mut std::ffi::c_char,
+            output: *mut std::ffi::c_char,
+            max_len: std::ffi::c_int,
+        ) -> $crate::msgbroker::ffi::NvDsMsgApiErrorType {
+            $crate::msgbroker::ffi::connection_signature::<$A>(
+                connection_str,
+                config_path,
+                output,
+                max_len,
+            )
+        }
+
+        #[no_mangle]
+        pub extern "C" fn nvds_msgapi_getversion() -> *mut std::ffi::c_char {
+            static VERSION: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+            VERSION
+                .get_or_init(|| {
+                    std::ffi::CString::new(<$A as $crate::msgbroker::ProtocolAdapter>::VERSION)
+                        .unwrap_or_default()
+                })
+                .as_ptr() as _
+        }
+
+        #[no_mangle]
+        pub extern "C" fn nvds_msgapi_get_protocol_name() -> *mut std::ffi::c_char {
+            static NAME: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+            NAME.get_or_init(|| {
+                std::ffi::CString::new(<$A as $crate::msgbroker::ProtocolAdapter>::PROTOCOL_NAME)
+                    .unwrap_or_default()
+            })
+            .as_ptr() as _
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::memory::MemoryAdapter;
+    use super::*;
+    use std::ffi::CString;
+    use std::sync::Mutex;
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    struct PanicAdapter;
+
+    impl ProtocolAdapter for PanicAdapter {
+        const PROTOCOL_NAME: &'static str = "PANIC";
+
+        fn connect(
+            connection_str: Option<&str>,
+            _config_path: Option<&str>,
+            _on_event: Option<ConnectCallback>,
+        ) -> Result<Self, Error> {
+            match connection_str {
+                Some("panic") => panic!("connect"),
+                _ => Ok(PanicAdapter),
+            }
+        }
+
+        fn send(&mut self, _topic: &str, _payload: &[u8]) -> Result<(), Error> {
+            panic!("send")
+        }
+
+        fn do_work(&mut self) {
+            panic!("do_work")
+        }
+
+        fn disconnect(self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    static RECEIVED: Mutex<Vec<(String, Vec<u8>)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn on_message(
+        status: ffi::NvDsMsgApiErrorType,
+        payload: *mut c_void,
+        len: c_int,
+        topic: *mut c_char,
+        _user_ctx: *mut c_void,
+    ) {
+        assert_eq!(status, ffi::NVDS_MSGAPI_OK);
+        let topic = CStr::from_ptr(topic).to_str().unwrap().to_string();
+        let payload = std::slice::from_raw_parts(payload as *const u8, len as _).to_vec();
+        RECEIVED.lock().unwrap().push((topic, payload));
+    }
+
+    #[test]
+    fn memory_adapter() {
+        unsafe {
+            let h = ffi::connect::<MemoryAdapter>(c("ffi-bus").as_ptr(), None, std::ptr::null());
+            assert!(!h.is_null());
+            let topic = c("a");
+            let mut topics = [topic.as_ptr() as *mut c_char];
+            let status = ffi::subscribe::<MemoryAdapter>(
+                h,
+                topics.as_mut_ptr(),
+                1,
+                Some(on_message),
+                std::ptr::null_mut(),
+            );
+            assert_eq!(status, ffi::NVDS_MSGAPI_OK);
+            let payload = b"hello";
+            let status =
+                ffi::send::<MemoryAdapter>(h, c("a").as_ptr(), payload.as_ptr(), payload.len());
+            assert_eq!(status, ffi::NVDS_MSGAPI_OK);
+            ffi::do_work::<MemoryAdapter>(h);
+            assert_eq!(
+                *RECEIVED.lock().unwrap(),
+                [("a".to_string(), b"hello".to_vec())]
+            );
+            assert_eq!(ffi::disconnect::<MemoryAdapter>(h), ffi::NVDS_MSGAPI_OK);
+
+            let mut signature = [0 as c_char; 4];
+            let status = ffi::connection_signature::<MemoryAdapter>(
+                c("ffi-bus").as_ptr(),
+                std::ptr::null(),
+                signature.as_mut_ptr(),
+                signature.len() as _,
+            );
+            assert_eq!(status, ffi::NVDS_MSGAPI_OK);
+            assert_eq!(CStr::from_ptr(signature.as_ptr()).to_str(), Ok("ffi"));
+        }
+    }
+
+    #[test]
+    fn panics_are_errors() {
+        unsafe {
+            let h = ffi::connect::<PanicAdapter>(c("panic").as_ptr(), None, std::ptr::null());
+            assert!(h.is_null());
+            assert_eq!(
+                ffi::send::<PanicAdapter>(h, c("a").as_ptr(), std::ptr::null(), 0),
+                Error::Err as ffi::NvDsMsgApiErrorType
+            );
+
+            let h = ffi::connect::<PanicAdapter>(std::ptr::null(), None, std::ptr::null());
+            assert!(!h.is_null());
+            assert_eq!(
+                ffi::send::<PanicAdapter>(h, c("a").as_ptr(), std::ptr::null(), 0),
+                Error::Err as ffi::NvDsMsgApiErrorType
+            );
+            ffi::do_work::<PanicAdapter>(h);
+            assert_eq!(
+                ffi::send_async::<PanicAdapter>(
+                    h,
+                    c("a").as_ptr(),
+                    std::ptr::null(),
+                    0,
+                    None,
+                    std::ptr::null_mut()
+                ),
+                Error::Err as ffi::NvDsMsgApiErrorType
+            );
+            assert_eq!(ffi::disconnect::<PanicAdapter>(h), ffi::NVDS_MSGAPI_OK);
+        }
+    }
+}
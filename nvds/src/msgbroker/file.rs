@@ -0,0 +1,127 @@
+use super::{ConnectCallback, ConnectionEvent, Error, ProtocolAdapter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+
+/// Appends every message to the file given as connection string, one JSON object
+/// `{"topic": ..., "payload": ...}` per line.
+///
+/// JSON payloads are embedded as is, other payloads as (lossy) UTF-8 strings.
+pub struct FileAdapter {
+    writer: BufWriter<File>,
+    on_event: Option<ConnectCallback>,
+}
+
+impl FileAdapter {
+    pub fn format_line(topic: &str, payload: &[u8]) -> String {
+        let payload = match serde_json::from_slice::<serde_json::Value>(payload) {
+            Ok(value) => match std::str::from_utf8(payload) {
+                Ok(s) if !s.contains('\n') => s.trim().to_string(),
+                _ => value.to_string(),
+            },
+            Err(_) => serde_json::Value::from(String::from_utf8_lossy(payload)).to_string(),
+        };
+        format!(
+            "{{\"topic\":{},\"payload\":{}}}",
+            serde_json::Value::from(topic),
+            payload
+        )
+    }
+}
+
+impl ProtocolAdapter for FileAdapter {
+    const PROTOCOL_NAME: &'static str = "FILE";
+
+    fn connect(
+        connection_str: Option<&str>,
+        _config_path: Option<&str>,
+        mut on_event: Option<ConnectCallback>,
+    ) -> Result<Self, Error> {
+        let path = connection_str.filter(|s| !s.is_empty()).ok_or(Error::Err)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|_| Error::Err)?;
+        if let Some(cb) = on_event.as_mut() {
+            cb(ConnectionEvent::Success);
+        }
+        Ok(FileAdapter {
+            writer: BufWriter::new(file),
+            on_event,
+        })
+    }
+
+    fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), Error> {
+        let line = Self::format_line(topic, payload);
+        let r = writeln!(self.writer, "{}", line).and_then(|_| self.writer.flush());
+        if r.is_err() {
+            if let Some(cb) = self.on_event.as_mut() {
+                cb(ConnectionEvent::ServiceDown);
+            }
+            return Err(Error::Err);
+        }
+        Ok(())
+    }
+
+    fn disconnect(mut self) -> Result<(), Error> {
+        let r = self.writer.flush().map_err(|_| Error::Err);
+        if let Some(cb) = self.on_event.as_mut() {
+            cb(ConnectionEvent::Disconnect);
+        }
+        r
+    }
+
+    fn connection_signature(connection_str: &str, _config_path: Option<&str>) -> Option<String> {
+        Some(connection_str.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_line() {
+        assert_eq!(
+            FileAdapter::format_line("t", br#" {"a": 1} "#),
+            r#"{"topic":"t","payload":{"a": 1}}"#
+        );
+        assert_eq!(
+            FileAdapter::format_line("t", b"{\n\"a\": 1\n}"),
+            r#"{"topic":"t","payload":{"a":1}}"#
+        );
+        assert_eq!(
+            FileAdapter::format_line("t\"", b"x|y"),
+            r#"{"topic":"t\"","payload":"x|y"}"#
+        );
+    }
+
+    #[test]
+    fn append_lines() {
+        let path =
+            std::env::temp_dir().join(format!("nvds-msgbroker-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(FileAdapter::connect(None, None, None).is_err());
+
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let on_event = {
+            let events = events.clone();
+            Box::new(move |e| events.lock().unwrap().push(e)) as ConnectCallback
+        };
+        let mut adapter = FileAdapter::connect(path.to_str(), None, Some(on_event)).unwrap();
+        adapter.send("a", br#"{"n":1}"#).unwrap();
+        adapter.send("b", b"text").unwrap();
+        adapter.disconnect().unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            content,
+            "{\"topic\":\"a\",\"payload\":{\"n\":1}}\n{\"topic\":\"b\",\"payload\":\"text\"}\n"
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            [ConnectionEvent::Success, ConnectionEvent::Disconnect]
+        );
+    }
+}
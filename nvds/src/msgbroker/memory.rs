@@ -0,0 +1,183 @@
+use super::{ConnectCallback, ConnectionEvent, Error, ProtocolAdapter, SubscribeCallback};
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Message {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Bus {
+    subscribers: Vec<(Vec<String>, Sender<Message>)>,
+}
+
+fn bus(name: &str) -> Arc<Mutex<Bus>> {
+    static BUSES: OnceLock<Mutex<HashMap<String, Arc<Mutex<Bus>>>>> = OnceLock::new();
+    BUSES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Receives the messages published on the bus `name` to one of `topics`, or to
+/// any topic when `topics` is empty.
+pub fn subscribe(name: &str, topics: &[&str]) -> Receiver<Message> {
+    let (sender, receiver) = channel();
+    bus(name)
+        .lock()
+        .unwrap()
+        .subscribers
+        .push((topics.iter().map(|t| t.to_string()).collect(), sender));
+    receiver
+}
+
+/// Returns the number of subscribers which received the message.
+pub fn publish(name: &str, topic: &str, payload: &[u8]) -> usize {
+    let bus = bus(name);
+    let mut bus = bus.lock().unwrap();
+    let mut delivered = 0;
+    bus.subscribers.retain(|(topics, sender)| {
+        if !topics.is_empty() && !topics.iter().any(|t| t == topic) {
+            return true;
+        }
+        let sent = sender
+            .send(Message {
+                topic: topic.to_string(),
+                payload: payload.to_vec(),
+            })
+            .is_ok();
+        if sent {
+            delivered += 1;
+        }
+        sent
+    });
+    delivered
+}
+
+/// In-process adapter, the connection string names the bus.
+///
+/// Subscription callbacks are called from [`ProtocolAdapter::do_work`].
+pub struct MemoryAdapter {
+    name: String,
+    subscriptions: Vec<(Receiver<Message>, SubscribeCallback)>,
+    on_event: Option<ConnectCallback>,
+}
+
+impl MemoryAdapter {
+    pub fn bus_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ProtocolAdapter for MemoryAdapter {
+    const PROTOCOL_NAME: &'static str = "MEMORY";
+
+    fn connect(
+        connection_str: Option<&str>,
+        _config_path: Option<&str>,
+        mut on_event: Option<ConnectCallback>,
+    ) -> Result<Self, Error> {
+        if let Some(cb) = on_event.as_mut() {
+            cb(ConnectionEvent::Success);
+        }
+        Ok(MemoryAdapter {
+            name: connection_str.unwrap_or_default().to_string(),
+            subscriptions: Vec::new(),
+            on_event,
+        })
+    }
+
+    fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), Error> {
+        publish(&self.name, topic, payload);
+        Ok(())
+    }
+
+    fn subscribe(&mut self, topics: &[&str], callback: SubscribeCallback) -> Result<(), Error> {
+        self.subscriptions
+            .push((subscribe(&self.name, topics), callback));
+        Ok(())
+    }
+
+    fn do_work(&mut self) {
+        for (receiver, callback) in self.subscriptions.iter_mut() {
+            while let Ok(message) = receiver.try_recv() {
+                callback(Ok((&message.topic, &message.payload)));
+            }
+        }
+    }
+
+    fn disconnect(mut self) -> Result<(), Error> {
+        if let Some(cb) = self.on_event.as_mut() {
+            cb(ConnectionEvent::Disconnect);
+        }
+        Ok(())
+    }
+
+    fn connection_signature(connection_str: &str, _config_path: Option<&str>) -> Option<String> {
+        Some(connection_str.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish_and_subscribe() {
+        let all = subscribe("memory-publish", &[]);
+        let a = subscribe("memory-publish", &["a"]);
+        assert_eq!(publish("memory-publish", "a", b"1"), 2);
+        assert_eq!(publish("memory-publish", "b", b"2"), 1);
+        assert_eq!(publish("memory-other", "a", b"3"), 0);
+
+        let message = |topic: &str, payload: &[u8]| Message {
+            topic: topic.to_string(),
+            payload: payload.to_vec(),
+        };
+        assert_eq!(
+            all.try_iter().collect::<Vec<_>>(),
+            [message("a", b"1"), message("b", b"2")]
+        );
+        assert_eq!(a.try_iter().collect::<Vec<_>>(), [message("a", b"1")]);
+
+        drop(a);
+        assert_eq!(publish("memory-publish", "a", b"4"), 1);
+    }
+
+    #[test]
+    fn adapter() {
+        let mut sender = MemoryAdapter::connect(Some("memory-adapter"), None, None).unwrap();
+        let mut receiver = MemoryAdapter::connect(Some("memory-adapter"), None, None).unwrap();
+        assert_eq!(receiver.bus_name(), "memory-adapter");
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let callback = {
+            let received = received.clone();
+            Box::new(move |r: Result<(&str, &[u8]), Error>| {
+                let (topic, payload) = r.unwrap();
+                received
+                    .lock()
+                    .unwrap()
+                    .push((topic.to_string(), payload.to_vec()));
+            }) as SubscribeCallback
+        };
+        receiver.subscribe(&["a"], callback).unwrap();
+        sender.send("a", b"1").unwrap();
+        sender.send("b", b"2").unwrap();
+        assert!(received.lock().unwrap().is_empty());
+
+        receiver.do_work();
+        assert_eq!(
+            *received.lock().unwrap(),
+            [("a".to_string(), b"1".to_vec())]
+        );
+        sender.disconnect().unwrap();
+        receiver.disconnect().unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+use super::{ConnectCallback, ConnectionEvent, Error, ProtocolAdapter, SubscribeCallback};
+use rumqttc::{Client, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+enum Notification {
+    Connection(ConnectionEvent),
+    Message(String, Vec<u8>),
+}
+
+/// MQTT adapter, the connection string is `host;port` as for the DeepStream MQTT
+/// adapter.
+///
+/// The optional configuration file may set `username`, `password`, `client-id` and
+/// `keep-alive` in its `[message-broker]` section. Connection events and received
+/// messages are delivered from [`ProtocolAdapter::do_work`].
+pub struct MqttAdapter {
+    client: Client,
+    notifications: Receiver<Notification>,
+    subscriptions: Vec<(Vec<String>, SubscribeCallback)>,
+    on_event: Option<ConnectCallback>,
+}
+
+fn parse_connection_str(connection_str: &str) -> Result<(String, u16), Error> {
+    let mut it = connection_str.split([';', ':']);
+    let host = it.next().filter(|h| !h.is_empty()).ok_or(Error::Err)?;
+    let port = match it.next() {
+        Some(port) => port.trim().parse().map_err(|_| Error::Err)?,
+        None => 1883,
+    };
+    Ok((host.trim().to_string(), port))
+}
+
+fn options(host: String, port: u16, config_path: Option<&str>) -> Result<MqttOptions, Error> {
+    let config = match config_path.filter(|p| !p.is_empty()) {
+        Some(path) => Some(ini::Ini::load_from_file(path).map_err(|_| Error::Err)?),
+        None => None,
+    };
+    let section = config
+        .as_ref()
+        .and_then(|c| c.section(Some("message-broker")));
+    let get = |key| section.and_then(|s| s.get(key));
+
+    let client_id = get("client-id")
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("nvds-{}", std::process::id()));
+    let mut options = MqttOptions::new(client_id, host, port);
+    if let Some(username) = get("username") {
+        options.set_credentials(username, get("password").unwrap_or_default());
+    }
+    if let Some(keep_alive) = get("keep-alive") {
+        let secs = keep_alive.parse().map_err(|_| Error::Err)?;
+        options.set_keep_alive(Duration::from_secs(secs));
+    }
+    Ok(options)
+}
+
+/// Matches an MQTT topic filter with `+` and `#` wildcards. As required by MQTT,
+/// filters starting with a wildcard do not match topics starting with `$`, e.g.
+/// `$SYS/broker/uptime`.
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic_levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (l, Some(t)) if l == t => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+/// Event of an error of the event loop: `Disconnect` when an established connection
+/// drops, `ServiceDown` while the broker stays unreachable.
+fn connection_lost(connected: &mut bool) -> ConnectionEvent {
+    if std::mem::replace(connected, false) {
+        ConnectionEvent::Disconnect
+    } else {
+        ConnectionEvent::ServiceDown
+    }
+}
+
+impl ProtocolAdapter for MqttAdapter {
+    const PROTOCOL_NAME: &'static str = "MQTT";
+
+    fn connect(
+        connection_str: Option<&str>,
+        config_path: Option<&str>,
+        on_event: Option<ConnectCallback>,
+    ) -> Result<Self, Error> {
+        let (host, port) = parse_connection_str(connection_str.ok_or(Error::Err)?)?;
+        let (client, mut connection) = Client::new(options(host, port, config_path)?, 64);
+
+        let (sender, notifications) = channel();
+        std::thread::spawn(move || {
+            let mut connected = false;
+            for notification in connection.iter() {
+                let n = match notification {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        connected = true;
+                        Notification::Connection(ConnectionEvent::Success)
+                    }
+                    Ok(Event::Incoming(Packet::Publish(p))) => {
+                        Notification::Message(p.topic, p.payload.to_vec())
+                    }
+                    Ok(Event::Incoming(Packet::Disconnect)) => {
+                        Notification::Connection(connection_lost(&mut connected))
+                    }
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => {
+                        let _ = sender.send(Notification::Connection(ConnectionEvent::Disconnect));
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(_) => {
+                        let event = connection_lost(&mut connected);
+                        if event == ConnectionEvent::ServiceDown {
+                            std::thread::sleep(Duration::from_secs(1));
+                        }
+                        Notification::Connection(event)
+                    }
+                };
+                if sender.send(n).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(MqttAdapter {
+            client,
+            notifications,
+            subscriptions: Vec::new(),
+            on_event,
+        })
+    }
+
+    fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), Error> {
+        self.client
+            .publish(topic, QoS::AtLeastOnce, false, payload)
+            .map_err(|_| Error::Err)
+    }
+
+    fn subscribe(&mut self, topics: &[&str], callback: SubscribeCallback) -> Result<(), Error> {
+        for topic in topics {
+            self.client
+                .subscribe(*topic, QoS::AtLeastOnce)
+                .map_err(|_| Error::Err)?;
+        }
+        self.subscriptions
+            .push((topics.iter().map(|t| t.to_string()).collect(), callback));
+        Ok(())
+    }
+
+    fn do_work(&mut self) {
+        while let Ok(n) = self.notifications.try_recv() {
+            match n {
+                Notification::Connection(event) => {
+                    if let Some(cb) = self.on_event.as_mut() {
+                        cb(event);
+                    }
+                }
+                Notification::Message(topic, payload) => {
+                    for (filters, callback) in self.subscriptions.iter_mut() {
+                        if filters.iter().any(|f| topic_matches(f, &topic)) {
+                            callback(Ok((&topic, &payload)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn disconnect(self) -> Result<(), Error> {
+        // The worker stops once the disconnection is sent or, when the broker is
+        // unreachable, on its next notification after the receiver is dropped.
+        self.client.disconnect().map_err(|_| Error::Err)
+    }
+
+    fn connection_signature(connection_str: &str, config_path: Option<&str>) -> Option<String> {
+        let (host, port) = parse_connection_str(connection_str).ok()?;
+        Some(format!(
+            "{};{};{}",
+            host,
+            port,
+            config_path.unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_filters() {
+        for (filter, topic, matches) in [
+            ("a/b", "a/b", true),
+            ("a/b", "a/c", false),
+            ("a/b", "a/b/c", false),
+            ("a/b/c", "a/b", false),
+            ("a/+", "a/b", true),
+            ("a/+", "a/", true),
+            ("a/+", "a", false),
+            ("a/+", "a/b/c", false),
+            ("+/b", "a/b", true),
+            ("+/+", "/b", true),
+            ("+", "/b", false),
+            ("a/#", "a", true),
+            ("a/#", "a/b/c", true),
+            ("a/#", "b/c", false),
+            ("#", "a/b", true),
+            ("#", "/", true),
+            ("#", "$SYS/broker/uptime", false),
+            ("+/broker/uptime", "$SYS/broker/uptime", false),
+            ("$SYS/#", "$SYS/broker/uptime", true),
+            ("$SYS/+/uptime", "$SYS/broker/uptime", true),
+        ] {
+            assert_eq!(
+                topic_matches(filter, topic),
+                matches,
+                "{} {}",
+                filter,
+                topic
+            );
+        }
+    }
+
+    #[test]
+    fn connection_strings() {
+        assert_eq!(
+            parse_connection_str("localhost;1884").unwrap(),
+            ("localhost".to_string(), 1884)
+        );
+        assert_eq!(
+            parse_connection_str("localhost:1884").unwrap(),
+            ("localhost".to_string(), 1884)
+        );
+        assert_eq!(
+            parse_connection_str("localhost").unwrap(),
+            ("localhost".to_string(), 1883)
+        );
+        assert_eq!(parse_connection_str(";1883"), Err(Error::Err));
+        assert_eq!(parse_connection_str("localhost;port"), Err(Error::Err));
+    }
+
+    #[test]
+    fn lost_connections() {
+        let mut connected = false;
+        assert_eq!(
+            connection_lost(&mut connected),
+            ConnectionEvent::ServiceDown
+        );
+        connected = true;
+        assert_eq!(connection_lost(&mut connected), ConnectionEvent::Disconnect);
+        assert_eq!(
+            connection_lost(&mut connected),
+            ConnectionEvent::ServiceDown
+        );
+    }
+}
@@ -0,0 +1,675 @@
+//! Rust implementation of the DeepStream message schema produced by nvmsgconv.
+//!
+//! [`Event`] is an owned equivalent of `NvDsEventMsgMeta` which can be created without
+//! DeepStream; [`json`] converts it from and to the full and minimal schema payloads.
+
+pub mod json;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+
+use crate::meta::schema::{
+    self, EventMsgMeta, EventMsgMetaBuilder, EventType, FaceObjectBuilder, ObjectType,
+    PersonObjectBuilder, VehicleObjectBuilder,
+};
+use crate::meta::{BatchMetaExt, FrameMeta, UserMeta};
+use crate::WrapperExt;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Ini(String),
+    Json(serde_json::Error),
+    #[cfg(feature = "protobuf")]
+    Protobuf(prost::DecodeError),
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Ini(e) => write!(f, "config error: {}", e),
+            Error::Json(e) => write!(f, "json error: {}", e),
+            #[cfg(feature = "protobuf")]
+            Error::Protobuf(e) => write!(f, "protobuf error: {}", e),
+            Error::InvalidValue { key, value } => {
+                write!(f, "invalid value `{}` for `{}`", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            #[cfg(feature = "protobuf")]
+            Error::Protobuf(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+const EVENT_TYPES: &[(EventType, &str)] = &[
+    (EventType::Entry, "entry"),
+    (EventType::Exit, "exit"),
+    (EventType::Moving, "moving"),
+    (EventType::Stopped, "stopped"),
+    (EventType::Empty, "empty"),
+    (EventType::Parked, "parked"),
+    (EventType::Reset, "reset"),
+    (EventType::Reserved, "reserved"),
+    (EventType::Custom, "custom"),
+];
+
+const OBJECT_TYPES: &[(ObjectType, &str)] = &[
+    (ObjectType::Vehicle, "Vehicle"),
+    (ObjectType::Person, "Person"),
+    (ObjectType::Face, "Face"),
+    (ObjectType::Bag, "Bag"),
+    (ObjectType::Bicycle, "Bicycle"),
+    (ObjectType::RoadSign, "RoadSign"),
+    (ObjectType::VehicleExt, "Vehicle"),
+    (ObjectType::PersonExt, "Person"),
+    (ObjectType::FaceExt, "Face"),
+    (ObjectType::Reserved, "Reserved"),
+    (ObjectType::Custom, "Custom"),
+    (ObjectType::Unknown, "Unknown"),
+];
+
+impl EventType {
+    /// Name used for `event.type` in the payloads.
+    pub fn as_schema_str(&self) -> &'static str {
+        EVENT_TYPES
+            .iter()
+            .find(|(t, _)| t == self)
+            .map(|(_, s)| *s)
+            .unwrap_or("unknown")
+    }
+
+    pub fn from_schema_str(s: &str) -> Option<EventType> {
+        EVENT_TYPES.iter().find(|(_, n)| *n == s).map(|(t, _)| *t)
+    }
+}
+
+impl ObjectType {
+    /// Name used for the object type in the minimal schema.
+    pub fn as_schema_str(&self) -> &'static str {
+        OBJECT_TYPES
+            .iter()
+            .find(|(t, _)| t == self)
+            .map(|(_, s)| *s)
+            .unwrap_or("Unknown")
+    }
+
+    pub fn from_schema_str(s: &str) -> Option<ObjectType> {
+        OBJECT_TYPES.iter().find(|(_, n)| *n == s).map(|(t, _)| *t)
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct BBox {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Location {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt: f64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Coordinate {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Vehicle {
+    pub type_: String,
+    pub make: String,
+    pub model: String,
+    pub color: String,
+    pub region: String,
+    pub license: String,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Person {
+    pub gender: String,
+    pub hair: String,
+    pub cap: String,
+    pub apparel: String,
+    pub age: u32,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Face {
+    pub gender: String,
+    pub hair: String,
+    pub cap: String,
+    pub glasses: String,
+    pub facial_hair: String,
+    pub name: String,
+    pub eye_color: String,
+    pub age: u32,
+}
+
+/// Content of `extMsg`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Attributes {
+    Vehicle(Vehicle),
+    Person(Person),
+    Face(Face),
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Joint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub confidence: f32,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Pose {
+    pub pose_type: i32,
+    pub joints: Vec<Joint>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Tracking3d {
+    pub visibility: f32,
+    pub img_feet: [f32; 2],
+    pub world_feet: [f32; 2],
+    pub convex_hull: Vec<[i32; 2]>,
+}
+
+/// Owned equivalent of `NvDsEventMsgMeta`.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Event {
+    pub type_: EventType,
+    pub obj_type: ObjectType,
+    pub bbox: BBox,
+    pub location: Location,
+    pub coordinate: Coordinate,
+    pub obj_class_id: i32,
+    pub sensor_id: i32,
+    pub module_id: i32,
+    pub place_id: i32,
+    pub component_id: i32,
+    pub frame_id: i32,
+    pub confidence: f64,
+    pub tracking_id: u64,
+    pub ts: String,
+    pub object_id: String,
+    pub sensor_str: String,
+    pub other_attrs: String,
+    pub video_path: String,
+    pub attributes: Option<Attributes>,
+    pub pose: Option<Pose>,
+    pub embedding: Option<Vec<f32>>,
+    pub tracking_3d: Option<Tracking3d>,
+}
+
+unsafe fn owned_string(p: *const nvidia_deepstream_sys::gchar) -> String {
+    if p.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(p).to_string_lossy().into_owned()
+    }
+}
+
+impl Event {
+    /// Reads `meta`, interpreting `extMsg` according to `objType` like nvmsgconv does.
+    pub fn from_event_msg_meta<T: Clone>(meta: &EventMsgMeta<T>) -> Event {
+        let n = meta.base().as_native_type_ref();
+        unsafe {
+            let ext_msg_size = n.extMsgSize as usize;
+            let attributes = if n.extMsg.is_null() {
+                None
+            } else {
+                match meta.obj_type() {
+                    ObjectType::Vehicle
+                        if ext_msg_size
+                            == std::mem::size_of::<nvidia_deepstream_sys::NvDsVehicleObject>() =>
+                    {
+                        let v = &*(n.extMsg as *const nvidia_deepstream_sys::NvDsVehicleObject);
+                        Some(Attributes::Vehicle(Vehicle {
+                            type_: owned_string(v.type_),
+                            make: owned_string(v.make),
+                            model: owned_string(v.model),
+                            color: owned_string(v.color),
+                            region: owned_string(v.region),
+                            license: owned_string(v.license),
+                        }))
+                    }
+                    ObjectType::Person
+                        if ext_msg_size
+                            == std::mem::size_of::<nvidia_deepstream_sys::NvDsPersonObject>() =>
+                    {
+                        let p = &*(n.extMsg as *const nvidia_deepstream_sys::NvDsPersonObject);
+                        Some(Attributes::Person(Person {
+                            gender: owned_string(p.gender),
+                            hair: owned_string(p.hair),
+                            cap: owned_string(p.cap),
+                            apparel: owned_string(p.apparel),
+                            age: p.age,
+                        }))
+                    }
+                    ObjectType::Face
+                        if ext_msg_size
+                            == std::mem::size_of::<nvidia_deepstream_sys::NvDsFaceObject>() =>
+                    {
+                        let f = &*(n.extMsg as *const nvidia_deepstream_sys::NvDsFaceObject);
+                        Some(Attributes::Face(Face {
+                            gender: owned_string(f.gender),
+                            hair: owned_string(f.hair),
+                            cap: owned_string(f.cap),
+                            glasses: owned_string(f.glasses),
+                            facial_hair: owned_string(f.facialhair),
+                            name: owned_string(f.name),
+                            eye_color: owned_string(f.eyecolor),
+                            age: f.age,
+                        }))
+                    }
+                    _ => None,
+                }
+            };
+
+            #[cfg(feature = "v6_2")]
+            let pose = if n.pose.joints.is_null() || n.pose.num_joints <= 0 {
+                None
+            } else {
+                Some(Pose {
+                    pose_type: n.pose.pose_type as _,
+                    joints: std::slice::from_raw_parts(n.pose.joints, n.pose.num_joints as _)
+                        .iter()
+                        .map(|j| Joint {
+                            x: j.x,
+                            y: j.y,
+                            z: j.z,
+                            confidence: j.confidence,
+                        })
+                        .collect(),
+                })
+            };
+            #[cfg(not(feature = "v6_2"))]
+            let pose = None;
+
+            #[cfg(feature = "v6_2")]
+            let embedding = if n.embedding.embedding_vector.is_null() {
+                None
+            } else {
+                Some(
+                    std::slice::from_raw_parts(
+                        n.embedding.embedding_vector as *const f32,
+                        n.embedding.embedding_length as _,
+                    )
+                    .to_vec(),
+                )
+            };
+            #[cfg(not(feature = "v6_2"))]
+            let embedding = None;
+
+            #[cfg(feature = "v6_4")]
+            let tracking_3d = if n.has3DTracking {
+                let t = &n.singleView3DTracking;
+                let points = if t.convexHull.points.is_null() {
+                    &[][..]
+                } else {
+                    std::slice::from_raw_parts(
+                        t.convexHull.points as *const i32,
+                        t.convexHull.numFilled as usize * 2,
+                    )
+                };
+                Some(Tracking3d {
+                    visibility: t.visibility,
+                    img_feet: t.ptImgFeet,
+                    world_feet: t.ptWorldFeet,
+                    convex_hull: points.chunks_exact(2).map(|p| [p[0], p[1]]).collect(),
+                })
+            } else {
+                None
+            };
+            #[cfg(not(feature = "v6_4"))]
+            let tracking_3d = None;
+
+            Event {
+                type_: meta.type_(),
+                obj_type: meta.obj_type(),
+                bbox: BBox {
+                    left: n.bbox.left,
+                    top: n.bbox.top,
+                    width: n.bbox.width,
+                    height: n.bbox.height,
+                },
+                location: Location {
+                    lat: n.location.lat,
+                    lon: n.location.lon,
+                    alt: n.location.alt,
+                },
+                coordinate: Coordinate {
+                    x: n.coordinate.x,
+                    y: n.coordinate.y,
+                    z: n.coordinate.z,
+                },
+                obj_class_id: n.objClassId,
+                sensor_id: n.sensorId,
+                module_id: n.moduleId,
+                place_id: n.placeId,
+                component_id: n.componentId,
+                frame_id: n.frameId,
+                confidence: n.confidence,
+                tracking_id: n.trackingId as _,
+                ts: owned_string(n.ts),
+                object_id: owned_string(n.objectId),
+                sensor_str: owned_string(n.sensorStr),
+                other_attrs: owned_string(n.otherAttrs),
+                video_path: owned_string(n.videoPath),
+                attributes,
+                pose,
+                embedding,
+                tracking_3d,
+            }
+        }
+    }
+
+    /// Builder with every field except `extMsg` set.
+    #[allow(clippy::let_and_return)]
+    pub fn event_msg_meta_builder(&self) -> EventMsgMetaBuilder {
+        let builder = EventMsgMetaBuilder::new()
+            .type_(self.type_)
+            .obj_type(self.obj_type)
+            .bbox(schema::Rect::new(
+                self.bbox.left,
+                self.bbox.top,
+                self.bbox.width,
+                self.bbox.height,
+            ))
+            .location(schema::GeoLocation::new(
+                self.location.lat,
+                self.location.lon,
+                self.location.alt,
+            ))
+            .coordinate(schema::Coordinate::new(
+                self.coordinate.x,
+                self.coordinate.y,
+                self.coordinate.z,
+            ))
+            .obj_class_id(self.obj_class_id)
+            .sensor_id(self.sensor_id)
+            .module_id(self.module_id)
+            .place_id(self.place_id)
+            .component_id(self.component_id)
+            .frame_id(self.frame_id)
+            .confidence(self.confidence)
+            .tracking_id(self.tracking_id)
+            .ts(&self.ts)
+            .object_id(&self.object_id)
+            .sensor_str(&self.sensor_str)
+            .other_attrs(&self.other_attrs)
+            .video_path(&self.video_path);
+        #[cfg(feature = "v6_2")]
+        let builder = match &self.pose {
+            Some(pose) => {
+                let joints: Vec<_> = pose
+                    .joints
+                    .iter()
+                    .map(|j| schema::Joint::new(j.x, j.y, j.z, j.confidence))
+                    .collect();
+                builder.pose_joints(pose.pose_type, &joints)
+            }
+            None => builder,
+        };
+        #[cfg(feature = "v6_2")]
+        let builder = match &self.embedding {
+            Some(embedding) => builder.embedding_vector(embedding),
+            None => builder,
+        };
+        #[cfg(feature = "v6_4")]
+        let builder = match &self.tracking_3d {
+            Some(t) => builder
+                .has_3d_tracking(true)
+                .single_view_3d_tracking(schema::Tracking3d::new(
+                    t.visibility,
+                    t.img_feet,
+                    t.world_feet,
+                ))
+                .convex_hull(&t.convex_hull),
+            None => builder,
+        };
+        builder
+    }
+
+    /// Attaches the event to `frame_meta` with the typed object in `extMsg`, see
+    /// [`EventMsgMeta::attach_to_frame`].
+    pub fn attach_to_frame<'b, BM: BatchMetaExt>(
+        &self,
+        batch_meta: &'b BM,
+        frame_meta: &FrameMeta,
+    ) -> Option<&'b UserMeta> {
+        let builder = self.event_msg_meta_builder();
+        match &self.attributes {
+            Some(Attributes::Vehicle(v)) => EventMsgMeta::vehicle(
+                builder,
+                VehicleObjectBuilder::new()
+                    .type_(&v.type_)
+                    .make(&v.make)
+                    .model(&v.model)
+                    .color(&v.color)
+                    .region(&v.region)
+                    .license(&v.license)
+                    .build(),
+            )
+            .attach_to_frame(batch_meta, frame_meta),
+            Some(Attributes::Person(p)) => EventMsgMeta::person(
+                builder,
+                PersonObjectBuilder::new()
+                    .gender(&p.gender)
+                    .hair(&p.hair)
+                    .cap(&p.cap)
+                    .apparel(&p.apparel)
+                    .age(p.age)
+                    .build(),
+            )
+            .attach_to_frame(batch_meta, frame_meta),
+            Some(Attributes::Face(f)) => EventMsgMeta::face(
+                builder,
+                FaceObjectBuilder::new()
+                    .gender(&f.gender)
+                    .hair(&f.hair)
+                    .cap(&f.cap)
+                    .glasses(&f.glasses)
+                    .facial_hair(&f.facial_hair)
+                    .name(&f.name)
+                    .eye_color(&f.eye_color)
+                    .age(f.age)
+                    .build(),
+            )
+            .attach_to_frame(batch_meta, frame_meta),
+            None => builder.build().attach_to_frame(batch_meta, frame_meta),
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Sensor {
+    pub id: String,
+    pub type_: String,
+    pub description: String,
+    pub location: Location,
+    pub coordinate: Coordinate,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Place {
+    pub id: String,
+    pub name: String,
+    pub type_: String,
+    pub location: Location,
+    pub coordinate: Coordinate,
+    pub sub_field1: String,
+    pub sub_field2: String,
+    pub sub_field3: String,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct AnalyticsModule {
+    pub id: String,
+    pub description: String,
+    pub source: String,
+    pub version: String,
+}
+
+/// Static description of sensors, places and analytics modules, indexed by the
+/// `sensorId`, `placeId` and `moduleId` of the events.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Config {
+    pub sensors: BTreeMap<i32, Sensor>,
+    pub places: BTreeMap<i32, Place>,
+    pub modules: BTreeMap<i32, AnalyticsModule>,
+}
+
+fn parse_triple(key: &str, value: &str) -> Result<(f64, f64, f64), Error> {
+    let invalid = || Error::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let v = value
+        .split(';')
+        .map(|s| s.trim().parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match v[..] {
+        [a, b, c] => Ok((a, b, c)),
+        _ => Err(invalid()),
+    }
+}
+
+impl Config {
+    /// Parses the key file given to nvmsgconv with `config`, e.g. `cfg_msgconv.txt`.
+    pub fn from_ini(s: &str) -> Result<Config, Error> {
+        let ini = ini::Ini::load_from_str(s).map_err(|e| Error::Ini(e.to_string()))?;
+        let mut r = Config::default();
+        for (section, props) in ini.iter() {
+            let Some(section) = section else { continue };
+            let get = |key: &str| props.get(key).unwrap_or_default().to_string();
+            if props.get("enable").map(|v| v.trim()) != Some("1") {
+                continue;
+            }
+            let location = |key: &str| -> Result<Location, Error> {
+                match props.get(key) {
+                    Some(v) => {
+                        let (lat, lon, alt) = parse_triple(key, v)?;
+                        Ok(Location { lat, lon, alt })
+                    }
+                    None => Ok(Location::default()),
+                }
+            };
+            let coordinate = |key: &str| -> Result<Coordinate, Error> {
+                match props.get(key) {
+                    Some(v) => {
+                        let (x, y, z) = parse_triple(key, v)?;
+                        Ok(Coordinate { x, y, z })
+                    }
+                    None => Ok(Coordinate::default()),
+                }
+            };
+            let index = |prefix: &str| -> Result<Option<i32>, Error> {
+                match section.strip_prefix(prefix) {
+                    Some(n) => n.parse().map(Some).map_err(|_| Error::InvalidValue {
+                        key: "section".to_string(),
+                        value: section.to_string(),
+                    }),
+                    None => Ok(None),
+                }
+            };
+
+            if let Some(n) = index("sensor")? {
+                r.sensors.insert(
+                    n,
+                    Sensor {
+                        id: get("id"),
+                        type_: get("type"),
+                        description: get("description"),
+                        location: location("location")?,
+                        coordinate: coordinate("coordinate")?,
+                    },
+                );
+            } else if let Some(n) = index("place")? {
+                r.places.insert(
+                    n,
+                    Place {
+                        id: get("id"),
+                        name: get("name"),
+                        type_: get("type"),
+                        location: location("location")?,
+                        coordinate: coordinate("coordinate")?,
+                        sub_field1: get("place-sub-field1"),
+                        sub_field2: get("place-sub-field2"),
+                        sub_field3: get("place-sub-field3"),
+                    },
+                );
+            } else if let Some(n) = index("analytics")? {
+                r.modules.insert(
+                    n,
+                    AnalyticsModule {
+                        id: get("id"),
+                        description: get("description"),
+                        source: get("source"),
+                        version: get("version"),
+                    },
+                );
+            }
+        }
+        Ok(r)
+    }
+
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Config, Error> {
+        Self::from_ini(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn sensor_index(&self, id: &str) -> Option<i32> {
+        self.sensors
+            .iter()
+            .find(|(_, s)| s.id == id)
+            .map(|(n, _)| *n)
+    }
+
+    pub fn place_index(&self, id: &str) -> Option<i32> {
+        self.places
+            .iter()
+            .find(|(_, p)| p.id == id)
+            .map(|(n, _)| *n)
+    }
+
+    pub fn module_index(&self, id: &str) -> Option<i32> {
+        self.modules
+            .iter()
+            .find(|(_, m)| m.id == id)
+            .map(|(n, _)| *n)
+    }
+}
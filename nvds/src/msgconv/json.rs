@@ -0,0 +1,1156 @@
+//! Full (`PAYLOAD_DEEPSTREAM`) and minimal (`PAYLOAD_DEEPSTREAM_MINIMAL`) schema payloads.
+
+use super::{Attributes, Config, Error, Event, Face, Joint, Person, Pose, Tracking3d, Vehicle};
+use crate::meta::schema::{EventType, ObjectType};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocationJson {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt: f64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoordinateJson {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaceSubJson {
+    pub id: String,
+    pub name: String,
+    pub level: String,
+    pub coordinate: CoordinateJson,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaceJson {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub location: LocationJson,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrance: Option<PlaceSubJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aisle: Option<PlaceSubJson>,
+    #[serde(rename = "parkingSpot", skip_serializing_if = "Option::is_none")]
+    pub parking_spot: Option<PlaceSubJson>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensorJson {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub description: String,
+    pub location: LocationJson,
+    pub coordinate: CoordinateJson,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsModuleJson {
+    pub id: String,
+    pub description: String,
+    pub source: String,
+    pub version: String,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VehicleJson {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub make: String,
+    pub model: String,
+    pub color: String,
+    #[serde(rename = "licenseState")]
+    pub license_state: String,
+    pub license: String,
+    pub confidence: f64,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonJson {
+    pub age: u32,
+    pub gender: String,
+    pub hair: String,
+    pub cap: String,
+    pub apparel: String,
+    pub confidence: f64,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaceJson {
+    pub age: u32,
+    pub gender: String,
+    pub hair: String,
+    pub cap: String,
+    pub glasses: String,
+    pub facialhair: String,
+    pub name: String,
+    pub eyecolor: String,
+    pub confidence: f64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BBoxJson {
+    pub topleftx: i32,
+    pub toplefty: i32,
+    pub bottomrightx: i32,
+    pub bottomrighty: i32,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JointJson {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub confidence: f32,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoseJson {
+    pub pose_type: i32,
+    pub num_joints: usize,
+    pub joints: Vec<JointJson>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingJson {
+    pub embedding_length: usize,
+    pub embedding_vector: Vec<f32>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tracking3dJson {
+    pub visibility: f32,
+    #[serde(rename = "footLocation2D")]
+    pub foot_location_2d: [f32; 2],
+    #[serde(rename = "footLocation3D")]
+    pub foot_location_3d: [f32; 2],
+    #[serde(rename = "convexHull")]
+    pub convex_hull: Vec<[i32; 2]>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectJson {
+    pub id: String,
+    pub speed: f64,
+    pub direction: f64,
+    pub orientation: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<VehicleJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub person: Option<PersonJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub face: Option<FaceJson>,
+    pub bbox: BBoxJson,
+    pub location: LocationJson,
+    pub coordinate: CoordinateJson,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pose: Option<PoseJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<EmbeddingJson>,
+    #[serde(
+        rename = "singleView3DTracking",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub single_view_3d_tracking: Option<Tracking3dJson>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventJson {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+/// Message of the full DeepStream schema, one per event.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FullMessage {
+    pub messageid: String,
+    pub mdsversion: String,
+    #[serde(rename = "@timestamp")]
+    pub timestamp: String,
+    pub place: PlaceJson,
+    pub sensor: SensorJson,
+    #[serde(rename = "analyticsModule")]
+    pub analytics_module: AnalyticsModuleJson,
+    pub object: ObjectJson,
+    pub event: EventJson,
+    #[serde(rename = "videoPath")]
+    pub video_path: String,
+}
+
+/// Message of the minimal DeepStream schema, grouping the events of a frame.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinimalMessage {
+    pub version: String,
+    pub id: String,
+    #[serde(rename = "@timestamp")]
+    pub timestamp: String,
+    #[serde(rename = "sensorId")]
+    pub sensor_id: String,
+    pub objects: Vec<String>,
+}
+
+pub const MDS_VERSION: &str = "1.0";
+pub const MINIMAL_VERSION: &str = "4.0";
+
+/// Formats like `std::ostream` with the default precision (`%g`), which nvmsgconv
+/// uses for the fields of the minimal schema.
+pub fn format_g(v: f64) -> String {
+    if v == 0.0 {
+        return "0".to_string();
+    }
+    if !v.is_finite() {
+        return if v.is_nan() {
+            "nan".to_string()
+        } else if v > 0.0 {
+            "inf".to_string()
+        } else {
+            "-inf".to_string()
+        };
+    }
+    let e = format!("{:.5e}", v);
+    let (mantissa, exp) = e.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    if !(-4..6).contains(&exp) {
+        let mantissa = if mantissa.contains('.') {
+            mantissa.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            mantissa
+        };
+        format!(
+            "{}e{}{:02}",
+            mantissa,
+            if exp < 0 { '-' } else { '+' },
+            exp.abs()
+        )
+    } else {
+        let s = format!("{:.*}", (5 - exp) as usize, v);
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            s
+        }
+    }
+}
+
+pub(crate) const MINIMAL_SEPARATOR: &str = "|";
+pub(crate) const MINIMAL_ATTRIBUTES_MARKER: &str = "#";
+
+fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Creates payloads from [`Event`]s, filling the sensor, place and analytics module
+/// objects from the [`Config`].
+pub struct Generator {
+    config: Config,
+    id_generator: fn() -> String,
+}
+
+impl Generator {
+    pub fn new(config: Config) -> Generator {
+        Generator {
+            config,
+            id_generator: new_uuid,
+        }
+    }
+
+    /// Replaces the random UUIDs used for `messageid` and `event.id`.
+    pub fn with_id_generator(mut self, id_generator: fn() -> String) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn full_message(&self, event: &Event) -> FullMessage {
+        let place = self
+            .config
+            .places
+            .get(&event.place_id)
+            .map(|p| {
+                let sub = PlaceSubJson {
+                    id: p.sub_field1.clone(),
+                    name: p.sub_field2.clone(),
+                    level: p.sub_field3.clone(),
+                    coordinate: CoordinateJson {
+                        x: event.coordinate.x,
+                        y: event.coordinate.y,
+                        z: event.coordinate.z,
+                    },
+                };
+                let (entrance, aisle, parking_spot) = match event.type_ {
+                    EventType::Entry | EventType::Exit => (Some(sub), None, None),
+                    EventType::Parked | EventType::Empty => (None, None, Some(sub)),
+                    _ => (None, Some(sub), None),
+                };
+                PlaceJson {
+                    id: p.id.clone(),
+                    name: p.name.clone(),
+                    type_: p.type_.clone(),
+                    location: LocationJson {
+                        lat: p.location.lat,
+                        lon: p.location.lon,
+                        alt: p.location.alt,
+                    },
+                    entrance,
+                    aisle,
+                    parking_spot,
+                }
+            })
+            .unwrap_or_default();
+
+        let sensor = match self.config.sensors.get(&event.sensor_id) {
+            Some(s) => SensorJson {
+                id: s.id.clone(),
+                type_: s.type_.clone(),
+                description: s.description.clone(),
+                location: LocationJson {
+                    lat: s.location.lat,
+                    lon: s.location.lon,
+                    alt: s.location.alt,
+                },
+                coordinate: CoordinateJson {
+                    x: s.coordinate.x,
+                    y: s.coordinate.y,
+                    z: s.coordinate.z,
+                },
+            },
+            None => SensorJson {
+                id: event.sensor_str.clone(),
+                ..Default::default()
+            },
+        };
+
+        let analytics_module = self
+            .config
+            .modules
+            .get(&event.module_id)
+            .map(|m| AnalyticsModuleJson {
+                id: m.id.clone(),
+                description: m.description.clone(),
+                source: m.source.clone(),
+                version: m.version.clone(),
+            })
+            .unwrap_or_default();
+
+        let mut object = ObjectJson {
+            id: event.object_id.clone(),
+            bbox: BBoxJson {
+                topleftx: event.bbox.left as _,
+                toplefty: event.bbox.top as _,
+                bottomrightx: (event.bbox.left + event.bbox.width) as _,
+                bottomrighty: (event.bbox.top + event.bbox.height) as _,
+            },
+            location: LocationJson {
+                lat: event.location.lat,
+                lon: event.location.lon,
+                alt: event.location.alt,
+            },
+            coordinate: CoordinateJson {
+                x: event.coordinate.x,
+                y: event.coordinate.y,
+                z: event.coordinate.z,
+            },
+            pose: event.pose.as_ref().map(|p| PoseJson {
+                pose_type: p.pose_type,
+                num_joints: p.joints.len(),
+                joints: p
+                    .joints
+                    .iter()
+                    .map(|j| JointJson {
+                        x: j.x,
+                        y: j.y,
+                        z: j.z,
+                        confidence: j.confidence,
+                    })
+                    .collect(),
+            }),
+            embedding: event.embedding.as_ref().map(|e| EmbeddingJson {
+                embedding_length: e.len(),
+                embedding_vector: e.clone(),
+            }),
+            single_view_3d_tracking: event.tracking_3d.as_ref().map(|t| Tracking3dJson {
+                visibility: t.visibility,
+                foot_location_2d: t.img_feet,
+                foot_location_3d: t.world_feet,
+                convex_hull: t.convex_hull.clone(),
+            }),
+            ..Default::default()
+        };
+        match &event.attributes {
+            Some(Attributes::Vehicle(v)) => {
+                object.vehicle = Some(VehicleJson {
+                    type_: v.type_.clone(),
+                    make: v.make.clone(),
+                    model: v.model.clone(),
+                    color: v.color.clone(),
+                    license_state: v.region.clone(),
+                    license: v.license.clone(),
+                    confidence: event.confidence,
+                })
+            }
+            Some(Attributes::Person(p)) => {
+                object.person = Some(PersonJson {
+                    age: p.age,
+                    gender: p.gender.clone(),
+                    hair: p.hair.clone(),
+                    cap: p.cap.clone(),
+                    apparel: p.apparel.clone(),
+                    confidence: event.confidence,
+                })
+            }
+            Some(Attributes::Face(f)) => {
+                object.face = Some(FaceJson {
+                    age: f.age,
+                    gender: f.gender.clone(),
+                    hair: f.hair.clone(),
+                    cap: f.cap.clone(),
+                    glasses: f.glasses.clone(),
+                    facialhair: f.facial_hair.clone(),
+                    name: f.name.clone(),
+                    eyecolor: f.eye_color.clone(),
+                    confidence: event.confidence,
+                })
+            }
+            None => {}
+        }
+
+        FullMessage {
+            messageid: (self.id_generator)(),
+            mdsversion: MDS_VERSION.to_string(),
+            timestamp: event.ts.clone(),
+            place,
+            sensor,
+            analytics_module,
+            object,
+            event: EventJson {
+                id: (self.id_generator)(),
+                type_: event.type_.as_schema_str().to_string(),
+            },
+            video_path: event.video_path.clone(),
+        }
+    }
+
+    pub fn to_full_json(&self, event: &Event) -> Result<String, Error> {
+        Ok(serde_json::to_string(&self.full_message(event))?)
+    }
+
+    /// Object entry of the minimal schema:
+    /// `trackingId|left|top|right|bottom|type[|#|attributes...]`, the attributes being
+    /// in the order of `generate_deepstream_message_minimal` of nvmsgconv:
+    /// `type|make|model|color|license|region|confidence` of vehicles,
+    /// `gender|age|hair|cap|apparel|confidence` of persons and
+    /// `gender|age|hair|cap|glasses|facialhair|name|eyecolor|confidence` of faces.
+    pub fn minimal_object(event: &Event) -> String {
+        let mut fields = vec![
+            event.tracking_id.to_string(),
+            format_g(event.bbox.left as _),
+            format_g(event.bbox.top as _),
+            format_g((event.bbox.left + event.bbox.width) as _),
+            format_g((event.bbox.top + event.bbox.height) as _),
+        ];
+        match event.obj_type {
+            ObjectType::Custom | ObjectType::Unknown if !event.object_id.is_empty() => {
+                fields.push(event.object_id.clone())
+            }
+            t => fields.push(t.as_schema_str().to_string()),
+        }
+
+        let confidence = format_g(event.confidence);
+        let attributes = match &event.attributes {
+            Some(Attributes::Vehicle(v)) => Some(vec![
+                v.type_.clone(),
+                v.make.clone(),
+                v.model.clone(),
+                v.color.clone(),
+                v.license.clone(),
+                v.region.clone(),
+                confidence,
+            ]),
+            Some(Attributes::Person(p)) => Some(vec![
+                p.gender.clone(),
+                p.age.to_string(),
+                p.hair.clone(),
+                p.cap.clone(),
+                p.apparel.clone(),
+                confidence,
+            ]),
+            Some(Attributes::Face(f)) => Some(vec![
+                f.gender.clone(),
+                f.age.to_string(),
+                f.hair.clone(),
+                f.cap.clone(),
+                f.glasses.clone(),
+                f.facial_hair.clone(),
+                f.name.clone(),
+                f.eye_color.clone(),
+                confidence,
+            ]),
+            None => None,
+        };
+        if let Some(attributes) = attributes {
+            fields.push(MINIMAL_ATTRIBUTES_MARKER.to_string());
+            fields.extend(attributes);
+        }
+        if let Some(embedding) = &event.embedding {
+            fields.push(MINIMAL_ATTRIBUTES_MARKER.to_string());
+            fields.push("embedding".to_string());
+            fields.push(
+                embedding
+                    .iter()
+                    .map(|v| format_g(*v as _))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        if let Some(pose) = &event.pose {
+            fields.push(MINIMAL_ATTRIBUTES_MARKER.to_string());
+            fields.push("pose".to_string());
+            fields.push(pose.pose_type.to_string());
+            fields.push(
+                pose.joints
+                    .iter()
+                    .map(|j| {
+                        [j.x, j.y, j.z, j.confidence]
+                            .iter()
+                            .map(|v| format_g(*v as _))
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            );
+        }
+        fields.join(MINIMAL_SEPARATOR)
+    }
+
+    /// Minimal message for the events of one frame, `events` should share the frame
+    /// and sensor.
+    pub fn minimal_message(&self, events: &[Event]) -> MinimalMessage {
+        let first = events.first().cloned().unwrap_or_default();
+        MinimalMessage {
+            version: MINIMAL_VERSION.to_string(),
+            id: first.frame_id.to_string(),
+            timestamp: first.ts.clone(),
+            sensor_id: self
+                .config
+                .sensors
+                .get(&first.sensor_id)
+                .map(|s| s.id.clone())
+                .unwrap_or(first.sensor_str),
+            objects: events.iter().map(Self::minimal_object).collect(),
+        }
+    }
+
+    pub fn to_minimal_json(&self, events: &[Event]) -> Result<String, Error> {
+        Ok(serde_json::to_string(&self.minimal_message(events))?)
+    }
+}
+
+impl FullMessage {
+    pub fn from_json(s: &str) -> Result<FullMessage, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Converts the message back to an [`Event`]; sensor, place and analytics
+    /// module ids are looked up in `config`.
+    pub fn to_event(&self, config: &Config) -> Result<Event, Error> {
+        let type_ = EventType::from_schema_str(&self.event.type_).ok_or(Error::InvalidValue {
+            key: "event.type".to_string(),
+            value: self.event.type_.clone(),
+        })?;
+
+        let o = &self.object;
+        let (obj_type, attributes, confidence) = if let Some(v) = &o.vehicle {
+            (
+                ObjectType::Vehicle,
+                Some(Attributes::Vehicle(Vehicle {
+                    type_: v.type_.clone(),
+                    make: v.make.clone(),
+                    model: v.model.clone(),
+                    color: v.color.clone(),
+                    region: v.license_state.clone(),
+                    license: v.license.clone(),
+                })),
+                v.confidence,
+            )
+        } else if let Some(p) = &o.person {
+            (
+                ObjectType::Person,
+                Some(Attributes::Person(Person {
+                    gender: p.gender.clone(),
+                    hair: p.hair.clone(),
+                    cap: p.cap.clone(),
+                    apparel: p.apparel.clone(),
+                    age: p.age,
+                })),
+                p.confidence,
+            )
+        } else if let Some(f) = &o.face {
+            (
+                ObjectType::Face,
+                Some(Attributes::Face(Face {
+                    gender: f.gender.clone(),
+                    hair: f.hair.clone(),
+                    cap: f.cap.clone(),
+                    glasses: f.glasses.clone(),
+                    facial_hair: f.facialhair.clone(),
+                    name: f.name.clone(),
+                    eye_color: f.eyecolor.clone(),
+                    age: f.age,
+                })),
+                f.confidence,
+            )
+        } else {
+            (ObjectType::Unknown, None, 0.0)
+        };
+
+        Ok(Event {
+            type_,
+            obj_type,
+            bbox: super::BBox {
+                left: o.bbox.topleftx as _,
+                top: o.bbox.toplefty as _,
+                width: (o.bbox.bottomrightx - o.bbox.topleftx) as _,
+                height: (o.bbox.bottomrighty - o.bbox.toplefty) as _,
+            },
+            location: super::Location {
+                lat: o.location.lat,
+                lon: o.location.lon,
+                alt: o.location.alt,
+            },
+            coordinate: super::Coordinate {
+                x: o.coordinate.x,
+                y: o.coordinate.y,
+                z: o.coordinate.z,
+            },
+            sensor_id: config.sensor_index(&self.sensor.id).unwrap_or_default(),
+            module_id: config
+                .module_index(&self.analytics_module.id)
+                .unwrap_or_default(),
+            place_id: config.place_index(&self.place.id).unwrap_or_default(),
+            confidence,
+            ts: self.timestamp.clone(),
+            object_id: o.id.clone(),
+            sensor_str: self.sensor.id.clone(),
+            video_path: self.video_path.clone(),
+            attributes,
+            pose: o.pose.as_ref().map(|p| Pose {
+                pose_type: p.pose_type,
+                joints: p
+                    .joints
+                    .iter()
+                    .map(|j| Joint {
+                        x: j.x,
+                        y: j.y,
+                        z: j.z,
+                        confidence: j.confidence,
+                    })
+                    .collect(),
+            }),
+            embedding: o.embedding.as_ref().map(|e| e.embedding_vector.clone()),
+            tracking_3d: o.single_view_3d_tracking.as_ref().map(|t| Tracking3d {
+                visibility: t.visibility,
+                img_feet: t.foot_location_2d,
+                world_feet: t.foot_location_3d,
+                convex_hull: t.convex_hull.clone(),
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(key: &str, value: Option<&str>) -> Result<T, Error> {
+    let value = value.unwrap_or_default();
+    value.parse().map_err(|_| Error::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn parse_floats(key: &str, value: Option<&str>, separator: char) -> Result<Vec<f32>, Error> {
+    match value {
+        Some(v) if !v.is_empty() => v
+            .split(separator)
+            .map(|v| parse_field(key, Some(v)))
+            .collect(),
+        _ => Ok(Vec::new()),
+    }
+}
+
+impl MinimalMessage {
+    pub fn from_json(s: &str) -> Result<MinimalMessage, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Parses an object entry written by [`Generator::minimal_object`].
+    pub fn parse_object(object: &str) -> Result<Event, Error> {
+        let mut fields = object.split(MINIMAL_SEPARATOR);
+        let mut event = Event {
+            tracking_id: parse_field("trackingId", fields.next())?,
+            ..Default::default()
+        };
+        let left: f32 = parse_field("left", fields.next())?;
+        let top: f32 = parse_field("top", fields.next())?;
+        let right: f32 = parse_field("right", fields.next())?;
+        let bottom: f32 = parse_field("bottom", fields.next())?;
+        event.bbox = super::BBox {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+        };
+        let type_ = fields.next().unwrap_or_default();
+        match ObjectType::from_schema_str(type_) {
+            Some(t) => event.obj_type = t,
+            None => {
+                event.obj_type = ObjectType::Custom;
+                event.object_id = type_.to_string();
+            }
+        }
+
+        let rest = fields.collect::<Vec<_>>();
+        for group in rest.split(|f| *f == MINIMAL_ATTRIBUTES_MARKER).skip(1) {
+            let mut g = group.iter().copied();
+            match group.first().copied() {
+                Some("embedding") => {
+                    g.next();
+                    event.embedding = Some(parse_floats("embedding", g.next(), ',')?);
+                }
+                Some("pose") => {
+                    g.next();
+                    let pose_type = parse_field("pose_type", g.next())?;
+                    let joints = match g.next() {
+                        Some(j) if !j.is_empty() => j
+                            .split(';')
+                            .map(|j| match parse_floats("joint", Some(j), ',')?[..] {
+                                [x, y, z, confidence] => Ok(Joint {
+                                    x,
+                                    y,
+                                    z,
+                                    confidence,
+                                }),
+                                _ => Err(Error::InvalidValue {
+                                    key: "joint".to_string(),
+                                    value: j.to_string(),
+                                }),
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                        _ => Vec::new(),
+                    };
+                    event.pose = Some(Pose { pose_type, joints });
+                }
+                _ => {
+                    let mut s = || g.next().unwrap_or_default().to_string();
+                    event.attributes = match event.obj_type {
+                        ObjectType::Vehicle => Some(Attributes::Vehicle(Vehicle {
+                            type_: s(),
+                            make: s(),
+                            model: s(),
+                            color: s(),
+                            license: s(),
+                            region: s(),
+                        })),
+                        ObjectType::Person => Some(Attributes::Person(Person {
+                            gender: s(),
+                            age: parse_field("age", Some(&s()))?,
+                            hair: s(),
+                            cap: s(),
+                            apparel: s(),
+                        })),
+                        ObjectType::Face => Some(Attributes::Face(Face {
+                            gender: s(),
+                            age: parse_field("age", Some(&s()))?,
+                            hair: s(),
+                            cap: s(),
+                            glasses: s(),
+                            facial_hair: s(),
+                            name: s(),
+                            eye_color: s(),
+                        })),
+                        _ => None,
+                    };
+                    if event.attributes.is_some() {
+                        event.confidence = parse_field("confidence", Some(&s()))?;
+                    }
+                }
+            }
+        }
+        Ok(event)
+    }
+
+    /// Converts the message back to [`Event`]s; the sensor id is looked up in `config`.
+    pub fn to_events(&self, config: &Config) -> Result<Vec<Event>, Error> {
+        let frame_id = parse_field("id", Some(&self.id))?;
+        self.objects
+            .iter()
+            .map(|o| {
+                Self::parse_object(o).map(|e| Event {
+                    frame_id,
+                    ts: self.timestamp.clone(),
+                    sensor_id: config.sensor_index(&self.sensor_id).unwrap_or_default(),
+                    sensor_str: self.sensor_id.clone(),
+                    ..e
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msgconv::BBox;
+
+    /// `dstest5_msgconv_sample_config.txt` of the DeepStream samples.
+    const CONFIG: &str = "\
+[sensor0]
+enable=1
+type=Camera
+id=CAMERA_ID
+location=45.293701447;-75.8303914499;48.1557479338
+description=Entrance of Garage Right Lane
+coordinate=5.2;10.1;11.2
+
+[place0]
+enable=1
+id=1
+type=intersection/road
+name=HWY_20_AND_LOCUST__EBA
+location=30.32;-40.55;100.0
+coordinate=1.0;2.0;3.0
+place-sub-field1=C_127_158
+place-sub-field2=Lane 1
+place-sub-field3=P1
+
+[analytics0]
+enable=1
+id=XYZ_1
+description=Vehicle Detection and License Plate Recognition
+source=OpenALR
+version=1.0
+";
+
+    fn generator() -> Generator {
+        Generator::new(Config::from_ini(CONFIG).unwrap()).with_id_generator(|| "ID".to_string())
+    }
+
+    fn vehicle_event() -> Event {
+        Event {
+            type_: EventType::Moving,
+            obj_type: ObjectType::Vehicle,
+            bbox: BBox {
+                left: 10.0,
+                top: 20.0,
+                width: 100.5,
+                height: 50.0,
+            },
+            confidence: 0.5,
+            tracking_id: 7,
+            frame_id: 30,
+            ts: "2024-01-01T00:00:00.000Z".to_string(),
+            object_id: "7".to_string(),
+            attributes: Some(Attributes::Vehicle(Vehicle {
+                type_: "sedan".to_string(),
+                make: "Bugatti".to_string(),
+                model: "M".to_string(),
+                color: "blue".to_string(),
+                region: "CA".to_string(),
+                license: "XX1234".to_string(),
+            })),
+            ..Default::default()
+        }
+    }
+
+    /// Payload of the full schema in the Gst-nvmsgconv section of the DeepStream
+    /// developer guide, for the sample configuration, transcribed with the place
+    /// sub-object left out (see `full_place_sub_object`) and the generated ids and
+    /// timestamp replaced.
+    const FULL_REFERENCE: &str = r#"{
+  "messageid" : "ID",
+  "mdsversion" : "1.0",
+  "@timestamp" : "2024-01-01T00:00:00.000Z",
+  "place" : {
+    "id" : "1",
+    "name" : "HWY_20_AND_LOCUST__EBA",
+    "type" : "intersection/road",
+    "location" : {
+      "lat" : 30.32,
+      "lon" : -40.55,
+      "alt" : 100.0
+    }
+  },
+  "sensor" : {
+    "id" : "CAMERA_ID",
+    "type" : "Camera",
+    "description" : "Entrance of Garage Right Lane",
+    "location" : {
+      "lat" : 45.293701447,
+      "lon" : -75.8303914499,
+      "alt" : 48.1557479338
+    },
+    "coordinate" : {
+      "x" : 5.2,
+      "y" : 10.1,
+      "z" : 11.2
+    }
+  },
+  "analyticsModule" : {
+    "id" : "XYZ_1",
+    "description" : "Vehicle Detection and License Plate Recognition",
+    "source" : "OpenALR",
+    "version" : "1.0"
+  },
+  "object" : {
+    "id" : "7",
+    "speed" : 0.0,
+    "direction" : 0.0,
+    "orientation" : 0.0,
+    "vehicle" : {
+      "type" : "sedan",
+      "make" : "Bugatti",
+      "model" : "M",
+      "color" : "blue",
+      "licenseState" : "CA",
+      "license" : "XX1234",
+      "confidence" : 0.5
+    },
+    "bbox" : {
+      "topleftx" : 10,
+      "toplefty" : 20,
+      "bottomrightx" : 110,
+      "bottomrighty" : 70
+    },
+    "location" : {
+      "lat" : 0.0,
+      "lon" : 0.0,
+      "alt" : 0.0
+    },
+    "coordinate" : {
+      "x" : 0.0,
+      "y" : 0.0,
+      "z" : 0.0
+    }
+  },
+  "event" : {
+    "id" : "ID",
+    "type" : "moving"
+  },
+  "videoPath" : ""
+}"#;
+
+    /// Example of the comment of `generate_deepstream_message_minimal` in
+    /// `eventmsg_payload.cpp` of nvmsgconv.
+    const MINIMAL_REFERENCE_OBJECT: &str =
+        "957|1834|150|1918|215|Vehicle|#|sedan|Bugatti|M|blue|CA 444|California|0.8";
+
+    #[test]
+    fn full_reference() {
+        let json = generator().to_full_json(&vehicle_event()).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["place"]
+            .as_object_mut()
+            .unwrap()
+            .remove("aisle")
+            .is_some());
+        let reference: serde_json::Value = serde_json::from_str(FULL_REFERENCE).unwrap();
+        assert_eq!(value, reference);
+    }
+
+    #[test]
+    fn full_place_sub_object() {
+        let generator = generator();
+        let place = |type_| {
+            let event = Event {
+                type_,
+                ..vehicle_event()
+            };
+            generator.full_message(&event).place
+        };
+
+        let entry = place(EventType::Entry);
+        assert!(entry.entrance.is_some() && entry.aisle.is_none());
+        assert!(entry.parking_spot.is_none());
+        for type_ in [EventType::Parked, EventType::Empty] {
+            let parked = place(type_);
+            assert!(parked.entrance.is_none() && parked.aisle.is_none());
+            assert_eq!(parked.parking_spot.unwrap().id, "C_127_158");
+        }
+        let json = serde_json::to_string(&place(EventType::Parked)).unwrap();
+        assert!(json.contains(r#""parkingSpot":{"id":"C_127_158""#));
+    }
+
+    #[test]
+    fn minimal_reference() {
+        let event = Event {
+            tracking_id: 957,
+            bbox: BBox {
+                left: 1834.0,
+                top: 150.0,
+                width: 84.0,
+                height: 65.0,
+            },
+            confidence: 0.8,
+            attributes: Some(Attributes::Vehicle(Vehicle {
+                type_: "sedan".to_string(),
+                make: "Bugatti".to_string(),
+                model: "M".to_string(),
+                color: "blue".to_string(),
+                license: "CA 444".to_string(),
+                region: "California".to_string(),
+            })),
+            ..vehicle_event()
+        };
+        assert_eq!(Generator::minimal_object(&event), MINIMAL_REFERENCE_OBJECT);
+        let parsed = MinimalMessage::parse_object(MINIMAL_REFERENCE_OBJECT).unwrap();
+        assert_eq!(parsed.attributes, event.attributes);
+        assert_eq!(
+            (parsed.tracking_id, parsed.bbox, parsed.confidence),
+            (957, event.bbox, 0.8)
+        );
+
+        let json = generator().to_minimal_json(&[event]).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"version":"4.0","id":"30","@timestamp":"2024-01-01T00:00:00.000Z","#,
+                r#""sensorId":"CAMERA_ID","objects":["#,
+                r#""957|1834|150|1918|215|Vehicle|#|sedan|Bugatti|M|blue|CA 444|California|0.8"]}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn minimal_person_and_face() {
+        let person = Event {
+            obj_type: ObjectType::Person,
+            tracking_id: 8,
+            attributes: Some(Attributes::Person(Person {
+                gender: "male".to_string(),
+                age: 45,
+                hair: "black".to_string(),
+                cap: "none".to_string(),
+                apparel: "formal".to_string(),
+            })),
+            ..vehicle_event()
+        };
+        let object = Generator::minimal_object(&person);
+        assert_eq!(
+            object,
+            "8|10|20|110.5|70|Person|#|male|45|black|none|formal|0.5"
+        );
+        assert_eq!(
+            MinimalMessage::parse_object(&object).unwrap().attributes,
+            person.attributes
+        );
+
+        let face = Event {
+            obj_type: ObjectType::Face,
+            attributes: Some(Attributes::Face(Face {
+                gender: "female".to_string(),
+                age: 30,
+                glasses: "yes".to_string(),
+                name: "Jane".to_string(),
+                eye_color: "brown".to_string(),
+                ..Default::default()
+            })),
+            ..person
+        };
+        let object = Generator::minimal_object(&face);
+        assert_eq!(
+            object,
+            "8|10|20|110.5|70|Face|#|female|30|||yes||Jane|brown|0.5"
+        );
+        assert_eq!(
+            MinimalMessage::parse_object(&object).unwrap().attributes,
+            face.attributes
+        );
+    }
+
+    #[test]
+    fn full_round_trip() {
+        let generator = generator();
+        let event = Event {
+            sensor_str: "CAMERA_ID".to_string(),
+            embedding: Some(vec![0.25, -1.0]),
+            ..vehicle_event()
+        };
+        let message = FullMessage::from_json(&generator.to_full_json(&event).unwrap()).unwrap();
+        let back = message.to_event(generator.config()).unwrap();
+        assert_eq!(
+            back,
+            Event {
+                bbox: BBox {
+                    width: 100.0,
+                    ..event.bbox
+                },
+                tracking_id: 0,
+                frame_id: 0,
+                ..event
+            }
+        );
+    }
+
+    #[test]
+    fn minimal_round_trip() {
+        let generator = generator();
+        let events = vec![vehicle_event()];
+        let message =
+            MinimalMessage::from_json(&generator.to_minimal_json(&events).unwrap()).unwrap();
+        let back = message.to_events(generator.config()).unwrap();
+        assert_eq!(
+            back,
+            vec![Event {
+                type_: EventType::default(),
+                object_id: String::new(),
+                sensor_str: "CAMERA_ID".to_string(),
+                ..vehicle_event()
+            }]
+        );
+    }
+
+    #[test]
+    fn g_format() {
+        for (v, s) in [
+            (0.0, "0"),
+            (1.0, "1"),
+            (10.5, "10.5"),
+            (123456.0, "123456"),
+            (1234567.0, "1.23457e+06"),
+            (0.0001, "0.0001"),
+            (0.00001234, "1.234e-05"),
+            (-3.25, "-3.25"),
+            (0.9f32 as f64, "0.9"),
+        ] {
+            assert_eq!(format_g(v), s);
+        }
+    }
+}
@@ -0,0 +1,663 @@
+//! Protobuf payload (`PAYLOAD_DEEPSTREAM_PROTOBUF`), following the `nv.Frame` message
+//! of DeepStream's `schema.proto`.
+
+use super::{
+    json, Attributes, Config, Coordinate, Error, Event, Face, Joint, Location, Person, Pose,
+    Tracking3d, Vehicle,
+};
+use crate::meta::schema::{EventMsgMeta, EventType, ObjectType};
+use crate::timestamp::{format_rfc3339_parts, parse_rfc3339_parts};
+use prost::Message;
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Frame {
+    #[prost(string, tag = "1")]
+    pub version: String,
+    #[prost(string, tag = "2")]
+    pub id: String,
+    #[prost(message, optional, tag = "3")]
+    pub timestamp: Option<Timestamp>,
+    #[prost(string, tag = "4")]
+    pub sensor_id: String,
+    #[prost(message, repeated, tag = "5")]
+    pub objects: Vec<Object>,
+    #[prost(map = "string, string", tag = "6")]
+    pub info: HashMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Object {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(message, optional, tag = "2")]
+    pub bbox: Option<Bbox>,
+    #[prost(string, tag = "3")]
+    pub type_: String,
+    #[prost(float, tag = "4")]
+    pub confidence: f32,
+    #[prost(map = "string, string", tag = "5")]
+    pub info: HashMap<String, String>,
+    #[prost(message, optional, tag = "6")]
+    pub embedding: Option<Embedding>,
+    #[prost(message, optional, tag = "7")]
+    pub pose: Option<PoseMessage>,
+    #[prost(float, tag = "10")]
+    pub speed: f32,
+    #[prost(float, repeated, tag = "11")]
+    pub dir: Vec<f32>,
+    #[prost(message, optional, tag = "12")]
+    pub coordinate: Option<CoordinateMessage>,
+    #[prost(message, optional, tag = "13")]
+    pub location: Option<LocationMessage>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Bbox {
+    #[prost(float, tag = "1")]
+    pub left_x: f32,
+    #[prost(float, tag = "2")]
+    pub top_y: f32,
+    #[prost(float, tag = "3")]
+    pub right_x: f32,
+    #[prost(float, tag = "4")]
+    pub bottom_y: f32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Embedding {
+    #[prost(float, repeated, tag = "1")]
+    pub vector: Vec<f32>,
+    #[prost(map = "string, string", tag = "2")]
+    pub info: HashMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Keypoint {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(float, repeated, tag = "2")]
+    pub coordinates: Vec<f32>,
+    #[prost(float, repeated, tag = "3")]
+    pub quaternion: Vec<f32>,
+    #[prost(float, tag = "4")]
+    pub confidence: f32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoseMessage {
+    #[prost(string, tag = "1")]
+    pub type_: String,
+    #[prost(message, repeated, tag = "2")]
+    pub keypoints: Vec<Keypoint>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CoordinateMessage {
+    #[prost(double, tag = "1")]
+    pub x: f64,
+    #[prost(double, tag = "2")]
+    pub y: f64,
+    #[prost(double, tag = "3")]
+    pub z: f64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LocationMessage {
+    #[prost(double, tag = "1")]
+    pub lat: f64,
+    #[prost(double, tag = "2")]
+    pub lon: f64,
+    #[prost(double, tag = "3")]
+    pub alt: f64,
+}
+
+// Keys of `Object::info`, named like the fields of the JSON payloads.
+const INFO_EVENT_TYPE: &str = "eventType";
+const INFO_OBJECT_ID: &str = "objectId";
+const INFO_OBJ_CLASS_ID: &str = "objClassId";
+const INFO_MODULE_ID: &str = "moduleId";
+const INFO_PLACE_ID: &str = "placeId";
+const INFO_COMPONENT_ID: &str = "componentId";
+const INFO_VIDEO_PATH: &str = "videoPath";
+const INFO_OTHER_ATTRS: &str = "otherAttrs";
+const INFO_VISIBILITY: &str = "visibility";
+const INFO_FOOT_LOCATION_2D: &str = "footLocation2D";
+const INFO_FOOT_LOCATION_3D: &str = "footLocation3D";
+const INFO_CONVEX_HULL: &str = "convexHull";
+const VEHICLE_KEYS: [&str; 6] = ["type", "make", "model", "color", "licenseState", "license"];
+const PERSON_KEYS: [&str; 5] = ["age", "gender", "hair", "cap", "apparel"];
+const FACE_KEYS: [&str; 8] = [
+    "age",
+    "gender",
+    "hair",
+    "cap",
+    "glasses",
+    "facialhair",
+    "name",
+    "eyecolor",
+];
+
+fn parse_timestamp(ts: &str) -> Option<Timestamp> {
+    let (seconds, nanos) = parse_rfc3339_parts(ts)?;
+    Some(Timestamp {
+        seconds,
+        nanos: nanos as _,
+    })
+}
+
+/// Keeps the sub-millisecond digits, unlike [`format_rfc3339_parts`].
+fn format_timestamp(t: &Timestamp) -> String {
+    let s = format_rfc3339_parts(t.seconds, t.nanos as _);
+    match t.nanos {
+        n if n % 1_000_000 == 0 => s,
+        n if n % 1000 == 0 => format!("{}.{:06}Z", &s[..s.len() - 5], n / 1000),
+        n => format!("{}.{:09}Z", &s[..s.len() - 5], n),
+    }
+}
+
+fn join_floats(values: &[f32]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The fields of [`Event`] without an equivalent in `nv.Object` are stored in `info`,
+/// except the ones of the frame: `frame_id`, `ts` and the sensor, see
+/// [`Frame::from_events`]. `confidence` is narrowed to `f32`.
+impl From<&Event> for Object {
+    fn from(event: &Event) -> Object {
+        let mut info = HashMap::new();
+        info.insert(
+            INFO_EVENT_TYPE.to_string(),
+            event.type_.as_schema_str().to_string(),
+        );
+        let mut insert = |key: &str, value: String| {
+            if !value.is_empty() {
+                info.insert(key.to_string(), value);
+            }
+        };
+        insert(INFO_OBJECT_ID, event.object_id.clone());
+        insert(INFO_VIDEO_PATH, event.video_path.clone());
+        insert(INFO_OTHER_ATTRS, event.other_attrs.clone());
+        for (key, value) in [
+            (INFO_OBJ_CLASS_ID, event.obj_class_id),
+            (INFO_MODULE_ID, event.module_id),
+            (INFO_PLACE_ID, event.place_id),
+            (INFO_COMPONENT_ID, event.component_id),
+        ] {
+            if value != 0 {
+                insert(key, value.to_string());
+            }
+        }
+        if let Some(t) = &event.tracking_3d {
+            insert(INFO_VISIBILITY, t.visibility.to_string());
+            insert(INFO_FOOT_LOCATION_2D, join_floats(&t.img_feet));
+            insert(INFO_FOOT_LOCATION_3D, join_floats(&t.world_feet));
+            insert(
+                INFO_CONVEX_HULL,
+                t.convex_hull
+                    .iter()
+                    .map(|[x, y]| format!("{},{}", x, y))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            );
+        }
+        let values = match &event.attributes {
+            Some(Attributes::Vehicle(v)) => VEHICLE_KEYS
+                .iter()
+                .zip([&v.type_, &v.make, &v.model, &v.color, &v.region, &v.license])
+                .map(|(k, v)| (k, v.clone()))
+                .collect::<Vec<_>>(),
+            Some(Attributes::Person(p)) => PERSON_KEYS
+                .iter()
+                .zip([
+                    p.age.to_string(),
+                    p.gender.clone(),
+                    p.hair.clone(),
+                    p.cap.clone(),
+                    p.apparel.clone(),
+                ])
+                .collect(),
+            Some(Attributes::Face(f)) => FACE_KEYS
+                .iter()
+                .zip([
+                    f.age.to_string(),
+                    f.gender.clone(),
+                    f.hair.clone(),
+                    f.cap.clone(),
+                    f.glasses.clone(),
+                    f.facial_hair.clone(),
+                    f.name.clone(),
+                    f.eye_color.clone(),
+                ])
+                .collect(),
+            None => Vec::new(),
+        };
+        info.extend(values.into_iter().map(|(k, v)| (k.to_string(), v)));
+
+        Object {
+            id: event.tracking_id.to_string(),
+            bbox: Some(Bbox {
+                left_x: event.bbox.left,
+                top_y: event.bbox.top,
+                right_x: event.bbox.left + event.bbox.width,
+                bottom_y: event.bbox.top + event.bbox.height,
+            }),
+            type_: event.obj_type.as_schema_str().to_string(),
+            confidence: event.confidence as _,
+            info,
+            embedding: event.embedding.as_ref().map(|e| Embedding {
+                vector: e.clone(),
+                info: HashMap::new(),
+            }),
+            pose: event.pose.as_ref().map(|p| PoseMessage {
+                type_: p.pose_type.to_string(),
+                keypoints: p
+                    .joints
+                    .iter()
+                    .map(|j| Keypoint {
+                        coordinates: vec![j.x, j.y, j.z],
+                        confidence: j.confidence,
+                        ..Default::default()
+                    })
+                    .collect(),
+            }),
+            coordinate: Some(CoordinateMessage {
+                x: event.coordinate.x,
+                y: event.coordinate.y,
+                z: event.coordinate.z,
+            }),
+            location: Some(LocationMessage {
+                lat: event.location.lat,
+                lon: event.location.lon,
+                alt: event.location.alt,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Clone> From<&EventMsgMeta<T>> for Object {
+    fn from(meta: &EventMsgMeta<T>) -> Object {
+        Object::from(&Event::from_event_msg_meta(meta))
+    }
+}
+
+fn invalid(key: &str, value: &str) -> Error {
+    Error::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn parse_value<T: std::str::FromStr + Default>(key: &str, value: &str) -> Result<T, Error> {
+    if value.is_empty() {
+        Ok(T::default())
+    } else {
+        value.parse().map_err(|_| invalid(key, value))
+    }
+}
+
+/// Parses `x,y`.
+fn parse_pair<T: std::str::FromStr + Default>(key: &str, value: &str) -> Result<[T; 2], Error> {
+    match value.split_once(',') {
+        Some((x, y)) => Ok([parse_value(key, x)?, parse_value(key, y)?]),
+        None if value.is_empty() => Ok([T::default(), T::default()]),
+        None => Err(invalid(key, value)),
+    }
+}
+
+impl Object {
+    /// Value of `key` in `info`, the default if missing or empty.
+    fn info_number<T: std::str::FromStr + Default>(&self, key: &str) -> Result<T, Error> {
+        parse_value(key, self.info.get(key).map_or("", |v| v.as_str()))
+    }
+
+    pub fn to_event(&self) -> Result<Event, Error> {
+        let info = |key: &str| self.info.get(key).cloned().unwrap_or_default();
+
+        let type_ = match self.info.get(INFO_EVENT_TYPE) {
+            Some(t) => EventType::from_schema_str(t).ok_or_else(|| invalid(INFO_EVENT_TYPE, t))?,
+            None => EventType::default(),
+        };
+        let obj_type = ObjectType::from_schema_str(&self.type_).unwrap_or(ObjectType::Custom);
+        let has = |keys: &[&str]| keys.iter().any(|k| self.info.contains_key(*k));
+        let attributes = match obj_type {
+            ObjectType::Vehicle if has(&VEHICLE_KEYS) => Some(Attributes::Vehicle(Vehicle {
+                type_: info(VEHICLE_KEYS[0]),
+                make: info(VEHICLE_KEYS[1]),
+                model: info(VEHICLE_KEYS[2]),
+                color: info(VEHICLE_KEYS[3]),
+                region: info(VEHICLE_KEYS[4]),
+                license: info(VEHICLE_KEYS[5]),
+            })),
+            ObjectType::Person if has(&PERSON_KEYS) => Some(Attributes::Person(Person {
+                age: self.info_number(PERSON_KEYS[0])?,
+                gender: info(PERSON_KEYS[1]),
+                hair: info(PERSON_KEYS[2]),
+                cap: info(PERSON_KEYS[3]),
+                apparel: info(PERSON_KEYS[4]),
+            })),
+            ObjectType::Face if has(&FACE_KEYS) => Some(Attributes::Face(Face {
+                age: self.info_number(FACE_KEYS[0])?,
+                gender: info(FACE_KEYS[1]),
+                hair: info(FACE_KEYS[2]),
+                cap: info(FACE_KEYS[3]),
+                glasses: info(FACE_KEYS[4]),
+                facial_hair: info(FACE_KEYS[5]),
+                name: info(FACE_KEYS[6]),
+                eye_color: info(FACE_KEYS[7]),
+            })),
+            _ => None,
+        };
+
+        let pose = match &self.pose {
+            Some(p) => Some(Pose {
+                pose_type: p
+                    .type_
+                    .parse()
+                    .map_err(|_| invalid("pose.type", &p.type_))?,
+                joints: p
+                    .keypoints
+                    .iter()
+                    .map(|k| Joint {
+                        x: k.coordinates.first().copied().unwrap_or_default(),
+                        y: k.coordinates.get(1).copied().unwrap_or_default(),
+                        z: k.coordinates.get(2).copied().unwrap_or_default(),
+                        confidence: k.confidence,
+                    })
+                    .collect(),
+            }),
+            None => None,
+        };
+
+        let tracking_3d = if self.info.contains_key(INFO_VISIBILITY) {
+            Some(Tracking3d {
+                visibility: self.info_number(INFO_VISIBILITY)?,
+                img_feet: parse_pair(INFO_FOOT_LOCATION_2D, &info(INFO_FOOT_LOCATION_2D))?,
+                world_feet: parse_pair(INFO_FOOT_LOCATION_3D, &info(INFO_FOOT_LOCATION_3D))?,
+                convex_hull: info(INFO_CONVEX_HULL)
+                    .split(';')
+                    .filter(|p| !p.is_empty())
+                    .map(|p| parse_pair(INFO_CONVEX_HULL, p))
+                    .collect::<Result<_, _>>()?,
+            })
+        } else {
+            None
+        };
+
+        let bbox = self.bbox.clone().unwrap_or_default();
+        let coordinate = self.coordinate.clone().unwrap_or_default();
+        let location = self.location.clone().unwrap_or_default();
+        Ok(Event {
+            type_,
+            obj_type,
+            bbox: super::BBox {
+                left: bbox.left_x,
+                top: bbox.top_y,
+                width: bbox.right_x - bbox.left_x,
+                height: bbox.bottom_y - bbox.top_y,
+            },
+            location: Location {
+                lat: location.lat,
+                lon: location.lon,
+                alt: location.alt,
+            },
+            coordinate: Coordinate {
+                x: coordinate.x,
+                y: coordinate.y,
+                z: coordinate.z,
+            },
+            obj_class_id: self.info_number(INFO_OBJ_CLASS_ID)?,
+            module_id: self.info_number(INFO_MODULE_ID)?,
+            place_id: self.info_number(INFO_PLACE_ID)?,
+            component_id: self.info_number(INFO_COMPONENT_ID)?,
+            confidence: self.confidence as _,
+            tracking_id: self.id.parse().map_err(|_| invalid("id", &self.id))?,
+            object_id: info(INFO_OBJECT_ID),
+            other_attrs: info(INFO_OTHER_ATTRS),
+            video_path: info(INFO_VIDEO_PATH),
+            attributes,
+            pose,
+            embedding: self.embedding.as_ref().map(|e| e.vector.clone()),
+            tracking_3d,
+            ..Default::default()
+        })
+    }
+}
+
+impl Frame {
+    /// Frame for the events of one frame, `events` should share the frame and sensor.
+    /// The sensor id is taken from `config` like for the minimal schema; `frame_id`,
+    /// `ts` and the sensor of the first event are used for all of them.
+    pub fn from_events(config: &Config, events: &[Event]) -> Frame {
+        let first = events.first().cloned().unwrap_or_default();
+        Frame {
+            version: json::MINIMAL_VERSION.to_string(),
+            id: first.frame_id.to_string(),
+            timestamp: parse_timestamp(&first.ts),
+            sensor_id: config
+                .sensors
+                .get(&first.sensor_id)
+                .map(|s| s.id.clone())
+                .unwrap_or(first.sensor_str),
+            objects: events.iter().map(Object::from).collect(),
+            info: HashMap::new(),
+        }
+    }
+
+    pub fn to_events(&self, config: &Config) -> Result<Vec<Event>, Error> {
+        let frame_id = self.id.parse().map_err(|_| invalid("id", &self.id))?;
+        let ts = self
+            .timestamp
+            .as_ref()
+            .map(format_timestamp)
+            .unwrap_or_default();
+        self.objects
+            .iter()
+            .map(|o| {
+                o.to_event().map(|e| Event {
+                    frame_id,
+                    ts: ts.clone(),
+                    sensor_id: config.sensor_index(&self.sensor_id).unwrap_or_default(),
+                    sensor_str: self.sensor_id.clone(),
+                    ..e
+                })
+            })
+            .collect()
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Frame, Error> {
+        Frame::decode(buf).map_err(Error::Protobuf)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msgconv::BBox;
+
+    const CONFIG: &str = "\
+[sensor0]
+enable=1
+id=CAMERA_ID
+";
+
+    fn event(obj_type: ObjectType, attributes: Option<Attributes>) -> Event {
+        Event {
+            type_: EventType::Moving,
+            obj_type,
+            bbox: BBox {
+                left: 10.0,
+                top: 20.0,
+                width: 100.5,
+                height: 50.0,
+            },
+            location: Location {
+                lat: 45.29,
+                lon: -75.83,
+                alt: 48.15,
+            },
+            coordinate: Coordinate {
+                x: 5.2,
+                y: 10.1,
+                z: 11.2,
+            },
+            obj_class_id: 2,
+            module_id: 1,
+            place_id: 3,
+            component_id: 4,
+            frame_id: 30,
+            confidence: 0.5,
+            tracking_id: 7,
+            ts: "2024-01-01T00:00:00.123Z".to_string(),
+            object_id: "7".to_string(),
+            sensor_str: "CAMERA_ID".to_string(),
+            other_attrs: "a=b".to_string(),
+            video_path: "/videos/0.mp4".to_string(),
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    fn round_trip(events: &[Event]) -> Vec<Event> {
+        let config = Config::from_ini(CONFIG).unwrap();
+        let bytes = Frame::from_events(&config, events).to_bytes();
+        Frame::from_bytes(&bytes)
+            .unwrap()
+            .to_events(&config)
+            .unwrap()
+    }
+
+    #[test]
+    fn vehicle() {
+        let e = event(
+            ObjectType::Vehicle,
+            Some(Attributes::Vehicle(Vehicle {
+                type_: "sedan".to_string(),
+                make: "Bugatti".to_string(),
+                model: "M".to_string(),
+                color: "blue".to_string(),
+                region: "CA".to_string(),
+                license: "XX1234".to_string(),
+            })),
+        );
+        let events = vec![e];
+        assert_eq!(round_trip(&events), events);
+    }
+
+    #[test]
+    fn person_and_face() {
+        let person = event(
+            ObjectType::Person,
+            Some(Attributes::Person(Person {
+                gender: "male".to_string(),
+                hair: "black".to_string(),
+                cap: "none".to_string(),
+                apparel: "formal".to_string(),
+                age: 45,
+            })),
+        );
+        let face = Event {
+            tracking_id: 8,
+            type_: EventType::Exit,
+            ..event(
+                ObjectType::Face,
+                Some(Attributes::Face(Face {
+                    gender: "female".to_string(),
+                    hair: "red".to_string(),
+                    cap: "none".to_string(),
+                    glasses: "yes".to_string(),
+                    facial_hair: "no".to_string(),
+                    name: "x".to_string(),
+                    eye_color: "green".to_string(),
+                    age: 30,
+                })),
+            )
+        };
+        let events = vec![person, face];
+        assert_eq!(round_trip(&events), events);
+    }
+
+    #[test]
+    fn embedding_pose_and_tracking() {
+        let e = Event {
+            embedding: Some(vec![0.25, -1.0, 3.5]),
+            pose: Some(Pose {
+                pose_type: 2,
+                joints: vec![
+                    Joint {
+                        x: 1.5,
+                        y: 2.0,
+                        z: -0.5,
+                        confidence: 0.75,
+                    },
+                    Joint::default(),
+                ],
+            }),
+            tracking_3d: Some(Tracking3d {
+                visibility: 0.8,
+                img_feet: [12.5, 30.0],
+                world_feet: [-1.25, 4.0],
+                convex_hull: vec![[0, 0], [10, -2], [5, 7]],
+            }),
+            ..event(ObjectType::Person, None)
+        };
+        let events = vec![e];
+        assert_eq!(round_trip(&events), events);
+    }
+
+    #[test]
+    fn custom_type_and_defaults() {
+        let e = Event {
+            obj_type: ObjectType::Custom,
+            frame_id: 1,
+            ts: "2024-01-01T00:00:00.000Z".to_string(),
+            sensor_str: "CAMERA_ID".to_string(),
+            ..Default::default()
+        };
+        let events = vec![e];
+        assert_eq!(round_trip(&events), events);
+    }
+
+    #[test]
+    fn timestamp_precision() {
+        for ts in [
+            "2024-02-29T23:59:58.123Z",
+            "2024-02-29T23:59:58.123456Z",
+            "2024-02-29T23:59:58.123456789Z",
+        ] {
+            let t = parse_timestamp(ts).unwrap();
+            assert_eq!(format_timestamp(&t), ts);
+        }
+        let t = parse_timestamp("2024-02-29T23:59:58.5+01:30").unwrap();
+        assert_eq!(format_timestamp(&t), "2024-02-29T22:29:58.500Z");
+    }
+
+    #[test]
+    fn invalid_info() {
+        let mut object = Object::from(&event(ObjectType::Vehicle, None));
+        object
+            .info
+            .insert(INFO_MODULE_ID.to_string(), "x".to_string());
+        assert!(matches!(
+            object.to_event(),
+            Err(Error::InvalidValue { key, .. }) if key == INFO_MODULE_ID
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::meta::{BaseMetaType, FrameMeta, MetaType, ObjectMeta, UserMeta};
 use crate::WrapperExt;
 use gstreamer::glib::GStr;
 use std::ptr::NonNull;
@@ -138,6 +139,56 @@ impl<'a> ObjEncUsrArgsBuilder<'a> {
     }
 }
 
+crate::wrapper_impl_value_type!(ObjEncOutParams, nvidia_deepstream_sys::NvDsObjEncOutParams);
+
+impl ObjEncOutParams {
+    pub fn out_buffer(&self) -> &[u8] {
+        let p = self.as_native_type_ref();
+        if p.outBuffer.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(p.outBuffer, p.outLen as _) }
+        }
+    }
+}
+
+impl UserMeta {
+    /// Get [`ObjEncOutParams`] stored in our data if it exists
+    pub fn obj_enc_out_params(&self) -> Option<&ObjEncOutParams> {
+        if self.base_meta().meta_type() == MetaType::Base(BaseMetaType::CropImageDataMeta) {
+            unsafe { self.user_meta_data() }
+        } else {
+            None
+        }
+    }
+}
+
+impl ObjectMeta {
+    /// Crops attached by [`ObjEnc`] when `attach_usr_meta` is set.
+    pub fn crop_images(&self) -> Vec<&ObjEncOutParams> {
+        self.obj_user_meta_list()
+            .map(|list| list.iter().filter_map(|m| m.obj_enc_out_params()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl FrameMeta {
+    /// Full frame images attached by [`ObjEnc`] when `attach_usr_meta` is set.
+    pub fn crop_images(&self) -> Vec<&ObjEncOutParams> {
+        self.frame_user_meta_list()
+            .map(|list| list.iter().filter_map(|m| m.obj_enc_out_params()).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EncodedCrop {
+    pub jpeg: Vec<u8>,
+    pub object_id: u64,
+    pub frame_num: i32,
+    pub source_id: u32,
+}
+
 pub struct ObjEnc(NonNull<nvidia_deepstream_sys::_NvDsObjEncCtx>);
 
 impl Drop for ObjEnc {
@@ -163,7 +214,7 @@ impl ObjEnc {
         surface: &crate::surface::Surface,
         object_meta: &crate::meta::ObjectMeta,
         frame_meta: &crate::meta::FrameMeta,
-    ) -> Result<(), bool> {
+    ) -> Result<(), crate::Error> {
         unsafe {
             if nvidia_deepstream_sys::nvds_obj_enc_process(
                 self.0.as_ptr(),
@@ -174,7 +225,7 @@ impl ObjEnc {
             ) {
                 Ok(())
             } else {
-                Err(false)
+                Err(crate::Error::ObjEncode)
             }
         }
     }
@@ -182,4 +233,56 @@ impl ObjEnc {
     pub fn finish(&self) {
         unsafe { nvidia_deepstream_sys::nvds_obj_enc_finish(self.0.as_ptr()) }
     }
+
+    /// Encodes the objects of `frame_meta` accepted by `filter` and returns the JPEG
+    /// data in memory. `user_args` is used for scaling and quality only: images are never
+    /// saved to disk, and objects are always encoded, not the frame. The crops are
+    /// read back from the `NVDS_CROP_IMAGE_META` attached to the objects, which stays
+    /// attached as with `attachUsrMeta`.
+    pub fn encode_objects<F: FnMut(&ObjectMeta) -> bool>(
+        &self,
+        user_args: &ObjEncUsrArgs,
+        surface: &crate::surface::Surface,
+        frame_meta: &FrameMeta,
+        mut filter: F,
+    ) -> Vec<EncodedCrop> {
+        let mut args = ObjEncUsrArgs::from_native_type(user_args.as_native_type());
+        args.as_native_type_mut().saveImg = false;
+        args.as_native_type_mut().attachUsrMeta = true;
+        #[cfg(feature = "v6_2")]
+        {
+            args.as_native_type_mut().isFrame = false;
+        }
+
+        let objects = match frame_meta.obj_meta_list() {
+            Some(list) => list
+                .iter()
+                .filter(|o| filter(*o))
+                .map(|o| (o, o.crop_images().len()))
+                .collect::<Vec<_>>(),
+            None => return Vec::new(),
+        };
+
+        let objects = objects
+            .into_iter()
+            .filter(|(o, _)| self.process(&args, surface, o, frame_meta).is_ok())
+            .collect::<Vec<_>>();
+        self.finish();
+
+        objects
+            .into_iter()
+            .flat_map(|(o, skip)| {
+                o.crop_images()
+                    .into_iter()
+                    .skip(skip)
+                    .map(|p| EncodedCrop {
+                        jpeg: p.out_buffer().to_vec(),
+                        object_id: o.object_id(),
+                        frame_num: frame_meta.frame_num(),
+                        source_id: frame_meta.source_id(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,483 @@
+use crate::meta::osd;
+use crate::meta::{BatchMetaExt, DisplayMeta, DisplayMetaBuilder, FrameMeta};
+use crate::WrapperExt;
+use gstreamer::glib::{GStr, GString};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum OverlayError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+    UnknownFormat,
+}
+
+impl std::fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlayError::Io(e) => write!(f, "failed to read overlay: {}", e),
+            OverlayError::Json(e) => write!(f, "invalid overlay json: {}", e),
+            OverlayError::Yaml(e) => write!(f, "invalid overlay yaml: {}", e),
+            OverlayError::UnknownFormat => write!(f, "unknown overlay file format"),
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OverlayError::Io(e) => Some(e),
+            OverlayError::Json(e) => Some(e),
+            OverlayError::Yaml(e) => Some(e),
+            OverlayError::UnknownFormat => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Color {
+    #[serde(default)]
+    pub red: f64,
+    #[serde(default)]
+    pub green: f64,
+    #[serde(default)]
+    pub blue: f64,
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+}
+
+fn default_alpha() -> f64 {
+    1.0
+}
+
+impl Color {
+    fn to_color_params(self) -> osd::ColorParams {
+        osd::ColorParams::new(self.red, self.green, self.blue, self.alpha)
+    }
+
+    fn from_color_params(c: &osd::ColorParams) -> Color {
+        Color {
+            red: c.red(),
+            green: c.green(),
+            blue: c.blue(),
+            alpha: c.alpha(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rect {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub border_width: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg_color: Option<Color>,
+}
+
+impl Rect {
+    fn to_builder(&self) -> osd::RectParamsBuilder {
+        let mut builder = osd::RectParamsBuilder::new()
+            .left(self.left)
+            .top(self.top)
+            .width(self.width)
+            .height(self.height)
+            .border_width(self.border_width)
+            .bg_color(self.bg_color.map(Color::to_color_params));
+        if let Some(color) = self.border_color {
+            builder = builder.border_color(color.to_color_params());
+        }
+        builder
+    }
+
+    fn from_params(p: &osd::RectParams) -> Rect {
+        Rect {
+            left: p.left(),
+            top: p.top(),
+            width: p.width(),
+            height: p.height(),
+            border_width: p.border_width(),
+            border_color: Some(Color::from_color_params(p.border_color())),
+            bg_color: p.bg_color().map(Color::from_color_params),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Label {
+    pub text: String,
+    pub x_offset: u32,
+    pub y_offset: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg_color: Option<Color>,
+}
+
+impl Label {
+    fn to_builder(&self) -> osd::TextParamsBuilder {
+        let mut font = osd::FontParamsBuilder::new();
+        if let Some(name) = &self.font_name {
+            if let Some(name) = intern_font_name(name) {
+                font = font.font_name(name);
+            }
+        }
+        if let Some(size) = self.font_size {
+            font = font.font_size(size);
+        }
+        if let Some(color) = self.font_color {
+            font = font.font_color(color.to_color_params());
+        }
+
+        let mut builder = osd::TextParamsBuilder::new()
+            .display_text(GString::from(self.text.as_str()))
+            .x_offset(self.x_offset)
+            .y_offset(self.y_offset)
+            .font_params(font.build());
+        if let Some(color) = self.bg_color {
+            builder = builder.text_bg_clr(color.to_color_params());
+        }
+        builder
+    }
+
+    fn from_params(p: &osd::TextParams) -> Label {
+        let text = p.as_native_type_ref().display_text;
+        let font_name = p.font_params().as_native_type_ref().font_name;
+        Label {
+            text: if text.is_null() {
+                String::new()
+            } else {
+                p.display_text().to_string()
+            },
+            x_offset: p.x_offset(),
+            y_offset: p.y_offset(),
+            font_name: if font_name.is_null() {
+                None
+            } else {
+                Some(p.font_params().font_name().to_string())
+            },
+            font_size: Some(p.font_params().font_size()),
+            font_color: Some(Color::from_color_params(p.font_params().font_color())),
+            bg_color: p.text_bg_clr().map(Color::from_color_params),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Line {
+    pub x1: u32,
+    pub y1: u32,
+    pub x2: u32,
+    pub y2: u32,
+    pub line_width: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_color: Option<Color>,
+}
+
+impl Line {
+    fn to_builder(&self) -> osd::LineParamsBuilder {
+        let mut builder = osd::LineParamsBuilder::new()
+            .x1(self.x1)
+            .y1(self.y1)
+            .x2(self.x2)
+            .y2(self.y2)
+            .line_width(self.line_width);
+        if let Some(color) = self.line_color {
+            builder = builder.line_color(color.to_color_params());
+        }
+        builder
+    }
+
+    fn from_params(p: &osd::LineParams) -> Line {
+        Line {
+            x1: p.x1(),
+            y1: p.y1(),
+            x2: p.x2(),
+            y2: p.y2(),
+            line_width: p.line_width(),
+            line_color: Some(Color::from_color_params(p.line_color())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrowHead {
+    #[default]
+    Start,
+    End,
+    Both,
+}
+
+impl From<ArrowHead> for osd::ArrowHeadDirection {
+    fn from(value: ArrowHead) -> Self {
+        match value {
+            ArrowHead::Start => osd::ArrowHeadDirection::Start,
+            ArrowHead::End => osd::ArrowHeadDirection::End,
+            ArrowHead::Both => osd::ArrowHeadDirection::Both,
+        }
+    }
+}
+
+impl From<osd::ArrowHeadDirection> for ArrowHead {
+    fn from(value: osd::ArrowHeadDirection) -> Self {
+        match value {
+            osd::ArrowHeadDirection::Start => ArrowHead::Start,
+            osd::ArrowHeadDirection::End => ArrowHead::End,
+            osd::ArrowHeadDirection::Both => ArrowHead::Both,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Arrow {
+    pub x1: u32,
+    pub y1: u32,
+    pub x2: u32,
+    pub y2: u32,
+    pub arrow_width: u32,
+    pub arrow_head: ArrowHead,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arrow_color: Option<Color>,
+}
+
+impl Arrow {
+    fn to_builder(&self) -> osd::ArrowParamsBuilder {
+        let mut builder = osd::ArrowParamsBuilder::new()
+            .x1(self.x1)
+            .y1(self.y1)
+            .x2(self.x2)
+            .y2(self.y2)
+            .arrow_width(self.arrow_width)
+            .arrow_head(self.arrow_head.into());
+        if let Some(color) = self.arrow_color {
+            builder = builder.arrow_color(color.to_color_params());
+        }
+        builder
+    }
+
+    fn from_params(p: &osd::ArrowParams) -> Arrow {
+        Arrow {
+            x1: p.x1(),
+            y1: p.y1(),
+            x2: p.x2(),
+            y2: p.y2(),
+            arrow_width: p.arrow_width(),
+            arrow_head: p.arrow_head().into(),
+            arrow_color: Some(Color::from_color_params(p.arrow_color())),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Circle {
+    pub xc: u32,
+    pub yc: u32,
+    pub radius: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circle_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg_color: Option<Color>,
+}
+
+impl Circle {
+    fn to_builder(&self) -> osd::CircleParamsBuilder {
+        let mut builder = osd::CircleParamsBuilder::new()
+            .xc(self.xc)
+            .yc(self.yc)
+            .radius(self.radius)
+            .bg_color(self.bg_color.map(Color::to_color_params));
+        if let Some(color) = self.circle_color {
+            builder = builder.circle_color(color.to_color_params());
+        }
+        builder
+    }
+
+    fn from_params(p: &osd::CircleParams) -> Circle {
+        Circle {
+            xc: p.xc(),
+            yc: p.yc(),
+            radius: p.radius(),
+            circle_color: Some(Color::from_color_params(p.circle_color())),
+            bg_color: p.bg_color().map(Color::from_color_params),
+        }
+    }
+}
+
+/// Set of overlay elements drawn on the frames of the listed sources.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayLayer {
+    /// Sources this layer applies to. An empty list matches every source.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub source_ids: Vec<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rects: Vec<Rect>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<Line>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub arrows: Vec<Arrow>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub circles: Vec<Circle>,
+}
+
+impl OverlayLayer {
+    pub fn matches(&self, source_id: u32) -> bool {
+        self.source_ids.is_empty() || self.source_ids.contains(&source_id)
+    }
+
+    pub fn from_display_meta(display_meta: &DisplayMeta) -> OverlayLayer {
+        OverlayLayer {
+            source_ids: Vec::new(),
+            rects: display_meta
+                .rect_params()
+                .iter()
+                .map(Rect::from_params)
+                .collect(),
+            labels: display_meta
+                .text_params()
+                .iter()
+                .map(Label::from_params)
+                .collect(),
+            lines: display_meta
+                .line_params()
+                .iter()
+                .map(Line::from_params)
+                .collect(),
+            arrows: display_meta
+                .arrow_params()
+                .iter()
+                .map(Arrow::from_params)
+                .collect(),
+            circles: display_meta
+                .circle_params()
+                .iter()
+                .map(Circle::from_params)
+                .collect(),
+        }
+    }
+
+    /// Adds the layer to the frame, splitting it across as many display metas as needed.
+    /// Returns false if a display meta could not be acquired from the pool.
+    pub fn attach<BM: BatchMetaExt>(&self, batch_meta: &BM, frame_meta: &FrameMeta) -> bool {
+        let max = nvidia_deepstream_sys::MAX_ELEMENTS_IN_DISPLAY_META as usize;
+        let chunks = [
+            self.rects.len(),
+            self.labels.len(),
+            self.lines.len(),
+            self.arrows.len(),
+            self.circles.len(),
+        ]
+        .iter()
+        .map(|len| (len + max - 1) / max)
+        .max()
+        .unwrap_or_default();
+
+        for i in 0..chunks {
+            let mut rects = chunk(&self.rects, i, max, Rect::to_builder);
+            let mut labels = chunk(&self.labels, i, max, Label::to_builder);
+            let mut lines = chunk(&self.lines, i, max, Line::to_builder);
+            let mut arrows = chunk(&self.arrows, i, max, Arrow::to_builder);
+            let mut circles = chunk(&self.circles, i, max, Circle::to_builder);
+            match DisplayMetaBuilder::new()
+                .rect_params(&mut rects)
+                .text_params(&mut labels)
+                .line_params(&mut lines)
+                .arrow_params(&mut arrows)
+                .circle_params(&mut circles)
+                .build(batch_meta)
+            {
+                Some(display_meta) => frame_meta.add_display_meta(display_meta),
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+fn chunk<T, B>(items: &[T], index: usize, size: usize, f: fn(&T) -> B) -> Vec<B> {
+    items.iter().skip(index * size).take(size).map(f).collect()
+}
+
+/// Font names are referenced, not copied, by `NvOSD_FontParams`, so they have to
+/// live as long as the pipeline. Each distinct name is allocated once.
+fn intern_font_name(name: &str) -> Option<&'static GStr> {
+    static FONT_NAMES: std::sync::Mutex<Vec<&'static GStr>> = std::sync::Mutex::new(Vec::new());
+    let mut names = FONT_NAMES.lock().ok()?;
+    if let Some(n) = names.iter().find(|n| n.as_str() == name) {
+        return Some(n);
+    }
+    let n: &'static GStr =
+        unsafe { GStr::from_ptr(std::ffi::CString::new(name).ok()?.into_raw() as _) };
+    names.push(n);
+    Some(n)
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overlay {
+    pub layers: Vec<OverlayLayer>,
+}
+
+impl Overlay {
+    pub fn from_json(s: &str) -> Result<Overlay, OverlayError> {
+        serde_json::from_str(s).map_err(OverlayError::Json)
+    }
+
+    pub fn from_yaml(s: &str) -> Result<Overlay, OverlayError> {
+        serde_yaml::from_str(s).map_err(OverlayError::Yaml)
+    }
+
+    /// Loads an overlay, choosing the format from the file extension (`json`, `yml` or `yaml`).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Overlay, OverlayError> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(OverlayError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&s),
+            Some("yml") | Some("yaml") => Self::from_yaml(&s),
+            _ => Err(OverlayError::UnknownFormat),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, OverlayError> {
+        serde_json::to_string_pretty(self).map_err(OverlayError::Json)
+    }
+
+    pub fn to_yaml(&self) -> Result<String, OverlayError> {
+        serde_yaml::to_string(self).map_err(OverlayError::Yaml)
+    }
+
+    pub fn layers_for(&self, source_id: u32) -> impl Iterator<Item = &OverlayLayer> {
+        self.layers.iter().filter(move |l| l.matches(source_id))
+    }
+
+    /// Adds every layer matching the frame's source to the frame.
+    pub fn attach<BM: BatchMetaExt>(&self, batch_meta: &BM, frame_meta: &FrameMeta) -> bool {
+        self.layers_for(frame_meta.source_id())
+            .all(|layer| layer.attach(batch_meta, frame_meta))
+    }
+
+    /// Adds the matching layers to every frame of the batch.
+    pub fn attach_to_batch<BM: BatchMetaExt>(&self, batch_meta: &BM) -> bool {
+        batch_meta.frame_meta_list().map_or(true, |frames| {
+            frames
+                .iter()
+                .all(|frame_meta| self.attach(batch_meta, frame_meta))
+        })
+    }
+}
@@ -0,0 +1,347 @@
+//! Re-identification helpers: distances between embeddings, a gallery of per track
+//! features and extraction of embeddings from nvinfer tensor output meta.
+
+use crate::meta::{ObjectMeta, UserMeta};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub fn l2_norm(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// Scales `v` to unit length, zero vectors are left unchanged.
+pub fn normalize(v: &mut [f32]) {
+    let norm = l2_norm(v);
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+pub fn normalized(v: &[f32]) -> Vec<f32> {
+    let mut v = v.to_vec();
+    normalize(&mut v);
+    v
+}
+
+/// The functions comparing two vectors return `None` when their dimensions differ.
+pub fn dot(a: &[f32], b: &[f32]) -> Option<f32> {
+    (a.len() == b.len()).then(|| a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+/// Returns 0 when one of the vectors is zero.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    let dot = dot(a, b)?;
+    let norm = l2_norm(a) * l2_norm(b);
+    Some(if norm > 0.0 { dot / norm } else { 0.0 })
+}
+
+/// `1 - cosine_similarity`, in `[0, 2]`.
+pub fn cosine_distance(a: &[f32], b: &[f32]) -> Option<f32> {
+    cosine_similarity(a, b).map(|s| 1.0 - s)
+}
+
+pub fn l2_distance(a: &[f32], b: &[f32]) -> Option<f32> {
+    (a.len() == b.len()).then(|| {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt()
+    })
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum Metric {
+    #[default]
+    Cosine,
+    L2,
+}
+
+impl Metric {
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> Option<f32> {
+        match self {
+            Metric::Cosine => cosine_distance(a, b),
+            Metric::L2 => l2_distance(a, b),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GalleryEntry {
+    feature: Vec<f32>,
+    count: u32,
+}
+
+impl GalleryEntry {
+    /// Normalized average of the features added for this key.
+    pub fn feature(&self) -> &[f32] {
+        &self.feature
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Match<K> {
+    pub key: K,
+    pub distance: f32,
+}
+
+/// Features of known objects keyed by e.g. track id or `(source_id, object_id)`.
+///
+/// Features are normalized before being averaged, either with a running mean or,
+/// with [`Gallery::with_momentum`], an exponential moving average.
+#[derive(Clone, Debug)]
+pub struct Gallery<K = u64> {
+    metric: Metric,
+    momentum: Option<f32>,
+    entries: HashMap<K, GalleryEntry>,
+}
+
+impl<K: Hash + Eq + Clone> Gallery<K> {
+    pub fn new(metric: Metric) -> Gallery<K> {
+        Gallery {
+            metric,
+            momentum: None,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Weight of a new feature in the average, in `(0, 1]`.
+    pub fn with_momentum(mut self, momentum: f32) -> Self {
+        self.momentum = Some(momentum.clamp(f32::EPSILON, 1.0));
+        self
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// Adds a feature of `key`, replacing its entry when the dimension changed.
+    pub fn update(&mut self, key: K, feature: &[f32]) -> &GalleryEntry {
+        let feature = normalized(feature);
+        let momentum = self.momentum;
+        self.entries
+            .entry(key)
+            .and_modify(|e| {
+                if e.feature.len() != feature.len() {
+                    e.feature = feature.clone();
+                    e.count = 1;
+                    return;
+                }
+                let w = momentum.unwrap_or(1.0 / (e.count + 1) as f32);
+                e.feature
+                    .iter_mut()
+                    .zip(feature.iter())
+                    .for_each(|(m, f)| *m += w * (f - *m));
+                normalize(&mut e.feature);
+                e.count = e.count.saturating_add(1);
+            })
+            .or_insert_with(|| GalleryEntry {
+                feature: feature.clone(),
+                count: 1,
+            })
+    }
+
+    pub fn get(&self, key: &K) -> Option<&GalleryEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<GalleryEntry> {
+        self.entries.remove(key)
+    }
+
+    pub fn retain<F: FnMut(&K, &GalleryEntry) -> bool>(&mut self, mut f: F) {
+        self.entries.retain(|k, v| f(k, v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &GalleryEntry)> {
+        self.entries.iter()
+    }
+
+    /// The `k` closest entries sorted by distance, entries of another dimension are
+    /// skipped.
+    pub fn nearest(&self, query: &[f32], k: usize) -> Vec<Match<K>> {
+        let query = normalized(query);
+        let mut matches = self
+            .entries
+            .iter()
+            .filter_map(|(key, e)| {
+                Some(Match {
+                    key: key.clone(),
+                    distance: self.metric.distance(&query, &e.feature)?,
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        matches.truncate(k);
+        matches
+    }
+
+    pub fn nearest_within(&self, query: &[f32], max_distance: f32) -> Option<Match<K>> {
+        self.nearest(query, 1)
+            .into_iter()
+            .find(|m| m.distance <= max_distance)
+    }
+}
+
+impl UserMeta {
+    /// Reads an output layer of `NVDSINFER_TENSOR_OUTPUT_META` user meta as an
+    /// embedding: `layer_name`, or the first output layer. Only float layers are
+    /// supported.
+    pub fn tensor_output_embedding(
+        &self,
+        unique_id: Option<u32>,
+        layer_name: Option<&str>,
+    ) -> Option<Vec<f32>> {
+        let meta = self.tensor_output_meta()?;
+        if unique_id.is_some_and(|id| id != meta.unique_id()) {
+            return None;
+        }
+        let index = meta
+            .output_layers_info()
+            .iter()
+            .position(|l| match layer_name {
+                Some(name) => l.layer_name().as_str() == name,
+                None => !l.is_input(),
+            })?;
+        meta.tensor::<f32>(index).ok().map(|t| t.to_vec())
+    }
+}
+
+impl ObjectMeta {
+    /// Embedding from the tensor output meta attached to this object by a secondary
+    /// nvinfer with `output-tensor-meta=1`, see [`UserMeta::tensor_output_embedding`].
+    ///
+    /// The result can be set with `EventMsgMetaBuilder::embedding_vector`.
+    pub fn tensor_output_embedding(
+        &self,
+        unique_id: Option<u32>,
+        layer_name: Option<&str>,
+    ) -> Option<Vec<f32>> {
+        self.obj_user_meta_list()?
+            .iter()
+            .find_map(|m| m.tensor_output_embedding(unique_id, layer_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn metrics() {
+        let a = [3.0, 4.0];
+        let b = [4.0, -3.0];
+        assert_close(l2_norm(&a), 5.0);
+        assert_eq!(normalized(&a), [0.6, 0.8]);
+        assert_eq!(normalized(&[0.0, 0.0]), [0.0, 0.0]);
+        assert_eq!(dot(&a, &b), Some(0.0));
+
+        assert_close(cosine_similarity(&a, &a).unwrap(), 1.0);
+        assert_close(cosine_distance(&a, &b).unwrap(), 1.0);
+        assert_close(cosine_distance(&a, &[-3.0, -4.0]).unwrap(), 2.0);
+        assert_close(Metric::Cosine.distance(&a, &[6.0, 8.0]).unwrap(), 0.0);
+        assert_close(Metric::L2.distance(&a, &b).unwrap(), 50f32.sqrt());
+        assert_close(Metric::L2.distance(&a, &a).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn zero_norm() {
+        let zero = [0.0, 0.0];
+        assert_eq!(cosine_similarity(&zero, &[1.0, 0.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&zero, &zero), Some(0.0));
+        assert_eq!(Metric::Cosine.distance(&zero, &[1.0, 0.0]), Some(1.0));
+        assert_eq!(Metric::L2.distance(&zero, &[3.0, 4.0]), Some(5.0));
+    }
+
+    #[test]
+    fn mismatched_dimensions() {
+        let a = [1.0, 0.0];
+        let b = [1.0, 0.0, 0.0];
+        assert_eq!(dot(&a, &b), None);
+        assert_eq!(cosine_similarity(&a, &b), None);
+        assert_eq!(Metric::Cosine.distance(&a, &b), None);
+        assert_eq!(Metric::L2.distance(&a, &b), None);
+        assert_eq!(Metric::L2.distance(&[], &[]), Some(0.0));
+    }
+
+    #[test]
+    fn gallery_update() {
+        let mut gallery = Gallery::new(Metric::Cosine);
+        assert!(gallery.is_empty());
+        let entry = gallery.update(1, &[2.0, 0.0]);
+        assert_eq!(entry.feature(), [1.0, 0.0]);
+        assert_eq!(entry.count(), 1);
+
+        // Running mean of the normalized features.
+        let entry = gallery.update(1, &[0.0, 5.0]);
+        assert_close(entry.feature()[0], 0.5f32.sqrt());
+        assert_close(entry.feature()[1], 0.5f32.sqrt());
+        assert_eq!(entry.count(), 2);
+
+        let entry = gallery.update(1, &[0.0, 0.0, 1.0]);
+        assert_eq!(entry.feature(), [0.0, 0.0, 1.0]);
+        assert_eq!(entry.count(), 1);
+
+        let mut gallery = Gallery::new(Metric::Cosine).with_momentum(0.5);
+        gallery.update(1, &[1.0, 0.0]);
+        gallery.update(1, &[0.0, 1.0]);
+        let entry = gallery.update(1, &[0.0, 1.0]);
+        let expected = normalized(&[0.5f32.sqrt() / 2.0, 0.5f32.sqrt() / 2.0 + 0.5]);
+        assert_close(entry.feature()[0], expected[0]);
+        assert_close(entry.feature()[1], expected[1]);
+        assert_eq!(entry.count(), 3);
+    }
+
+    #[test]
+    fn gallery_eviction() {
+        let mut gallery = Gallery::new(Metric::L2);
+        for key in 0..4u64 {
+            gallery.update(key, &[key as f32, 1.0]);
+        }
+        assert_eq!(gallery.len(), 4);
+        assert_eq!(gallery.remove(&0).unwrap().feature(), [0.0, 1.0]);
+        assert!(gallery.remove(&0).is_none());
+        gallery.retain(|key, _| key % 2 == 1);
+        let mut keys = gallery.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, [1, 3]);
+        assert!(gallery.get(&2).is_none());
+    }
+
+    #[test]
+    fn gallery_match() {
+        let mut gallery = Gallery::new(Metric::Cosine);
+        gallery.update("a", &[1.0, 0.0]);
+        gallery.update("b", &[1.0, 1.0]);
+        gallery.update("c", &[0.0, 1.0]);
+        gallery.update("d", &[1.0, 0.0, 0.0]);
+
+        let matches = gallery.nearest(&[2.0, 0.2], 3);
+        let keys = matches.iter().map(|m| m.key).collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "b", "c"]);
+        assert!(matches.windows(2).all(|m| m[0].distance <= m[1].distance));
+        assert_eq!(gallery.nearest(&[2.0, 0.2], 1).len(), 1);
+        assert_eq!(gallery.nearest(&[1.0, 0.0, 0.0], 5)[0].key, "d");
+        assert_eq!(gallery.nearest(&[1.0, 0.0, 0.0], 5).len(), 1);
+        assert!(gallery.nearest(&[1.0], 5).is_empty());
+
+        assert_eq!(gallery.nearest_within(&[0.1, 1.0], 0.1).unwrap().key, "c");
+        assert!(gallery.nearest_within(&[-1.0, -1.0], 1.0).is_none());
+    }
+}
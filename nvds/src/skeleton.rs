@@ -0,0 +1,622 @@
+//! Body poses with named keypoints, drawn with nvdsosd lines and circles and attached
+//! to event messages as `NvDsEventMsgMeta::pose`.
+
+use crate::meta::osd;
+use crate::meta::{BatchMetaExt, DisplayMetaBuilder, FrameMeta};
+
+const COCO_17_KEYPOINTS: [&str; 17] = [
+    "nose",
+    "left_eye",
+    "right_eye",
+    "left_ear",
+    "right_ear",
+    "left_shoulder",
+    "right_shoulder",
+    "left_elbow",
+    "right_elbow",
+    "left_wrist",
+    "right_wrist",
+    "left_hip",
+    "right_hip",
+    "left_knee",
+    "right_knee",
+    "left_ankle",
+    "right_ankle",
+];
+
+const COCO_17_EDGES: [(usize, usize); 19] = [
+    (15, 13),
+    (13, 11),
+    (16, 14),
+    (14, 12),
+    (11, 12),
+    (5, 11),
+    (6, 12),
+    (5, 6),
+    (5, 7),
+    (6, 8),
+    (7, 9),
+    (8, 10),
+    (1, 2),
+    (0, 1),
+    (0, 2),
+    (1, 3),
+    (2, 4),
+    (3, 5),
+    (4, 6),
+];
+
+const BODY_POSE_18_KEYPOINTS: [&str; 18] = [
+    "nose",
+    "neck",
+    "right_shoulder",
+    "right_elbow",
+    "right_wrist",
+    "left_shoulder",
+    "left_elbow",
+    "left_wrist",
+    "right_hip",
+    "right_knee",
+    "right_ankle",
+    "left_hip",
+    "left_knee",
+    "left_ankle",
+    "right_eye",
+    "left_eye",
+    "right_ear",
+    "left_ear",
+];
+
+const BODY_POSE_18_EDGES: [(usize, usize); 17] = [
+    (1, 2),
+    (1, 5),
+    (2, 3),
+    (3, 4),
+    (5, 6),
+    (6, 7),
+    (1, 8),
+    (8, 9),
+    (9, 10),
+    (1, 11),
+    (11, 12),
+    (12, 13),
+    (1, 0),
+    (0, 14),
+    (14, 16),
+    (0, 15),
+    (15, 17),
+];
+
+const BODY_POSE_34_KEYPOINTS: [&str; 34] = [
+    "pelvis",
+    "left_hip",
+    "right_hip",
+    "torso",
+    "left_knee",
+    "right_knee",
+    "neck",
+    "left_ankle",
+    "right_ankle",
+    "left_big_toe",
+    "right_big_toe",
+    "left_small_toe",
+    "right_small_toe",
+    "left_heel",
+    "right_heel",
+    "nose",
+    "left_eye",
+    "right_eye",
+    "left_ear",
+    "right_ear",
+    "left_shoulder",
+    "right_shoulder",
+    "left_elbow",
+    "right_elbow",
+    "left_wrist",
+    "right_wrist",
+    "left_pinky_knuckle",
+    "right_pinky_knuckle",
+    "left_middle_tip",
+    "right_middle_tip",
+    "left_index_knuckle",
+    "right_index_knuckle",
+    "left_thumb_tip",
+    "right_thumb_tip",
+];
+
+const BODY_POSE_34_EDGES: [(usize, usize); 33] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (1, 4),
+    (2, 5),
+    (3, 6),
+    (4, 7),
+    (5, 8),
+    (7, 9),
+    (8, 10),
+    (7, 11),
+    (8, 12),
+    (7, 13),
+    (8, 14),
+    (6, 15),
+    (15, 16),
+    (15, 17),
+    (16, 18),
+    (17, 19),
+    (6, 20),
+    (6, 21),
+    (20, 22),
+    (21, 23),
+    (22, 24),
+    (23, 25),
+    (24, 26),
+    (25, 27),
+    (24, 28),
+    (25, 29),
+    (24, 30),
+    (25, 31),
+    (24, 32),
+    (25, 33),
+];
+
+/// Keypoint layout of a pose model.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Topology {
+    /// COCO keypoints, e.g. YOLOv8-pose or TRTPose.
+    Coco17,
+    /// BodyPoseNet, OpenPose ordering with a neck keypoint.
+    BodyPose18,
+    /// BodyPose3DNet.
+    BodyPose34,
+}
+
+impl Topology {
+    pub fn keypoint_names(&self) -> &'static [&'static str] {
+        match self {
+            Topology::Coco17 => &COCO_17_KEYPOINTS,
+            Topology::BodyPose18 => &BODY_POSE_18_KEYPOINTS,
+            Topology::BodyPose34 => &BODY_POSE_34_KEYPOINTS,
+        }
+    }
+
+    /// Pairs of keypoint indices connected by a bone.
+    pub fn edges(&self) -> &'static [(usize, usize)] {
+        match self {
+            Topology::Coco17 => &COCO_17_EDGES,
+            Topology::BodyPose18 => &BODY_POSE_18_EDGES,
+            Topology::BodyPose34 => &BODY_POSE_34_EDGES,
+        }
+    }
+
+    pub fn num_keypoints(&self) -> usize {
+        self.keypoint_names().len()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.keypoint_names().iter().position(|n| *n == name)
+    }
+
+    pub fn from_num_keypoints(num_keypoints: usize) -> Option<Topology> {
+        [Topology::Coco17, Topology::BodyPose18, Topology::BodyPose34]
+            .into_iter()
+            .find(|t| t.num_keypoints() == num_keypoints)
+    }
+}
+
+/// Value of `NvDsJoints::pose_type`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PoseType {
+    #[default]
+    Pose2D = 0,
+    Pose25D = 1,
+    Pose3D = 2,
+}
+
+impl PoseType {
+    pub fn from_i32(value: i32) -> Option<PoseType> {
+        match value {
+            0 => Some(PoseType::Pose2D),
+            1 => Some(PoseType::Pose25D),
+            2 => Some(PoseType::Pose3D),
+            _ => None,
+        }
+    }
+}
+
+/// Keypoint in frame coordinates, `z` is only meaningful for 2.5D and 3D poses.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Keypoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub confidence: f32,
+}
+
+impl Keypoint {
+    pub fn new(x: f32, y: f32, confidence: f32) -> Keypoint {
+        Keypoint {
+            x,
+            y,
+            z: 0.0,
+            confidence,
+        }
+    }
+
+    /// Keypoints with a zero confidence are never visible.
+    pub fn is_visible(&self, min_confidence: f32) -> bool {
+        self.confidence > 0.0 && self.confidence >= min_confidence
+    }
+}
+
+/// Drawing parameters of [`Skeleton::line_params`] and [`Skeleton::circle_params`].
+#[derive(Clone, Copy, Debug)]
+pub struct SkeletonStyle {
+    /// Bones and keypoints below this confidence are not drawn.
+    pub min_confidence: f32,
+    pub line_width: u32,
+    pub line_color: osd::ColorParams,
+    /// No circles are drawn when 0.
+    pub radius: u32,
+    pub point_color: osd::ColorParams,
+}
+
+impl Default for SkeletonStyle {
+    fn default() -> Self {
+        SkeletonStyle {
+            min_confidence: 0.3,
+            line_width: 2,
+            line_color: osd::ColorParams::new(0.0, 1.0, 0.0, 1.0),
+            radius: 3,
+            point_color: osd::ColorParams::new(1.0, 0.0, 0.0, 1.0),
+        }
+    }
+}
+
+fn to_pixel(v: f32) -> u32 {
+    v.max(0.0).round() as _
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Skeleton {
+    topology: Topology,
+    pose_type: PoseType,
+    keypoints: Vec<Keypoint>,
+}
+
+impl Skeleton {
+    /// Returns `None` if the number of keypoints does not match the topology.
+    pub fn new(topology: Topology, keypoints: Vec<Keypoint>) -> Option<Skeleton> {
+        (keypoints.len() == topology.num_keypoints()).then_some(Skeleton {
+            topology,
+            pose_type: PoseType::Pose2D,
+            keypoints,
+        })
+    }
+
+    /// Builds a 2D skeleton from `[x, y, confidence]` triplets, the usual layout of pose
+    /// model outputs.
+    pub fn from_xyc(topology: Topology, values: &[f32]) -> Option<Skeleton> {
+        if values.len() != topology.num_keypoints() * 3 {
+            return None;
+        }
+        let keypoints = values
+            .chunks_exact(3)
+            .map(|v| Keypoint::new(v[0], v[1], v[2]))
+            .collect();
+        Self::new(topology, keypoints)
+    }
+
+    pub fn with_pose_type(mut self, pose_type: PoseType) -> Self {
+        self.pose_type = pose_type;
+        self
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn pose_type(&self) -> PoseType {
+        self.pose_type
+    }
+
+    pub fn keypoints(&self) -> &[Keypoint] {
+        &self.keypoints
+    }
+
+    pub fn keypoints_mut(&mut self) -> &mut [Keypoint] {
+        &mut self.keypoints
+    }
+
+    pub fn keypoint(&self, name: &str) -> Option<&Keypoint> {
+        self.keypoints.get(self.topology.index_of(name)?)
+    }
+
+    /// Resets keypoints below `min_confidence` to the default keypoint.
+    pub fn filter_confidence(&mut self, min_confidence: f32) {
+        self.keypoints
+            .iter_mut()
+            .filter(|k| !k.is_visible(min_confidence))
+            .for_each(|k| *k = Keypoint::default());
+    }
+
+    /// Maps keypoints from model input coordinates to frame coordinates.
+    pub fn transform(&mut self, scale_x: f32, scale_y: f32, offset_x: f32, offset_y: f32) {
+        self.keypoints.iter_mut().for_each(|k| {
+            k.x = k.x * scale_x + offset_x;
+            k.y = k.y * scale_y + offset_y;
+        });
+    }
+
+    /// Visible keypoints with their names.
+    pub fn visible(&self, min_confidence: f32) -> impl Iterator<Item = (&'static str, &Keypoint)> {
+        self.topology
+            .keypoint_names()
+            .iter()
+            .copied()
+            .zip(self.keypoints.iter())
+            .filter(move |(_, k)| k.is_visible(min_confidence))
+    }
+
+    /// Bones whose both ends are visible.
+    pub fn bones(&self, min_confidence: f32) -> impl Iterator<Item = (&Keypoint, &Keypoint)> {
+        self.topology
+            .edges()
+            .iter()
+            .map(|&(a, b)| (&self.keypoints[a], &self.keypoints[b]))
+            .filter(move |(a, b)| a.is_visible(min_confidence) && b.is_visible(min_confidence))
+    }
+
+    /// `(left, top, width, height)` of the visible keypoints.
+    pub fn bbox(&self, min_confidence: f32) -> Option<(f32, f32, f32, f32)> {
+        let (left, top, right, bottom) = self.visible(min_confidence).fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(l, t, r, b), (_, k)| (l.min(k.x), t.min(k.y), r.max(k.x), b.max(k.y)),
+        );
+        (left <= right).then_some((left, top, right - left, bottom - top))
+    }
+
+    pub fn line_params(&self, style: &SkeletonStyle) -> Vec<osd::LineParamsBuilder> {
+        self.bones(style.min_confidence)
+            .map(|(a, b)| {
+                osd::LineParamsBuilder::new()
+                    .x1(to_pixel(a.x))
+                    .y1(to_pixel(a.y))
+                    .x2(to_pixel(b.x))
+                    .y2(to_pixel(b.y))
+                    .line_width(style.line_width)
+                    .line_color(style.line_color)
+            })
+            .collect()
+    }
+
+    pub fn circle_params(&self, style: &SkeletonStyle) -> Vec<osd::CircleParamsBuilder> {
+        if style.radius == 0 {
+            return Vec::new();
+        }
+        self.visible(style.min_confidence)
+            .map(|(_, k)| {
+                osd::CircleParamsBuilder::new()
+                    .xc(to_pixel(k.x))
+                    .yc(to_pixel(k.y))
+                    .radius(style.radius)
+                    .circle_color(style.point_color)
+                    .bg_color(Some(style.point_color))
+            })
+            .collect()
+    }
+
+    /// Draws the skeleton on the frame, see [`attach_skeletons`].
+    pub fn attach<BM: BatchMetaExt>(
+        &self,
+        batch_meta: &BM,
+        frame_meta: &FrameMeta,
+        style: &SkeletonStyle,
+    ) -> bool {
+        attach_skeletons(std::iter::once(self), batch_meta, frame_meta, style)
+    }
+
+    /// Joints for [`EventMsgMetaBuilder::pose_joints`](crate::meta::schema::EventMsgMetaBuilder::pose_joints)
+    /// along with [`PoseType`], keypoints filtered out are kept with a zero confidence so
+    /// that indices still match the topology.
+    #[cfg(feature = "v6_2")]
+    pub fn joints(&self) -> Vec<crate::meta::schema::Joint> {
+        self.keypoints
+            .iter()
+            .map(|k| crate::meta::schema::Joint::new(k.x, k.y, k.z, k.confidence))
+            .collect()
+    }
+
+    /// Returns `None` if the number of joints does not match the topology.
+    #[cfg(feature = "v6_2")]
+    pub fn from_joints(
+        topology: Topology,
+        joints: &crate::meta::schema::Joints,
+    ) -> Option<Skeleton> {
+        let keypoints = joints
+            .joints()
+            .map(|j| Keypoint {
+                x: j.x(),
+                y: j.y(),
+                z: j.z(),
+                confidence: j.confidence(),
+            })
+            .collect();
+        Some(
+            Self::new(topology, keypoints)?
+                .with_pose_type(PoseType::from_i32(joints.pose_type()).unwrap_or_default()),
+        )
+    }
+}
+
+/// Draws the skeletons of a frame, splitting them across as many display metas as
+/// needed. Returns false if a display meta could not be acquired from the pool.
+pub fn attach_skeletons<'s, BM: BatchMetaExt>(
+    skeletons: impl IntoIterator<Item = &'s Skeleton>,
+    batch_meta: &BM,
+    frame_meta: &FrameMeta,
+    style: &SkeletonStyle,
+) -> bool {
+    let mut lines = Vec::new();
+    let mut circles = Vec::new();
+    for skeleton in skeletons {
+        lines.extend(skeleton.line_params(style));
+        circles.extend(skeleton.circle_params(style));
+    }
+
+    let max = nvidia_deepstream_sys::MAX_ELEMENTS_IN_DISPLAY_META as usize;
+    for (lines, circles) in display_meta_chunks(&mut lines, &mut circles, max) {
+        match DisplayMetaBuilder::new()
+            .line_params(lines)
+            .circle_params(circles)
+            .build(batch_meta)
+        {
+            Some(display_meta) => frame_meta.add_display_meta(display_meta),
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Splits lines and circles into chunks of at most `max` elements, one pair per
+/// display meta.
+fn display_meta_chunks<'a, L, C>(
+    lines: &'a mut [L],
+    circles: &'a mut [C],
+    max: usize,
+) -> impl Iterator<Item = (&'a mut [L], &'a mut [C])> {
+    let chunks = lines.len().max(circles.len()).div_ceil(max);
+    let mut lines = lines.chunks_mut(max);
+    let mut circles = circles.chunks_mut(max);
+    (0..chunks).map(move |_| {
+        (
+            lines.next().unwrap_or_default(),
+            circles.next().unwrap_or_default(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGIES: [Topology; 3] =
+        [Topology::Coco17, Topology::BodyPose18, Topology::BodyPose34];
+
+    #[test]
+    fn topologies() {
+        for topology in TOPOLOGIES {
+            let n = topology.num_keypoints();
+            let mut connected = vec![false; n];
+            connected[0] = true;
+            for &(a, b) in topology.edges() {
+                assert!(a < n && b < n && a != b, "{:?} {:?}", topology, (a, b));
+                let duplicates = topology
+                    .edges()
+                    .iter()
+                    .filter(|e| **e == (a, b) || **e == (b, a))
+                    .count();
+                assert_eq!(duplicates, 1, "{:?} {:?}", topology, (a, b));
+            }
+            // Every keypoint is reachable from the first one.
+            for _ in 0..n {
+                for &(a, b) in topology.edges() {
+                    let c = connected[a] || connected[b];
+                    connected[a] = c;
+                    connected[b] = c;
+                }
+            }
+            assert!(connected.iter().all(|c| *c), "{:?}", topology);
+
+            for (i, name) in topology.keypoint_names().iter().enumerate() {
+                assert_eq!(topology.index_of(name), Some(i));
+            }
+            assert_eq!(Topology::from_num_keypoints(n), Some(topology));
+        }
+        assert_eq!(Topology::Coco17.num_keypoints(), 17);
+        assert_eq!(Topology::BodyPose18.num_keypoints(), 18);
+        assert_eq!(Topology::BodyPose34.num_keypoints(), 34);
+        // The body pose skeletons are trees, COCO links the eyes and ears in cycles.
+        assert_eq!(Topology::Coco17.edges().len(), 19);
+        assert_eq!(Topology::BodyPose18.edges().len(), 17);
+        assert_eq!(Topology::BodyPose34.edges().len(), 33);
+        assert_eq!(Topology::BodyPose18.index_of("neck"), Some(1));
+        assert_eq!(Topology::Coco17.index_of("neck"), None);
+        assert_eq!(Topology::from_num_keypoints(16), None);
+    }
+
+    fn skeleton() -> Skeleton {
+        // Nose hidden, left arm below the confidence threshold.
+        let mut values = Vec::new();
+        for i in 0..17 {
+            let confidence = match i {
+                0 => 0.0,
+                5 | 7 | 9 => 0.2,
+                _ => 0.9,
+            };
+            values.extend([i as f32 * 10.0, 100.0 + i as f32, confidence]);
+        }
+        Skeleton::from_xyc(Topology::Coco17, &values).unwrap()
+    }
+
+    #[test]
+    fn keypoints() {
+        let s = skeleton();
+        assert!(Skeleton::from_xyc(Topology::Coco17, &[0.0; 50]).is_none());
+        assert!(Skeleton::new(Topology::BodyPose18, s.keypoints().to_vec()).is_none());
+        assert_eq!(
+            s.keypoint("left_eye"),
+            Some(&Keypoint::new(10.0, 101.0, 0.9))
+        );
+        assert_eq!(s.keypoint("neck"), None);
+        assert_eq!(s.visible(0.3).count(), 13);
+        assert_eq!(s.visible(0.0).count(), 16);
+        assert_eq!(s.visible(0.3).next().unwrap().0, "left_eye");
+    }
+
+    #[test]
+    fn low_confidence() {
+        let mut s = skeleton();
+        assert_eq!(s.bbox(0.3), Some((10.0, 101.0, 150.0, 15.0)));
+        assert_eq!(s.bbox(0.1), Some((10.0, 101.0, 150.0, 15.0)));
+        assert_eq!(s.bbox(0.95), None);
+
+        // Bones of the nose or the left arm are dropped.
+        let bones = s.bones(0.3).collect::<Vec<_>>();
+        assert_eq!(bones.len(), 19 - 2 - 5);
+        assert!(bones
+            .iter()
+            .all(|(a, b)| a.confidence > 0.3 && b.confidence > 0.3));
+        assert_eq!(s.bones(0.0).count(), 19 - 2);
+
+        let style = SkeletonStyle::default();
+        assert_eq!(s.line_params(&style).len(), 12);
+        assert_eq!(s.circle_params(&style).len(), 13);
+        let style = SkeletonStyle {
+            radius: 0,
+            ..Default::default()
+        };
+        assert!(s.circle_params(&style).is_empty());
+
+        s.filter_confidence(0.3);
+        assert_eq!(s.keypoints()[5], Keypoint::default());
+        assert_eq!(s.visible(0.0).count(), 13);
+        s.transform(2.0, 0.5, 1.0, -50.0);
+        assert_eq!(s.bbox(0.3), Some((21.0, 0.5, 300.0, 7.5)));
+    }
+
+    #[test]
+    fn chunks() {
+        let chunks = |lines: usize, circles: usize| {
+            let mut lines = vec![0; lines];
+            let mut circles = vec![0; circles];
+            display_meta_chunks(&mut lines, &mut circles, 4)
+                .map(|(l, c)| (l.len(), c.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(chunks(0, 0), []);
+        assert_eq!(chunks(4, 3), [(4, 3)]);
+        assert_eq!(chunks(9, 5), [(4, 4), (4, 1), (1, 0)]);
+        assert_eq!(chunks(2, 8), [(2, 4), (0, 4)]);
+    }
+}
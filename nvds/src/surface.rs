@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 
 use crate::mem::NvdsBox;
-use crate::WrapperExt;
+use crate::{Error, WrapperExt};
 use std::ptr::{null_mut, NonNull};
 
 #[repr(u32)]
@@ -435,9 +435,12 @@ impl crate::mem::NvdsDrop for Surface {
 }
 
 impl Surface {
-    pub fn create(batch_size: u32, params: &CreateParams) -> Result<Vec<NvdsBox<Surface>>, i32> {
+    pub fn create(batch_size: u32, params: &CreateParams) -> Result<Vec<NvdsBox<Surface>>, Error> {
         if batch_size < 1 {
-            return Err(-1);
+            return Err(Error::Surface {
+                function: "NvBufSurfaceCreate",
+                code: -1,
+            });
         }
 
         unsafe {
@@ -446,7 +449,10 @@ impl Surface {
             let mut r = Vec::<NvdsBox<Surface>>::with_capacity(batch_size as _);
 
             if buf.capacity() < batch_size as _ || r.capacity() < batch_size as _ {
-                return Err(-1);
+                return Err(Error::Surface {
+                    function: "NvBufSurfaceCreate",
+                    code: -1,
+                });
             }
 
             buf.set_len(batch_size as _);
@@ -463,7 +469,10 @@ impl Surface {
                 }
                 Ok(r)
             } else {
-                Err(result)
+                Err(Error::Surface {
+                    function: "NvBufSurfaceCreate",
+                    code: result,
+                })
             }
         }
     }
@@ -471,9 +480,12 @@ impl Surface {
     pub fn allocate(
         batch_size: u32,
         params_ext: &AllocateParams,
-    ) -> Result<Vec<NvdsBox<Surface>>, i32> {
+    ) -> Result<Vec<NvdsBox<Surface>>, Error> {
         if batch_size < 1 {
-            return Err(-1);
+            return Err(Error::Surface {
+                function: "NvBufSurfaceAllocate",
+                code: -1,
+            });
         }
 
         unsafe {
@@ -482,7 +494,10 @@ impl Surface {
             let mut r = Vec::<NvdsBox<Surface>>::with_capacity(batch_size as _);
 
             if buf.capacity() < batch_size as _ || r.capacity() < batch_size as _ {
-                return Err(-1);
+                return Err(Error::Surface {
+                    function: "NvBufSurfaceAllocate",
+                    code: -1,
+                });
             }
 
             buf.set_len(batch_size as _);
@@ -499,46 +514,58 @@ impl Surface {
                 }
                 Ok(r)
             } else {
-                Err(ret)
+                Err(Error::Surface {
+                    function: "NvBufSurfaceAllocate",
+                    code: ret,
+                })
             }
         }
     }
 
-    fn to_result(r: i32) -> Result<(), i32> {
-        if r == 0 {
+    fn to_result(function: &'static str, code: i32) -> Result<(), Error> {
+        if code == 0 {
             Ok(())
         } else {
-            Err(r)
+            Err(Error::Surface { function, code })
         }
     }
 
-    pub fn map(mut self, index: u32, plane: u32, mem_type: MemType) -> Result<(), i32> {
+    pub fn map(mut self, index: u32, plane: u32, mem_type: MemType) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceMap(
-                self.as_native_type_mut() as _,
-                index as _,
-                plane as _,
-                std::mem::transmute(mem_type),
-            ))
+            Self::to_result(
+                "NvBufSurfaceMap",
+                nvidia_deepstream_sys::NvBufSurfaceMap(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                    plane as _,
+                    std::mem::transmute(mem_type),
+                ),
+            )
         }
     }
 
-    pub fn unmap(mut self, index: u32, plane: u32) -> Result<(), i32> {
+    pub fn unmap(mut self, index: u32, plane: u32) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceUnMap(
-                self.as_native_type_mut() as _,
-                index as _,
-                plane as _,
-            ))
+            Self::to_result(
+                "NvBufSurfaceUnMap",
+                nvidia_deepstream_sys::NvBufSurfaceUnMap(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                    plane as _,
+                ),
+            )
         }
     }
 
-    pub fn copy(src: &Surface, dst: &mut Surface) -> Result<(), i32> {
+    pub fn copy(src: &Surface, dst: &mut Surface) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceCopy(
-                src.as_native_type_ref() as *const _ as _,
-                dst.as_native_type_mut() as _,
-            ))
+            Self::to_result(
+                "NvBufSurfaceCopy",
+                nvidia_deepstream_sys::NvBufSurfaceCopy(
+                    src.as_native_type_ref() as *const _ as _,
+                    dst.as_native_type_mut() as _,
+                ),
+            )
         }
     }
 
@@ -549,15 +576,18 @@ impl Surface {
         out_width: u32,
         out_height: u32,
         ptr: *mut (),
-    ) -> Result<(), i32> {
-        Self::to_result(nvidia_deepstream_sys::NvBufSurface2Raw(
-            self.as_native_type_ref() as *const _ as _,
-            index,
-            plane,
-            out_width,
-            out_height,
-            ptr as _,
-        ))
+    ) -> Result<(), Error> {
+        Self::to_result(
+            "NvBufSurface2Raw",
+            nvidia_deepstream_sys::NvBufSurface2Raw(
+                self.as_native_type_ref() as *const _ as _,
+                index,
+                plane,
+                out_width,
+                out_height,
+                ptr as _,
+            ),
+        )
     }
 
     pub unsafe fn copy_from_raw(
@@ -567,40 +597,49 @@ impl Surface {
         plane: u32,
         in_width: u32,
         in_height: u32,
-    ) -> Result<(), i32> {
-        Self::to_result(nvidia_deepstream_sys::Raw2NvBufSurface(
-            ptr as _,
-            index,
-            plane,
-            in_width,
-            in_height,
-            self.as_native_type_mut() as _,
-        ))
-    }
-
-    pub fn sync_for_cpu(&mut self, index: u32, plane: u32) -> Result<(), i32> {
-        unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceSyncForCpu(
+    ) -> Result<(), Error> {
+        Self::to_result(
+            "Raw2NvBufSurface",
+            nvidia_deepstream_sys::Raw2NvBufSurface(
+                ptr as _,
+                index,
+                plane,
+                in_width,
+                in_height,
                 self.as_native_type_mut() as _,
-                index as _,
-                plane as _,
-            ))
+            ),
+        )
+    }
+
+    pub fn sync_for_cpu(&mut self, index: u32, plane: u32) -> Result<(), Error> {
+        unsafe {
+            Self::to_result(
+                "NvBufSurfaceSyncForCpu",
+                nvidia_deepstream_sys::NvBufSurfaceSyncForCpu(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                    plane as _,
+                ),
+            )
         }
     }
 
-    pub fn sync_for_device(&mut self, index: u32, plane: u32) -> Result<(), i32> {
+    pub fn sync_for_device(&mut self, index: u32, plane: u32) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceSyncForDevice(
-                self.as_native_type_mut() as _,
-                index as _,
-                plane as _,
-            ))
+            Self::to_result(
+                "NvBufSurfaceSyncForDevice",
+                nvidia_deepstream_sys::NvBufSurfaceSyncForDevice(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                    plane as _,
+                ),
+            )
         }
     }
 
-    pub fn from_fd(dmabuf_fd: i32) -> Result<*mut Surface, i32> {
+    pub fn from_fd(dmabuf_fd: i32) -> Result<*mut Surface, Error> {
         let mut buffer: *mut ::std::os::raw::c_void = null_mut();
-        Self::to_result(unsafe {
+        Self::to_result("NvBufSurfaceFromFd", unsafe {
             nvidia_deepstream_sys::NvBufSurfaceFromFd(dmabuf_fd, &mut buffer)
         })
         .map(|_| {
@@ -610,32 +649,41 @@ impl Surface {
         })
     }
 
-    pub fn memset(&mut self, index: u32, plane: u32, value: u8) -> Result<(), i32> {
+    pub fn memset(&mut self, index: u32, plane: u32, value: u8) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceMemSet(
-                self.as_native_type_mut() as _,
-                index as _,
-                plane as _,
-                value,
-            ))
+            Self::to_result(
+                "NvBufSurfaceMemSet",
+                nvidia_deepstream_sys::NvBufSurfaceMemSet(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                    plane as _,
+                    value,
+                ),
+            )
         }
     }
 
-    pub fn map_egl_image(mut self, index: u32) -> Result<(), i32> {
+    pub fn map_egl_image(mut self, index: u32) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceMapEglImage(
-                self.as_native_type_mut() as _,
-                index as _,
-            ))
+            Self::to_result(
+                "NvBufSurfaceMapEglImage",
+                nvidia_deepstream_sys::NvBufSurfaceMapEglImage(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                ),
+            )
         }
     }
 
-    pub fn unmap_egl_image(mut self, index: u32) -> Result<(), i32> {
+    pub fn unmap_egl_image(mut self, index: u32) -> Result<(), Error> {
         unsafe {
-            Self::to_result(nvidia_deepstream_sys::NvBufSurfaceUnMapEglImage(
-                self.as_native_type_mut() as _,
-                index as _,
-            ))
+            Self::to_result(
+                "NvBufSurfaceUnMapEglImage",
+                nvidia_deepstream_sys::NvBufSurfaceUnMapEglImage(
+                    self.as_native_type_mut() as _,
+                    index as _,
+                ),
+            )
         }
     }
 
@@ -60,6 +60,20 @@ impl Error {
     }
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::RoiError => write!(f, "invalid ROI"),
+            Error::InvalidParams => write!(f, "invalid parameters"),
+            Error::ExecutionError => write!(f, "execution error"),
+            Error::Unsupported => write!(f, "unsupported transform"),
+            Error::Success => write!(f, "success"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 #[repr(u32)]
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub enum TransformFlag {
@@ -0,0 +1,255 @@
+//! Wall-clock time of frames and RFC 3339 timestamps as used by `NvDsEventMsgMeta::ts`.
+//!
+//! Times are nanoseconds since the UNIX epoch (UTC), like `NvDsFrameMeta::ntp_timestamp`.
+
+use crate::meta::FrameMeta;
+use gstreamer::glib::IsA;
+use gstreamer::prelude::*;
+use std::collections::HashMap;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as _)
+}
+
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Formats seconds and nanoseconds since the epoch with millisecond precision, e.g.
+/// `2023-05-04T12:34:56.789Z`.
+pub(crate) fn format_rfc3339_parts(seconds: i64, nanos: u32) -> String {
+    let days = seconds.div_euclid(86400);
+    let secs = seconds.rem_euclid(86400);
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        y,
+        m,
+        d,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        nanos / 1_000_000
+    )
+}
+
+/// Parses an RFC 3339 date-time with any fraction digits and a `Z` or `±hh:mm`
+/// offset into seconds and nanoseconds since the epoch.
+pub(crate) fn parse_rfc3339_parts(s: &str) -> Option<(i64, u32)> {
+    let b = s.as_bytes();
+    let num = |r: std::ops::Range<usize>| -> Option<i64> { s.get(r)?.parse().ok() };
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || (b[10] | 0x20) != b't' {
+        return None;
+    }
+    let days = days_from_civil(num(0..4)?, num(5..7)? as _, num(8..10)? as _);
+    let mut seconds = days * 86400 + num(11..13)? * 3600 + num(14..16)? * 60 + num(17..19)?;
+
+    let mut p = 19;
+    let mut nanos = 0i64;
+    if b[p] == b'.' {
+        p += 1;
+        let start = p;
+        while p < b.len() && b[p].is_ascii_digit() {
+            p += 1;
+        }
+        let frac = &s[start..p];
+        nanos = frac.get(..9).unwrap_or(frac).parse().ok()?;
+        nanos *= 10i64.pow(9u32.saturating_sub(frac.len() as u32));
+    }
+    match b.get(p)? {
+        b'Z' | b'z' if p + 1 == b.len() => {}
+        b'+' | b'-' if p + 6 == b.len() && b[p + 3] == b':' => {
+            let offset = num(p + 1..p + 3)? * 3600 + num(p + 4..p + 6)? * 60;
+            seconds -= if b[p] == b'+' { offset } else { -offset };
+        }
+        _ => return None,
+    }
+    Some((seconds, nanos as _))
+}
+
+pub fn format_rfc3339(unix_nanos: u64) -> String {
+    format_rfc3339_parts(
+        (unix_nanos / NANOS_PER_SEC) as _,
+        (unix_nanos % NANOS_PER_SEC) as _,
+    )
+}
+
+/// Returns `None` for invalid timestamps and times before the epoch.
+pub fn parse_rfc3339(s: &str) -> Option<u64> {
+    let (seconds, nanos) = parse_rfc3339_parts(s)?;
+    u64::try_from(seconds)
+        .ok()?
+        .checked_mul(NANOS_PER_SEC)?
+        .checked_add(nanos as _)
+}
+
+/// Meaning of `ntp_timestamp` depending on the configuration of nvstreammux and of the
+/// sources.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NtpSyncMode {
+    /// `attach-sys-ts=TRUE`, the system time at which nvstreammux received the frame.
+    SystemTime,
+    /// `attach-sys-ts=FALSE` with RTSP sources set up by
+    /// [`configure_source_for_ntp_sync`](crate::helper::ElementHelperExt::configure_source_for_ntp_sync),
+    /// the capture time computed from RTCP sender reports; 0 until the first report.
+    Rtcp,
+    /// `ntp_timestamp` is ignored and times are derived from `buf_pts` only.
+    Disabled,
+}
+
+impl NtpSyncMode {
+    pub fn from_streammux<E: IsA<gstreamer::Element>>(streammux: &E) -> NtpSyncMode {
+        if streammux.find_property("attach-sys-ts").is_none() {
+            NtpSyncMode::Disabled
+        } else if streammux.property::<bool>("attach-sys-ts") {
+            NtpSyncMode::SystemTime
+        } else {
+            NtpSyncMode::Rtcp
+        }
+    }
+}
+
+/// Derives a consistent wall-clock time for the frames of every source.
+///
+/// The NTP timestamp of a frame is used when available. Otherwise the offset between
+/// NTP timestamps and `buf_pts` last seen for the source is applied to `buf_pts`, or
+/// `buf_pts` is added to the wall-clock time of the pipeline running time 0.
+#[derive(Clone, Debug)]
+pub struct FrameClock {
+    mode: NtpSyncMode,
+    base: u64,
+    offsets: HashMap<u32, i64>,
+}
+
+impl FrameClock {
+    /// `base` is the wall-clock time at which the running time of the pipeline was 0.
+    pub fn new(mode: NtpSyncMode, base: u64) -> FrameClock {
+        FrameClock {
+            mode,
+            base,
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Computes the base from the clock and base time of `pipeline`, which must be
+    /// playing.
+    pub fn from_pipeline<E: IsA<gstreamer::Element>>(
+        mode: NtpSyncMode,
+        pipeline: &E,
+    ) -> Option<FrameClock> {
+        let clock_time = pipeline.clock()?.time()?.nseconds();
+        let base_time = pipeline.base_time()?.nseconds();
+        let running_time = clock_time.saturating_sub(base_time);
+        Some(Self::new(mode, now().saturating_sub(running_time)))
+    }
+
+    pub fn mode(&self) -> NtpSyncMode {
+        self.mode
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// `buf_pts` may be `GST_CLOCK_TIME_NONE`, then the NTP timestamp or the base is
+    /// returned.
+    pub fn wall_clock(&mut self, source_id: u32, ntp_timestamp: u64, buf_pts: u64) -> u64 {
+        let buf_pts = (buf_pts != gstreamer::ffi::GST_CLOCK_TIME_NONE).then_some(buf_pts);
+        if self.mode != NtpSyncMode::Disabled {
+            if ntp_timestamp != 0 {
+                if let Some(pts) = buf_pts {
+                    self.offsets
+                        .insert(source_id, (ntp_timestamp as i64).saturating_sub(pts as _));
+                }
+                return ntp_timestamp;
+            }
+            if let (Some(offset), Some(pts)) = (self.offsets.get(&source_id), buf_pts) {
+                return (pts as i64).saturating_add(*offset).max(0) as _;
+            }
+        }
+        self.base.saturating_add(buf_pts.unwrap_or_default())
+    }
+
+    pub fn frame_wall_clock(&mut self, frame_meta: &FrameMeta) -> u64 {
+        self.wall_clock(
+            frame_meta.source_id(),
+            frame_meta.ntp_timestamp(),
+            frame_meta.buf_pts(),
+        )
+    }
+
+    /// Timestamp for [`EventMsgMetaBuilder::ts`](crate::meta::schema::EventMsgMetaBuilder::ts).
+    pub fn frame_rfc3339(&mut self, frame_meta: &FrameMeta) -> String {
+        format_rfc3339(self.frame_wall_clock(frame_meta))
+    }
+
+    /// Forgets the NTP offset of `source_id`, e.g. when the source is removed or reset.
+    pub fn reset_source(&mut self, source_id: u32) {
+        self.offsets.remove(&source_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: u64 = gstreamer::ffi::GST_CLOCK_TIME_NONE;
+
+    #[test]
+    fn rfc3339() {
+        let t = 1_709_249_398_123_456_789;
+        assert_eq!(format_rfc3339(t), "2024-02-29T23:29:58.123Z");
+        assert_eq!(parse_rfc3339("2024-02-29T23:29:58.123456789Z"), Some(t));
+        assert_eq!(
+            parse_rfc3339("2024-03-01T00:59:58.5+01:30"),
+            Some(1_709_249_398_500_000_000)
+        );
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_rfc3339("2024-02-29 23:29:58Z"), None);
+    }
+
+    #[test]
+    fn wall_clock() {
+        let mut clock = FrameClock::new(NtpSyncMode::Rtcp, 1_000);
+        assert_eq!(clock.wall_clock(0, 0, 10), 1_010);
+        assert_eq!(clock.wall_clock(0, 5_000, 100), 5_000);
+        assert_eq!(clock.wall_clock(0, 0, 200), 5_100);
+        assert_eq!(clock.wall_clock(1, 0, 200), 1_200);
+        clock.reset_source(0);
+        assert_eq!(clock.wall_clock(0, 0, 200), 1_200);
+
+        let mut clock = FrameClock::new(NtpSyncMode::Disabled, 1_000);
+        assert_eq!(clock.wall_clock(0, 5_000, 100), 1_100);
+    }
+
+    #[test]
+    fn wall_clock_without_pts() {
+        let mut clock = FrameClock::new(NtpSyncMode::SystemTime, u64::MAX - 10);
+        assert_eq!(clock.wall_clock(0, 0, NONE), u64::MAX - 10);
+        assert_eq!(clock.wall_clock(0, 0, 100), u64::MAX);
+        assert_eq!(clock.wall_clock(0, 5_000, NONE), 5_000);
+        assert_eq!(clock.wall_clock(0, 0, NONE), u64::MAX - 10);
+        assert_eq!(clock.wall_clock(0, 5_000, 100), 5_000);
+        assert_eq!(clock.wall_clock(0, 0, NONE), u64::MAX - 10);
+        assert_eq!(clock.wall_clock(0, 0, 150), 5_050);
+    }
+}
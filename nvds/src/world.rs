@@ -0,0 +1,610 @@
+//! Ground plane world coordinates of objects and the convex hulls of
+//! `NvDs3DTracking`, from a homography or a camera projection matrix.
+//!
+//! Calibration files use the layout of the nvtracker camera model files, with an
+//! optional image to ground plane homography instead of the projection matrix:
+//!
+//! ```yaml
+//! projectionMatrix_3x4: [996.2, 1210.1, 15.2, -2217.8, 124.5, 20.4, -1256.9, 2426.7, 0.27, 0.05, 0.03, 3.6]
+//! # homography_3x3: [0.1, 0.0, -5.0, 0.0, 0.2, -10.0, 0.0, 0.001, 1.0]
+//! modelInfo: { height: 250.0, radius: 30.0 }
+//! ```
+
+use crate::meta::ObjectMeta;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(std::io::Error),
+    Yaml(serde_yaml::Error),
+    /// Neither a projection matrix nor a homography is set, or the matrix is singular.
+    InvalidMatrix,
+}
+
+impl std::fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::Io(e) => write!(f, "failed to read calibration: {}", e),
+            CalibrationError::Yaml(e) => write!(f, "invalid calibration yaml: {}", e),
+            CalibrationError::InvalidMatrix => write!(f, "invalid calibration matrix"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalibrationError::Io(e) => Some(e),
+            CalibrationError::Yaml(e) => Some(e),
+            CalibrationError::InvalidMatrix => None,
+        }
+    }
+}
+
+/// Bottom center of a bounding box, where the object touches the ground.
+pub fn foot_point(left: f32, top: f32, width: f32, height: f32) -> [f32; 2] {
+    [left + width / 2.0, top + height]
+}
+
+impl ObjectMeta {
+    pub fn foot_point(&self) -> [f32; 2] {
+        let r = self.rect_params();
+        foot_point(r.left(), r.top(), r.width(), r.height())
+    }
+}
+
+fn cross(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+/// Convex hull of `points` in counter-clockwise order (in a y-up frame), without
+/// collinear vertices.
+pub fn convex_hull(points: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<[f32; 2]> = Vec::with_capacity(points.len() + 1);
+    for pass in 0..2 {
+        let start = hull.len();
+        for &p in points.iter() {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+/// Area of a simple polygon.
+pub fn polygon_area(polygon: &[[f32; 2]]) -> f32 {
+    let n = polygon.len();
+    let twice_area: f32 = (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    twice_area.abs() / 2.0
+}
+
+/// Whether `point` is inside or on the border of a convex polygon of either
+/// orientation.
+pub fn convex_polygon_contains(polygon: &[[f32; 2]], point: [f32; 2]) -> bool {
+    let n = polygon.len();
+    if n < 3 {
+        return false;
+    }
+    let mut sign = 0.0f32;
+    for i in 0..n {
+        let c = cross(polygon[i], polygon[(i + 1) % n], point);
+        if c != 0.0 {
+            if sign * c < 0.0 {
+                return false;
+            }
+            sign = c;
+        }
+    }
+    true
+}
+
+#[cfg(feature = "v6_4")]
+impl crate::meta::schema::ConvexHull {
+    fn vertices_f32(&self) -> Vec<[f32; 2]> {
+        self.vertices().map(|[x, y]| [x as f32, y as f32]).collect()
+    }
+
+    pub fn area(&self) -> f32 {
+        polygon_area(&self.vertices_f32())
+    }
+
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        convex_polygon_contains(&self.vertices_f32(), point)
+    }
+}
+
+fn invert_3x3(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor = |r: usize, c: usize| {
+        let (r0, r1) = ((r + 1) % 3, (r + 2) % 3);
+        let (c0, c1) = ((c + 1) % 3, (c + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = (0..3).map(|c| m[0][c] * cofactor(0, c)).sum::<f64>();
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    let mut inv = [[0.0; 3]; 3];
+    for (r, row) in inv.iter_mut().enumerate() {
+        for (c, v) in row.iter_mut().enumerate() {
+            *v = cofactor(c, r) / det;
+        }
+    }
+    Some(inv)
+}
+
+/// Solves `a x = b` with Gaussian elimination and partial pivoting.
+fn solve<const N: usize>(mut a: [[f64; N]; N], mut b: [f64; N]) -> Option<[f64; N]> {
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..N {
+            let f = a[row][col] / pivot_row[col];
+            a[row][col..]
+                .iter_mut()
+                .zip(&pivot_row[col..])
+                .for_each(|(v, p)| *v -= f * p);
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let s: f64 = (row + 1..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - s) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Projective transformation between two planes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Homography([[f64; 3]; 3]);
+
+impl Homography {
+    pub fn new(matrix: [[f64; 3]; 3]) -> Homography {
+        Homography(matrix)
+    }
+
+    pub fn identity() -> Homography {
+        Homography([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Homography mapping each of the `src` points to the `dst` point at the same
+    /// index, e.g. four marked floor points in the image to their floor plan
+    /// coordinates. Returns `None` if three of the points are collinear.
+    pub fn from_points(src: [[f32; 2]; 4], dst: [[f32; 2]; 4]) -> Option<Homography> {
+        let mut a = [[0.0; 8]; 8];
+        let mut b = [0.0; 8];
+        for (i, (s, d)) in src.iter().zip(dst.iter()).enumerate() {
+            let (x, y) = (s[0] as f64, s[1] as f64);
+            let (u, v) = (d[0] as f64, d[1] as f64);
+            a[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y];
+            a[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y];
+            b[2 * i] = u;
+            b[2 * i + 1] = v;
+        }
+        let h = solve(a, b)?;
+        Some(Homography([
+            [h[0], h[1], h[2]],
+            [h[3], h[4], h[5]],
+            [h[6], h[7], 1.0],
+        ]))
+    }
+
+    pub fn matrix(&self) -> &[[f64; 3]; 3] {
+        &self.0
+    }
+
+    pub fn inverse(&self) -> Option<Homography> {
+        invert_3x3(&self.0).map(Homography)
+    }
+
+    /// Returns `None` for points mapped to infinity, e.g. above the horizon.
+    pub fn project(&self, point: [f32; 2]) -> Option<[f32; 2]> {
+        let (x, y) = (point[0] as f64, point[1] as f64);
+        let [u, v, w] = self.0.map(|r| r[0] * x + r[1] * y + r[2]);
+        (w.abs() > f64::EPSILON).then(|| [(u / w) as f32, (v / w) as f32])
+    }
+}
+
+/// 3x4 camera projection matrix `K [R | t]` from world to image coordinates.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ProjectionMatrix([[f64; 4]; 3]);
+
+impl ProjectionMatrix {
+    pub fn new(matrix: [[f64; 4]; 3]) -> ProjectionMatrix {
+        ProjectionMatrix(matrix)
+    }
+
+    /// From the intrinsics `k`, the rotation `r` and the translation `t` of the camera.
+    pub fn from_parts(k: [[f64; 3]; 3], r: [[f64; 3]; 3], t: [f64; 3]) -> ProjectionMatrix {
+        let mut rt = [[0.0; 4]; 3];
+        for (i, row) in rt.iter_mut().enumerate() {
+            *row = [r[i][0], r[i][1], r[i][2], t[i]];
+        }
+        let mut p = [[0.0; 4]; 3];
+        for (i, row) in p.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|m| k[i][m] * rt[m][j]).sum();
+            }
+        }
+        ProjectionMatrix(p)
+    }
+
+    pub fn matrix(&self) -> &[[f64; 4]; 3] {
+        &self.0
+    }
+
+    /// Returns `None` for points behind or at the camera center.
+    pub fn project(&self, point: [f32; 3]) -> Option<[f32; 2]> {
+        let [x, y, z] = point.map(|v| v as f64);
+        let [u, v, w] = self.0.map(|r| r[0] * x + r[1] * y + r[2] * z + r[3]);
+        (w > f64::EPSILON).then(|| [(u / w) as f32, (v / w) as f32])
+    }
+
+    /// Homography from the `z = 0` ground plane to the image.
+    pub fn ground_homography(&self) -> Homography {
+        Homography(self.0.map(|r| [r[0], r[1], r[3]]))
+    }
+}
+
+/// Objects are modeled as vertical cylinders standing on the ground plane, in world
+/// units.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CylinderModel {
+    pub height: f32,
+    pub radius: f32,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct CalibrationFile {
+    #[serde(rename = "projectionMatrix_3x4")]
+    projection_matrix: Option<Vec<f64>>,
+    #[serde(rename = "homography_3x3")]
+    homography: Option<Vec<f64>>,
+    #[serde(rename = "modelInfo")]
+    model: Option<CylinderModel>,
+}
+
+/// `NvDs3DTracking` fields computed from a [`Calibration`].
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct SingleView3d {
+    pub visibility: f32,
+    pub img_feet: [f32; 2],
+    pub world_feet: [f32; 2],
+    pub convex_hull: Vec<[i32; 2]>,
+}
+
+impl SingleView3d {
+    #[cfg(feature = "v6_4")]
+    pub fn from_tracking_3d(tracking: &crate::meta::schema::Tracking3d) -> SingleView3d {
+        SingleView3d {
+            visibility: tracking.visibility(),
+            img_feet: tracking.pt_img_feet(),
+            world_feet: tracking.pt_world_feet(),
+            convex_hull: tracking.convex_hull().vertices().collect(),
+        }
+    }
+
+    /// Sets `has3DTracking` and `singleView3DTracking` of the event.
+    #[cfg(feature = "v6_4")]
+    pub fn apply<'a>(
+        &'a self,
+        builder: crate::meta::schema::EventMsgMetaBuilder<'a>,
+    ) -> crate::meta::schema::EventMsgMetaBuilder<'a> {
+        builder
+            .has_3d_tracking(true)
+            .single_view_3d_tracking(crate::meta::schema::Tracking3d::new(
+                self.visibility,
+                self.img_feet,
+                self.world_feet,
+            ))
+            .convex_hull(&self.convex_hull)
+    }
+}
+
+/// Mapping between image coordinates and the world ground plane of a camera.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Calibration {
+    image_to_world: Homography,
+    world_to_image: Homography,
+    projection: Option<ProjectionMatrix>,
+    model: Option<CylinderModel>,
+}
+
+impl Calibration {
+    /// `image_to_world` maps image points to ground plane coordinates.
+    pub fn from_homography(image_to_world: Homography) -> Result<Calibration, CalibrationError> {
+        Ok(Calibration {
+            image_to_world,
+            world_to_image: image_to_world
+                .inverse()
+                .ok_or(CalibrationError::InvalidMatrix)?,
+            projection: None,
+            model: None,
+        })
+    }
+
+    pub fn from_projection_matrix(
+        projection: ProjectionMatrix,
+    ) -> Result<Calibration, CalibrationError> {
+        let world_to_image = projection.ground_homography();
+        Ok(Calibration {
+            image_to_world: world_to_image
+                .inverse()
+                .ok_or(CalibrationError::InvalidMatrix)?,
+            world_to_image,
+            projection: Some(projection),
+            model: None,
+        })
+    }
+
+    /// The projection matrix takes precedence over the homography.
+    pub fn from_yaml(s: &str) -> Result<Calibration, CalibrationError> {
+        let file: CalibrationFile = serde_yaml::from_str(s).map_err(CalibrationError::Yaml)?;
+        let calibration = match (file.projection_matrix, file.homography) {
+            (Some(p), _) => {
+                let p: [f64; 12] = p.try_into().map_err(|_| CalibrationError::InvalidMatrix)?;
+                Self::from_projection_matrix(ProjectionMatrix([
+                    [p[0], p[1], p[2], p[3]],
+                    [p[4], p[5], p[6], p[7]],
+                    [p[8], p[9], p[10], p[11]],
+                ]))?
+            }
+            (None, Some(h)) => {
+                let h: [f64; 9] = h.try_into().map_err(|_| CalibrationError::InvalidMatrix)?;
+                Self::from_homography(Homography([
+                    [h[0], h[1], h[2]],
+                    [h[3], h[4], h[5]],
+                    [h[6], h[7], h[8]],
+                ]))?
+            }
+            (None, None) => return Err(CalibrationError::InvalidMatrix),
+        };
+        Ok(match file.model {
+            Some(model) => calibration.with_model(model),
+            None => calibration,
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Calibration, CalibrationError> {
+        let s = std::fs::read_to_string(path).map_err(CalibrationError::Io)?;
+        Self::from_yaml(&s)
+    }
+
+    /// Model used by [`Calibration::single_view_3d`] to compute convex hulls.
+    pub fn with_model(mut self, model: CylinderModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn projection(&self) -> Option<&ProjectionMatrix> {
+        self.projection.as_ref()
+    }
+
+    pub fn model(&self) -> Option<&CylinderModel> {
+        self.model.as_ref()
+    }
+
+    pub fn image_to_world(&self, point: [f32; 2]) -> Option<[f32; 2]> {
+        self.image_to_world.project(point)
+    }
+
+    pub fn world_to_image(&self, point: [f32; 2]) -> Option<[f32; 2]> {
+        self.world_to_image.project(point)
+    }
+
+    /// Image convex hull of `model` standing at `world_feet`, approximating its circles
+    /// with `segments` points. Requires a projection matrix.
+    pub fn cylinder_hull(
+        &self,
+        world_feet: [f32; 2],
+        model: &CylinderModel,
+        segments: usize,
+    ) -> Option<Vec<[i32; 2]>> {
+        let projection = self.projection.as_ref()?;
+        let mut points = Vec::with_capacity(segments * 2);
+        for i in 0..segments {
+            let a = i as f32 * std::f32::consts::TAU / segments as f32;
+            let x = world_feet[0] + model.radius * a.cos();
+            let y = world_feet[1] + model.radius * a.sin();
+            points.push(projection.project([x, y, 0.0])?);
+            points.push(projection.project([x, y, model.height])?);
+        }
+        Some(
+            convex_hull(&points)
+                .into_iter()
+                .map(|p| [p[0].round() as i32, p[1].round() as i32])
+                .collect(),
+        )
+    }
+
+    /// 3D tracking fields of an object with the given bounding box. The convex hull is
+    /// the projected cylinder model when a projection matrix and a model are set,
+    /// otherwise the bounding box. Returns `None` if the foot point does not map to the
+    /// ground plane.
+    pub fn single_view_3d(
+        &self,
+        left: f32,
+        top: f32,
+        width: f32,
+        height: f32,
+        visibility: f32,
+    ) -> Option<SingleView3d> {
+        let img_feet = foot_point(left, top, width, height);
+        let world_feet = self.image_to_world(img_feet)?;
+        let convex_hull = self
+            .model
+            .and_then(|model| self.cylinder_hull(world_feet, &model, 16))
+            .unwrap_or_else(|| {
+                let (l, t) = (left.round() as i32, top.round() as i32);
+                let (r, b) = ((left + width).round() as i32, (top + height).round() as i32);
+                vec![[l, t], [r, t], [r, b], [l, b]]
+            });
+        Some(SingleView3d {
+            visibility,
+            img_feet,
+            world_feet,
+            convex_hull,
+        })
+    }
+
+    pub fn object_single_view_3d(
+        &self,
+        object_meta: &ObjectMeta,
+        visibility: f32,
+    ) -> Option<SingleView3d> {
+        let r = object_meta.rect_params();
+        self.single_view_3d(r.left(), r.top(), r.width(), r.height(), visibility)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: [f32; 2], b: [f32; 2]) {
+        assert!(
+            (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn hull() {
+        let points = [
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [1.0, 1.0],
+            [2.0, 2.0],
+            [0.0, 2.0],
+            [1.0, 0.0],
+            [0.0, 0.0],
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(hull, [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]]);
+        assert_eq!(polygon_area(&hull), 4.0);
+        assert!(convex_polygon_contains(&hull, [1.0, 1.0]));
+        assert!(convex_polygon_contains(&hull, [2.0, 1.0]));
+        assert!(!convex_polygon_contains(&hull, [2.5, 1.0]));
+
+        let reversed: Vec<_> = hull.iter().rev().copied().collect();
+        assert!(convex_polygon_contains(&reversed, [0.5, 1.5]));
+        assert_eq!(
+            convex_hull(&[[1.0, 1.0], [0.0, 0.0]]),
+            [[0.0, 0.0], [1.0, 1.0]]
+        );
+        assert!(!convex_polygon_contains(
+            &[[0.0, 0.0], [1.0, 1.0]],
+            [0.5, 0.5]
+        ));
+    }
+
+    #[test]
+    fn homography_from_points() {
+        let src = [
+            [100.0, 400.0],
+            [500.0, 400.0],
+            [450.0, 100.0],
+            [150.0, 100.0],
+        ];
+        let dst = [[0.0, 0.0], [10.0, 0.0], [10.0, 20.0], [0.0, 20.0]];
+        let h = Homography::from_points(src, dst).unwrap();
+        for (s, d) in src.iter().zip(dst.iter()) {
+            assert_near(h.project(*s).unwrap(), *d);
+        }
+        let inverse = h.inverse().unwrap();
+        for (s, d) in src.iter().zip(dst.iter()) {
+            assert_near(inverse.project(*d).unwrap(), *s);
+        }
+
+        let collinear = [[0.0, 0.0], [1.0, 1.0], [2.0, 2.0], [0.0, 5.0]];
+        assert!(Homography::from_points(collinear, dst).is_none());
+    }
+
+    #[test]
+    fn projection_from_parts() {
+        let k = [[1000.0, 0.0, 960.0], [0.0, 1000.0, 540.0], [0.0, 0.0, 1.0]];
+        let r = [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]];
+        let p = ProjectionMatrix::from_parts(k, r, [0.0, 0.0, 10.0]);
+        assert_eq!(p.matrix()[0], [1000.0, 0.0, -960.0, 9600.0]);
+        assert_eq!(p.matrix()[2], [0.0, 0.0, -1.0, 10.0]);
+        assert_near(p.project([0.0, 0.0, 0.0]).unwrap(), [960.0, 540.0]);
+        assert!(p.project([0.0, 0.0, 10.0]).is_none());
+    }
+
+    #[test]
+    fn calibration_from_yaml() {
+        let c = Calibration::from_yaml(
+            "homography_3x3: [0.1, 0.0, -5.0, 0.0, 0.2, -10.0, 0.0, 0.0, 1.0]\n",
+        )
+        .unwrap();
+        assert!(c.projection().is_none() && c.model().is_none());
+        assert_near(c.image_to_world([50.0, 50.0]).unwrap(), [0.0, 0.0]);
+        assert_near(c.world_to_image([1.0, 2.0]).unwrap(), [60.0, 60.0]);
+        let view = c.single_view_3d(40.0, 10.0, 20.0, 40.0, 0.5).unwrap();
+        assert_near(view.world_feet, [0.0, 0.0]);
+        assert_eq!(view.convex_hull, [[40, 10], [60, 10], [60, 50], [40, 50]]);
+
+        let c = Calibration::from_yaml(
+            "projectionMatrix_3x4: [1000.0, 0.0, -960.0, 9600.0, 0.0, -1000.0, -540.0, 5400.0, 0.0, 0.0, -1.0, 10.0]\n\
+             homography_3x3: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]\n\
+             modelInfo: { height: 2.0, radius: 0.5 }\n",
+        )
+        .unwrap();
+        assert!(c.projection().is_some());
+        assert_eq!(
+            c.model(),
+            Some(&CylinderModel {
+                height: 2.0,
+                radius: 0.5
+            })
+        );
+        assert_near(c.image_to_world([960.0, 540.0]).unwrap(), [0.0, 0.0]);
+        let hull = c.cylinder_hull([0.0, 0.0], c.model().unwrap(), 16).unwrap();
+        assert!(hull.len() >= 4);
+        let hull: Vec<_> = hull.iter().map(|p| [p[0] as f32, p[1] as f32]).collect();
+        assert!(convex_polygon_contains(&hull, [960.0, 540.0]));
+
+        for yaml in [
+            "modelInfo: { height: 2.0, radius: 0.5 }\n",
+            "homography_3x3: [1.0, 0.0, 0.0]\n",
+            "homography_3x3: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]\n",
+        ] {
+            assert!(matches!(
+                Calibration::from_yaml(yaml),
+                Err(CalibrationError::InvalidMatrix)
+            ));
+        }
+        assert!(matches!(
+            Calibration::from_yaml("homography_3x3: x"),
+            Err(CalibrationError::Yaml(_))
+        ));
+    }
+}
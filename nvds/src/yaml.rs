@@ -9,23 +9,45 @@ pub enum YamlParserStatus {
     Error = nvidia_deepstream_sys::NvDsYamlParserStatus_NVDS_YAML_PARSER_ERROR as _,
 }
 
+impl YamlParserStatus {
+    pub fn into_result(self) -> Result<(), YamlParserStatus> {
+        match self {
+            YamlParserStatus::Success => Ok(()),
+            e => Err(e),
+        }
+    }
+}
+
+impl std::fmt::Display for YamlParserStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YamlParserStatus::Success => write!(f, "success"),
+            YamlParserStatus::Disabled => write!(f, "group is disabled"),
+            YamlParserStatus::Error => write!(f, "parse error"),
+        }
+    }
+}
+
+impl std::error::Error for YamlParserStatus {}
+
 macro_rules! define_element_nvds_yaml_ext_method {
     ($method:ident) => {
-        fn $method(&self, cfg_file_path: &str, group: &str) -> YamlParserStatus;
+        fn $method(&self, cfg_file_path: &str, group: &str) -> Result<(), YamlParserStatus>;
     };
 }
 
 macro_rules! impl_element_nvds_yaml_ext_method {
     ($method:ident) => {
-        fn $method(&self, cfg_file_path: &str, group: &str) -> YamlParserStatus {
+        fn $method(&self, cfg_file_path: &str, group: &str) -> Result<(), YamlParserStatus> {
             unsafe {
                 let cfg_file_path = GString::from(cfg_file_path);
                 let group = GString::from(group);
-                std::mem::transmute(nvidia_deepstream_sys::$method(
+                std::mem::transmute::<_, YamlParserStatus>(nvidia_deepstream_sys::$method(
                     self.as_ptr() as _,
                     cfg_file_path.as_ptr() as _,
                     group.as_ptr() as _,
                 ))
+                .into_result()
             }
         }
     };